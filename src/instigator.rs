@@ -21,11 +21,7 @@ use crate::{
         OAMStatus,
     },
     trait_manager::TraitManager,
-    workload_type::{
-        self, CoreWorkloadType, ExtendedWorkloadType, ReplicatedServer, ReplicatedTask,
-        ReplicatedWorker, SingletonServer, SingletonTask, SingletonWorker, WorkloadMetadata,
-        WorkloadType, OAM_API_VERSION,
-    },
+    workload_type::{self, ExtendedWorkloadType, WorkloadMetadata, WorkloadType, OAM_API_VERSION},
 };
 
 pub const CONFIG_GROUP: &str = "core.oam.dev";
@@ -378,6 +374,9 @@ impl Instigator {
                 comp_def.spec.parameters.clone(),
                 resolve_values(child, vec![])?,
             )?;
+            for warning in comp_def.spec.validate_instance(params.clone())? {
+                warn!("component {}: {}", component.component_name, warning);
+            }
 
             let inst_name = component.instance_name.clone();
             let new_owner_ref =
@@ -621,56 +620,23 @@ impl Instigator {
         comp: &KubeComponent,
         meta: WorkloadMetadata,
     ) -> Result<Box<dyn WorkloadType>, Error> {
+        if workload_type::is_core_workload_type(comp.spec.workload_type.as_str()) {
+            return Ok(Box::new(workload_type::core_workload_type(meta)?));
+        }
         match comp.spec.workload_type.as_str() {
-            workload_type::SERVER_NAME => {
-                let rs = ReplicatedServer { meta };
-                let workload = CoreWorkloadType::ReplicatedServerType(rs);
-                Ok(Box::new(workload))
-            }
-            workload_type::SINGLETON_SERVER_NAME => {
-                let sing = SingletonServer { meta };
-                Ok(Box::new(CoreWorkloadType::SingletonServerType(sing)))
-            }
-            workload_type::SINGLETON_TASK_NAME => {
-                let task = SingletonTask { meta };
-                Ok(Box::new(CoreWorkloadType::SingletonTaskType(task)))
-            }
-            workload_type::TASK_NAME => {
-                let task = ReplicatedTask {
-                    meta,
-                    replica_count: Some(1), // Every(1) needs Some(1) to love.
-                };
-                Ok(Box::new(CoreWorkloadType::ReplicatedTaskType(task)))
-            }
-            workload_type::SINGLETON_WORKER => {
-                let wrkr = SingletonWorker { meta };
-                Ok(Box::new(CoreWorkloadType::SingletonWorkerType(wrkr)))
-            }
-            workload_type::WORKER_NAME => {
-                let worker = ReplicatedWorker {
-                    meta,
-                    replica_count: Some(1), // Every(1) needs Some(1) to love.
-                };
-                Ok(Box::new(CoreWorkloadType::ReplicatedWorkerType(worker)))
-            }
             workload_type::extended_workload::openfaas::OPENFAAS => {
                 let openfaas = workload_type::extended_workload::openfaas::OpenFaaS { meta };
                 let workload = ExtendedWorkloadType::OpenFaaS(openfaas);
                 Ok(Box::new(workload))
             }
-            _ => {
-                match workload_type::extended_workload::others::Others::new(
-                    meta,
-                    comp.spec.workload_type.as_str(),
-                ) {
-                    Err(err) => Err(format_err!(
-                        "workloadType {} is unknown, {}",
-                        comp.spec.workload_type,
-                        err
-                    )),
-                    Ok(other) => Ok(Box::new(other)),
-                }
-            }
+            _ => match workload_type::extended_workload::others::Others::new(meta) {
+                Err(err) => Err(format_err!(
+                    "workloadType {} is unknown, {}",
+                    comp.spec.workload_type,
+                    err
+                )),
+                Ok(other) => Ok(Box::new(other)),
+            },
         }
     }
 
@@ -30,6 +30,7 @@ fn test_record_ann() {
             parameter_values: None,
             traits: None,
             application_scopes: None,
+            depends_on: vec![],
         },
     };
     let cr2 = ComponentRecord {
@@ -40,6 +41,7 @@ fn test_record_ann() {
             parameter_values: None,
             traits: None,
             application_scopes: None,
+            depends_on: vec![],
         },
     };
     one.insert("comp1".to_string(), cr.clone());
@@ -64,6 +66,7 @@ fn test_check_diff() {
             parameter_values: None,
             traits: None,
             application_scopes: None,
+            depends_on: vec![],
         },
     };
     let old_record = ComponentRecord {
@@ -74,6 +77,7 @@ fn test_check_diff() {
             parameter_values: None,
             traits: None,
             application_scopes: None,
+            depends_on: vec![],
         },
     };
 
@@ -88,6 +92,7 @@ fn test_check_diff() {
             parameter_values: None,
             traits: None,
             application_scopes: None,
+            depends_on: vec![],
         },
     };
     assert_eq!(check_diff(Some(new_record2), &old_record), true);
@@ -99,6 +104,7 @@ fn test_check_diff() {
             parameter_values: Some(vec![]),
             traits: None,
             application_scopes: None,
+            depends_on: vec![],
         },
     };
     assert_eq!(check_diff(Some(new_record3), &old_record), true);
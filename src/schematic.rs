@@ -1,19 +1,30 @@
 use failure::err_msg;
-use std::collections::BTreeMap;
+use log::warn;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::instigator::CONFIG_GROUP;
+use crate::schematic::component::Component;
+use crate::schematic::configuration::ComponentConfiguration;
+use crate::schematic::parameter::{resolve_parameters, resolve_values};
+use crate::workload_type::render_all;
 
 pub mod component;
 pub mod component_instance;
 pub mod configuration;
+pub mod migration;
 pub mod parameter;
+pub mod quantity;
 pub mod scopes;
 pub mod traits;
 pub mod variable;
 
 #[cfg(test)]
-mod component_test;
+pub(crate) mod component_test;
 #[cfg(test)]
 mod configuration_test;
 #[cfg(test)]
+mod migration_test;
+#[cfg(test)]
 mod parameter_test;
 #[cfg(test)]
 mod traits_test;
@@ -21,7 +32,116 @@ mod traits_test;
 /// Application defines an OAM application
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct Application {}
+pub struct Application {
+    /// The component instances that make up the application.
+    #[serde(default)]
+    pub components: Vec<ComponentConfiguration>,
+}
+
+impl Application {
+    /// Render the Kubernetes objects for every component instance of the application.
+    ///
+    /// Each instance is resolved against the component of the same name, using its own
+    /// parameter values, and its traits are applied to the rendered workload. Objects are
+    /// returned instance by instance, in the order the instances are listed, with the
+    /// objects created by traits after the workload's own.
+    pub fn render(
+        &self,
+        components: &HashMap<String, Component>,
+    ) -> Result<Vec<serde_json::Value>, failure::Error> {
+        let mut objects = vec![];
+        for instance in self.components.iter() {
+            let component = components.get(&instance.component_name).ok_or_else(|| {
+                format_err!(
+                    "component instance {} refers to unknown component {}",
+                    instance.instance_name,
+                    instance.component_name
+                )
+            })?;
+            let values = resolve_values(
+                instance.parameter_values.clone().unwrap_or_default(),
+                vec![],
+            )?;
+            let params = resolve_parameters(component.parameters.clone(), values)?;
+            for warning in component.validate_instance(params.clone())? {
+                warn!("component instance {}: {}", instance.instance_name, warning);
+            }
+            let mut workload = render_all(component, instance.instance_name.as_str(), params)?;
+            component.apply_instance_traits(&mut workload, instance)?;
+            objects.extend(workload);
+        }
+        Ok(objects)
+    }
+
+    /// render_order lists the instance names in an order that creates every instance after
+    /// the instances it depends on.
+    ///
+    /// Instances that do not depend on each other keep the order they are listed in. A
+    /// dependency on an instance the application does not list, or a dependency cycle,
+    /// is an error.
+    pub fn render_order(&self) -> Result<Vec<String>, failure::Error> {
+        let names: Vec<&str> = self
+            .components
+            .iter()
+            .map(|c| c.instance_name.as_str())
+            .collect();
+        for instance in self.components.iter() {
+            if let Some(dep) = instance
+                .depends_on
+                .iter()
+                .find(|d| !names.contains(&d.as_str()))
+            {
+                return Err(format_err!(
+                    "component instance {} depends on unknown instance {}",
+                    instance.instance_name,
+                    dep
+                ));
+            }
+        }
+        let mut order: Vec<String> = vec![];
+        let mut remaining: Vec<&ComponentConfiguration> = self.components.iter().collect();
+        while !remaining.is_empty() {
+            let ready = remaining
+                .iter()
+                .position(|c| c.depends_on.iter().all(|d| order.contains(d)));
+            match ready {
+                Some(i) => order.push(remaining.remove(i).instance_name.clone()),
+                None => {
+                    return Err(format_err!(
+                        "component instances form a dependency cycle: {}",
+                        dependency_cycle(&remaining).join(" -> ")
+                    ))
+                }
+            }
+        }
+        Ok(order)
+    }
+}
+
+/// Find a dependency cycle among instances that each depend on another of them.
+///
+/// The cycle is returned as the instance names along it, starting and ending with the
+/// same instance.
+fn dependency_cycle(instances: &[&ComponentConfiguration]) -> Vec<String> {
+    let mut path: Vec<&str> = vec![];
+    let mut current = instances[0];
+    loop {
+        if let Some(start) = path.iter().position(|n| *n == current.instance_name) {
+            let mut cycle: Vec<String> = path[start..].iter().map(|n| n.to_string()).collect();
+            cycle.push(current.instance_name.clone());
+            return cycle;
+        }
+        path.push(current.instance_name.as_str());
+        current = match current
+            .depends_on
+            .iter()
+            .find_map(|d| instances.iter().find(|i| &i.instance_name == d))
+        {
+            Some(next) => *next,
+            None => return path.iter().map(|n| n.to_string()).collect(),
+        };
+    }
+}
 
 // TODO: This part is not specified in the spec b/c it is considered a runtime
 // detail of Kubernetes. Need to fill this in as we go.
@@ -81,6 +201,16 @@ impl GroupVersionKind {
             kind: kind.into(),
         }
     }
+
+    /// Whether this is one of the core OAM types, whose group is `core.oam.dev`.
+    pub fn is_core_oam(&self) -> bool {
+        self.group == CONFIG_GROUP
+    }
+
+    /// The API version, `group/version`, as it appears in a manifest's `apiVersion`.
+    pub fn api_version(&self) -> String {
+        format!("{}/{}", self.group, self.version)
+    }
 }
 impl std::str::FromStr for GroupVersionKind {
     type Err = failure::Error;
@@ -1,24 +1,166 @@
+use failure::Error;
+use flate2::read::GzDecoder;
+use k8s_openapi::api::apps::v1 as apps;
 use k8s_openapi::api::core::v1 as core;
+use k8s_openapi::api::policy::v1beta1 as policy;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as meta;
 use k8s_openapi::apimachinery::pkg::{api::resource::Quantity, util::intstr::IntOrString};
-use log::info;
+use k8s_openapi::ByteString;
+use log::{error, info};
+use regex::Regex;
 use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::io::Read;
 use std::path::Path;
 
 use crate::schematic::parameter::{
-    resolve_value, resolve_value_string, ParameterList, ParameterType,
+    resolve_parameters, resolve_value, resolve_value_string, validate_parameters, ParameterList,
+    ParameterType,
+};
+use crate::schematic::quantity;
+use crate::schematic::{configuration::ComponentConfiguration, traits::TraitBinding};
+use crate::schematic::{GroupVersionKind, Status};
+use crate::trait_manager::TraitManager;
+use crate::workload_type::{
+    instance_labels, is_core_workload_type, render_all, workload_info, ParamMap, OAM_API_VERSION,
+    REPLICABLE_TASK_NAME, SCHEDULED_TASK_NAME, SERVER_NAME, TASK_NAME, WORKER_NAME,
 };
-use crate::workload_type::ParamMap;
 
 /// The default workload type if none is present.
 pub const DEFAULT_WORKLOAD_TYPE: &str = "core.oam.dev/v1alpha1.Singleton";
 
+/// The image pull policies Kubernetes accepts for a container.
+const IMAGE_PULL_POLICIES: &[&str] = &["Always", "IfNotPresent", "Never"];
+
+/// The mount propagation modes Kubernetes accepts for a volume mount.
+pub const MOUNT_PROPAGATION_MODES: &[&str] = &["None", "HostToContainer", "Bidirectional"];
+
+/// The pod annotation holding the hash of the component spec the pod was rendered from.
+pub const SPEC_HASH_ANNOTATION: &str = "rudr.io/spec-hash";
+
+/// The kind of the custom resource that holds a component in the cluster.
+pub const COMPONENT_KIND: &str = "ComponentSchematic";
+
+/// The environment variable names whose values `Component::redacted` hides. A `*`
+/// matches any run of characters.
+pub const DEFAULT_REDACTED_ENV_PATTERNS: &[&str] = &["*PASSWORD*", "*SECRET*", "*TOKEN*"];
+
+/// The value that replaces a redacted environment variable value.
+pub const REDACTED_VALUE: &str = "***";
+
+/// The extended resource a container's GPUs are requested as.
+pub const GPU_RESOURCE_NAME: &str = "nvidia.com/gpu";
+
+/// The pod fields, other than labels and annotations, that the downward API supplies to an
+/// environment variable.
+const DOWNWARD_API_FIELDS: &[&str] = &[
+    "metadata.name",
+    "metadata.namespace",
+    "metadata.uid",
+    "spec.nodeName",
+    "spec.serviceAccountName",
+    "status.hostIP",
+    "status.podIP",
+];
+
+/// The container resources that the downward API supplies to an environment variable.
+const DOWNWARD_API_RESOURCES: &[&str] = &[
+    "limits.cpu",
+    "limits.memory",
+    "limits.ephemeral-storage",
+    "requests.cpu",
+    "requests.memory",
+    "requests.ephemeral-storage",
+];
+
+/// The application protocols a port may declare.
+const APP_PROTOCOLS: &[&str] = &["http", "https", "http2", "grpc", "tcp", "tls"];
+
+/// The most ports a single port range may expand into.
+const MAX_PORT_RANGE_SIZE: i32 = 100;
+
+/// The longest port name Kubernetes accepts, an IANA service name.
+const MAX_PORT_NAME_LENGTH: usize = 15;
+
+/// The most ports a component may declare, across its containers, before a warning.
+const MAX_DECLARED_PORTS: usize = 50;
+
+/// The maximum length of a DNS-1123 label.
+const DNS1123_LABEL_MAX_LENGTH: usize = 63;
+
+/// The session affinities Kubernetes supports for a Service.
+const SESSION_AFFINITIES: &[&str] = &["None", "ClientIP"];
+
+/// The Service types Kubernetes supports for a component.
+const SERVICE_TYPES: &[&str] = &["ClusterIP", "NodePort", "LoadBalancer"];
+
+/// The external traffic policies Kubernetes supports for a Service.
+const EXTERNAL_TRAFFIC_POLICIES: &[&str] = &["Cluster", "Local"];
+
+/// The longest ClientIP session affinity timeout Kubernetes accepts.
+const MAX_SESSION_AFFINITY_TIMEOUT_SECONDS: i64 = 86_400;
+
+/// The values Kubernetes accepts for a topology spread constraint's whenUnsatisfiable.
+const WHEN_UNSATISFIABLE: &[&str] = &["DoNotSchedule", "ScheduleAnyway"];
+
+/// The operating systems Kubernetes labels nodes with.
+const OS_TYPES: &[&str] = &["linux", "windows"];
+
+/// Common names for operating systems, mapped to the names Kubernetes labels nodes with.
+const OS_ALIASES: &[(&str, &str)] = &[("win", "windows")];
+
+/// The architectures Kubernetes labels nodes with.
+const ARCHES: &[&str] = &["amd64", "arm64", "arm", "ppc64le", "s390x", "386"];
+
+/// Common names for architectures, mapped to the names Kubernetes labels nodes with.
+const ARCH_ALIASES: &[(&str, &str)] = &[("x86_64", "amd64"), ("aarch64", "arm64")];
+
+/// The workload setting holding the number of replicas of a scalable workload.
+const REPLICA_COUNT_SETTING: &str = "replicaCount";
+
+/// The workload setting holding the update strategy of a Deployment.
+const DEPLOYMENT_STRATEGY_SETTING: &str = "deploymentStrategy";
+
+/// The workload setting holding how long a new pod must be ready to count as available.
+const MIN_READY_SECONDS_SETTING: &str = "minReadySeconds";
+
+/// The workload setting holding how long a Deployment may go without progress.
+const PROGRESS_DEADLINE_SECONDS_SETTING: &str = "progressDeadlineSeconds";
+
+/// The progress deadline Kubernetes gives a Deployment when none is set.
+const DEFAULT_PROGRESS_DEADLINE_SECONDS: i32 = 600;
+
+/// The update strategies Kubernetes supports for a Deployment.
+const DEPLOYMENT_STRATEGY_TYPES: &[&str] = &["RollingUpdate", "Recreate"];
+
+/// The maxSurge and maxUnavailable Kubernetes gives a rolling update when none is set.
+const DEFAULT_ROLLOUT_LIMIT: &str = "25%";
+
+/// The workload setting holding the disruption budget of a replicated workload.
+pub const POD_DISRUPTION_BUDGET_SETTING: &str = "pdb";
+
+/// The workload setting holding the completion mode of a Task's Job.
+const COMPLETION_MODE_SETTING: &str = "completionMode";
+
+/// The completion modes Kubernetes supports for a Job.
+const COMPLETION_MODES: &[&str] = &["NonIndexed", "Indexed"];
+
+/// The workload types rendered as Deployments, which take the rollout settings.
+const DEPLOYMENT_WORKLOAD_TYPES: &[&str] = &[SERVER_NAME, WORKER_NAME];
+
+/// The termination grace period Kubernetes gives a pod when none is set.
+const DEFAULT_TERMINATION_GRACE_PERIOD_SECONDS: i64 = 30;
+
+/// The maximum size of the data Kubernetes accepts in a single ConfigMap.
+const CONFIG_MAP_MAX_BYTES: usize = 1_048_576;
+
 /// Component describes the "spec" of an OAM component schematic.
 ///
 /// The wrapper of the schematic is provided by the Kubernetes library natively.
 ///
 /// In addition to directly deserializing into a component, the from_string() helper
 /// can be used for testing and prototyping.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 #[serde(default)]
 pub struct Component {
@@ -27,9 +169,210 @@ pub struct Component {
     pub arch: Option<String>,
     pub parameters: ParameterList,
     pub containers: Vec<Container>,
+
+    /// Containers that run to completion, one after another, before the main containers start.
+    pub init_containers: Vec<Container>,
+
+    /// Containers that start before the init containers and keep running alongside the main
+    /// containers. They are rendered as init containers with an `Always` restart policy.
+    pub sidecars: Vec<Container>,
     pub workload_settings: Vec<WorkloadSetting>,
+
+    /// Spread replicas across failure domains such as zones or nodes.
+    pub topology_spread_constraints: Option<Vec<TopologySpreadConstraint>>,
+
+    /// Keep replicas from sharing a topology domain.
+    pub anti_affinity: Option<AntiAffinity>,
+
+    /// How long a terminating pod is given to shut down before it is killed.
+    pub termination_grace_period_seconds: Option<i64>,
+
+    /// The resources of containers that omit `resources`. Left unset, they get the defaults
+    /// of the workload type; see `default_resources_for`.
+    pub default_resources: Option<Resources>,
+
+    /// The RuntimeClass to run pods with, such as a gVisor or Kata sandbox.
+    pub runtime_class_name: Option<String>,
+
+    /// The PriorityClass that sets the scheduling priority of pods, and so what they may preempt.
+    pub priority_class_name: Option<String>,
+
+    /// Run pods in the host's network namespace. Container ports are then bound on the
+    /// host, so each is rendered with a host port of the same number.
+    pub host_network: bool,
+
+    /// Run pods in the host's process ID namespace.
+    #[serde(rename = "hostPID")]
+    pub host_pid: bool,
+
+    /// Run pods in the host's IPC namespace.
+    #[serde(rename = "hostIPC")]
+    pub host_ipc: bool,
+
+    /// Whether the service account token is mounted into pods. Left to the service
+    /// account when unset.
+    pub automount_service_account_token: Option<bool>,
 }
 impl Component {
+    /// from_file reads a Component from a JSON or YAML file, chosen by the file extension.
+    ///
+    /// The file may hold either a bare component spec or a whole ComponentSchematic
+    /// manifest, in which case its `spec` is used.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Component, Error> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|e| e.to_str());
+        let is_yaml = match extension {
+            Some("json") => false,
+            Some("yaml") | Some("yml") => true,
+            _ => {
+                return Err(format_err!(
+                    "cannot read component from {}: expected a .json, .yaml or .yml file",
+                    path.display()
+                ))
+            }
+        };
+        let data = std::fs::read_to_string(path)?;
+        let value: serde_json::Value = if is_yaml {
+            yaml_value(data.as_str())?
+        } else {
+            serde_json::from_str(data.as_str())?
+        };
+        Component::from_manifest(value)
+            .map_err(|e| format_err!("cannot read component from {}: {}", path.display(), e))
+    }
+
+    /// from_yaml parses a Component from YAML, holding either a bare component spec or a
+    /// whole ComponentSchematic manifest.
+    ///
+    /// Anchors, aliases and `<<` merge keys are resolved into concrete values before the
+    /// component is deserialized, so a block anchored once can be reused by several
+    /// containers.
+    pub fn from_yaml(data: &str) -> Result<Component, Error> {
+        Component::from_manifest(yaml_value(data)?)
+    }
+
+    /// from_value deserializes a Component from a bare component spec that has already
+    /// been parsed, such as the spec of a watched custom resource.
+    pub fn from_value(value: serde_json::Value) -> Result<Component, Error> {
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Deserialize a bare component spec, or the spec of a ComponentSchematic manifest.
+    fn from_manifest(value: serde_json::Value) -> Result<Component, Error> {
+        let spec = match value.get("kind").and_then(|k| k.as_str()) {
+            Some(COMPONENT_KIND) => value
+                .get("spec")
+                .cloned()
+                .ok_or_else(|| format_err!("{} has no spec", COMPONENT_KIND))?,
+            _ => value,
+        };
+        Ok(serde_json::from_value(spec)?)
+    }
+
+    /// from_crd reads a Component from the custom resource that wraps it in the cluster.
+    ///
+    /// The resource must be a ComponentSchematic with a name and a spec. The name and the
+    /// component held by the spec are returned.
+    pub fn from_crd(value: &serde_json::Value) -> Result<(String, Component), Error> {
+        match value.get("kind").and_then(|k| k.as_str()) {
+            Some(COMPONENT_KIND) => {}
+            kind => {
+                return Err(format_err!(
+                    "expected kind {}, got {}",
+                    COMPONENT_KIND,
+                    kind.unwrap_or("none")
+                ))
+            }
+        }
+        let name = value
+            .pointer("/metadata/name")
+            .and_then(|n| n.as_str())
+            .ok_or_else(|| format_err!("{} has no metadata.name", COMPONENT_KIND))?;
+        let spec = value
+            .get("spec")
+            .cloned()
+            .ok_or_else(|| format_err!("{} {} has no spec", COMPONENT_KIND, name))?;
+        let component = serde_json::from_value(spec)
+            .map_err(|e| format_err!("{} {}: {}", COMPONENT_KIND, name, e))?;
+        Ok((name.to_string(), component))
+    }
+
+    /// to_crd wraps the component in the custom resource that holds it in the cluster.
+    ///
+    /// It is the inverse of `from_crd`. A status of None is serialized as null.
+    pub fn to_crd(&self, name: &str, status: &Status) -> serde_json::Value {
+        serde_json::json!({
+            "apiVersion": OAM_API_VERSION,
+            "kind": COMPONENT_KIND,
+            "metadata": {
+                "name": name
+            },
+            "spec": self,
+            "status": status
+        })
+    }
+
+    /// canonical_json serializes the whole component as compact JSON with every object's
+    /// keys sorted, so that equal components always give the same bytes, for hashing or
+    /// signing.
+    pub fn canonical_json(&self) -> String {
+        let value = serde_json::to_value(self).unwrap_or_default();
+        serde_json::to_string(&sort_keys(value)).unwrap_or_default()
+    }
+
+    /// spec_hash returns a stable hash of the fields that affect how the component renders.
+    ///
+    /// Only the containers, init containers and sidecars, with their resources, ports, and
+    /// environment, and the component's default resources are hashed, so the hash changes
+    /// when a rollout is needed and stays the same when an identical component is
+    /// serialized again. Pod-level fields, such as hostNetwork, runtimeClassName and the
+    /// topology spread constraints, are not hashed.
+    pub fn spec_hash(&self) -> String {
+        // Fields added since the hash was introduced are only hashed when they are set, so
+        // that upgrading does not change the hash, and roll out, every existing component.
+        // Omitted resources are hashed as the defaults they used to deserialize to.
+        let containers: Vec<Container> = self
+            .containers
+            .iter()
+            .map(|c| Container {
+                resources: Some(c.resources().clone()),
+                ..c.clone()
+            })
+            .collect();
+        let mut data = serde_json::to_vec(&containers).unwrap_or_default();
+        if !self.init_containers.is_empty() {
+            data.extend(
+                serde_json::to_vec(&("initContainers", &self.init_containers)).unwrap_or_default(),
+            );
+        }
+        if !self.sidecars.is_empty() {
+            data.extend(serde_json::to_vec(&("sidecars", &self.sidecars)).unwrap_or_default());
+        }
+        if let Some(resources) = &self.default_resources {
+            data.extend(serde_json::to_vec(&("defaultResources", resources)).unwrap_or_default());
+        }
+        // FNV-1a is stable across Rust releases, unlike the standard library's hasher.
+        let hash = data.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        format!("{:016x}", hash)
+    }
+
+    /// workload_gvk parses the workload type into its group, version, and kind.
+    pub fn workload_gvk(&self) -> Result<GroupVersionKind, Error> {
+        self.workload_type.parse()
+    }
+
+    /// listening_ports returns every container port, in declaration order, with port
+    /// ranges expanded into their individual ports.
+    pub fn listening_ports(&self) -> Vec<Port> {
+        self.containers
+            .iter()
+            .flat_map(|c| c.ports.iter())
+            .flat_map(|p| p.expand())
+            .collect()
+    }
+
     /// listening_port returns the first container port listed.
     pub fn listening_port(&self) -> Option<&Port> {
         // Iterate through each container, and if any one contains a port,
@@ -39,12 +382,37 @@ impl Component {
             .find_map(|e| e.ports.iter().find_map(Some))
     }
 
+    /// effective_os_arch returns the OS type and architecture as Kubernetes labels nodes
+    /// with them.
+    ///
+    /// Values are matched ignoring case, and common aliases, such as `x86_64` for `amd64`,
+    /// are mapped to the Kubernetes name. Any other value is an error, since no node
+    /// would match it.
+    pub fn effective_os_arch(&self) -> Result<(Option<String>, Option<String>), Error> {
+        let os = self
+            .os_type
+            .as_ref()
+            .map(|os| normalize_alias("osType", os, OS_TYPES, OS_ALIASES))
+            .transpose()?;
+        let arch = self
+            .arch
+            .as_ref()
+            .map(|arch| normalize_alias("arch", arch, ARCHES, ARCH_ALIASES))
+            .transpose()?;
+        Ok((os, arch))
+    }
+
     pub fn to_node_selector(&self) -> Option<BTreeMap<String, String>> {
+        // Unknown values are kept as they are, so the pod is not scheduled onto the wrong node.
+        let (os, arch) = self.effective_os_arch().unwrap_or_else(|e| {
+            error!("{}", e);
+            (self.os_type.clone(), self.arch.clone())
+        });
         let mut selector = BTreeMap::new();
-        if let Some(os) = self.os_type.clone() {
+        if let Some(os) = os {
             selector.insert("kubernetes.io/os".to_string(), os);
         }
-        if let Some(arch) = self.arch.clone() {
+        if let Some(arch) = arch {
             selector.insert("kubernetes.io/arch".to_string(), arch);
         }
         if selector.is_empty() {
@@ -54,12 +422,27 @@ impl Component {
     }
 
     /// to_pod_spec generates a pod specification.
-    pub fn to_pod_spec(&self, param_vals: ParamMap) -> core::PodSpec {
-        let containers = self.to_containers(param_vals);
-        let image_pull_secrets = Some(self.image_pull_secrets());
+    ///
+    /// The containers, init containers and sidecars keep the order they are declared in,
+    /// and the volumes are sorted by name, so the same component always renders the same
+    /// pod spec. Empty lists are left unset, as the API server would return them.
+    pub fn to_pod_spec(&self, param_vals: ParamMap) -> Result<core::PodSpec, Error> {
+        let mut containers = self.to_containers(param_vals.clone())?;
+        let mut init_containers = self.to_init_containers(param_vals)?;
+        if self.host_network {
+            // Kubernetes requires the host port of a host network pod to match its port.
+            let ports = containers
+                .iter_mut()
+                .chain(init_containers.iter_mut().flatten())
+                .flat_map(|c| c.ports.iter_mut().flatten());
+            for port in ports {
+                port.host_port = Some(port.container_port);
+            }
+        }
+        let image_pull_secrets = non_empty(self.image_pull_secrets());
         let node_selector = self.to_node_selector();
         let mut vols = vec![];
-        for container in self.containers.iter() {
+        for container in self.all_containers() {
             for (i, _conf) in container
                 .config
                 .clone()
@@ -77,12 +460,28 @@ impl Component {
                 });
             }
             container
-                .resources
+                .resources()
                 .volumes
                 .clone()
                 .unwrap_or_else(|| vec![])
                 .iter()
                 .for_each(|v| {
+                    // Containers sharing a volume each mount it, but it is declared once.
+                    if vols.iter().any(|existing| existing.name == v.name) {
+                        return;
+                    }
+                    // Inline content is carried in its own ConfigMap.
+                    if v.inline_content.is_some() {
+                        vols.push(core::Volume {
+                            name: v.name.clone(),
+                            config_map: Some(core::ConfigMapVolumeSource {
+                                name: Some(v.config_map_name(container.name.as_str())),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        });
+                        return;
+                    }
                     // Fill out both the PVC and the EmptyDir fields at the same time.
                     let mut pvc: Option<core::PersistentVolumeClaimVolumeSource> = None;
                     let empty_dir = if v.disk.as_ref().map_or(false, |d| d.ephemeral) {
@@ -91,9 +490,16 @@ impl Component {
                             ..Default::default()
                         })
                     } else {
+                        // A shared volume is only read-only if every container mounts it
+                        // read-only; each mount still sets its own access mode.
+                        let read_only = self
+                            .all_containers()
+                            .flat_map(|c| c.resources().volumes.iter().flatten())
+                            .filter(|u| u.name == v.name)
+                            .all(|u| u.access_mode == AccessMode::RO);
                         pvc = Some(core::PersistentVolumeClaimVolumeSource {
                             claim_name: v.name.clone(),
-                            read_only: Some(v.access_mode == AccessMode::RO),
+                            read_only: Some(read_only),
                         });
                         None
                     };
@@ -107,13 +513,157 @@ impl Component {
                     });
                 })
         }
-        let volumes = Some(vols);
-        core::PodSpec {
+        // Sort the volumes so that reordering the containers' volumes does not change the
+        // pod spec, and roll out the pods again.
+        vols.sort_by(|a, b| a.name.cmp(&b.name));
+        let volumes = non_empty(vols);
+        Ok(core::PodSpec {
             containers,
+            init_containers,
             image_pull_secrets,
             node_selector,
             volumes,
+            termination_grace_period_seconds: self.termination_grace_period_seconds,
+            runtime_class_name: self.runtime_class_name.clone(),
+            priority_class_name: self.priority_class_name.clone(),
+            host_network: if self.host_network { Some(true) } else { None },
+            host_pid: if self.host_pid { Some(true) } else { None },
+            host_ipc: if self.host_ipc { Some(true) } else { None },
+            automount_service_account_token: self.automount_service_account_token,
             ..Default::default()
+        })
+    }
+
+    /// to_pod_spec_with_report generates a pod specification, along with a report of what
+    /// the conversion dropped or assumed.
+    ///
+    /// The pod specification is the one `to_pod_spec` generates.
+    pub fn to_pod_spec_with_report(
+        &self,
+        param_vals: ParamMap,
+    ) -> Result<(core::PodSpec, ConversionReport), Error> {
+        let mut report = ConversionReport::default();
+        for c in self.all_containers() {
+            if let Some(gpu) = c
+                .resources()
+                .gpu
+                .as_ref()
+                .filter(|g| g.required.fract() > 0.0)
+            {
+                report.warn(format!(
+                    "container {}: GPU request of {} is rounded up to {}; {} cannot be fractional",
+                    c.name,
+                    gpu.required,
+                    gpu.required.ceil(),
+                    GPU_RESOURCE_NAME
+                ));
+            }
+            for r in c.resources().extended.iter().flatten() {
+                report.warn(format!(
+                    "container {}: extended resource {} is not rendered",
+                    c.name, r.name
+                ));
+            }
+            // Kubernetes only expands references to variables declared earlier.
+            let mut declared: Vec<&str> = vec![];
+            for e in c.env.iter() {
+                let mut env_report = ConversionReport::default();
+                match e.resolve(&param_vals, &mut env_report) {
+                    Ok(var) => {
+                        let value = var.value.unwrap_or_default();
+                        for reference in env_references(&value) {
+                            if !declared.contains(&reference.as_str()) {
+                                env_report.warn(format!(
+                                    "environment variable {} refers to $({}), which is not declared before it, so it is not expanded",
+                                    e.name, reference
+                                ));
+                            }
+                        }
+                    }
+                    Err(err) => env_report.warn(err.to_string()),
+                }
+                for warning in env_report.warnings {
+                    report.warn(format!("container {}: {}", c.name, warning));
+                }
+                declared.push(e.name.as_str());
+            }
+        }
+        Ok((self.to_pod_spec(param_vals)?, report))
+    }
+
+    /// pod_spec_matches reports whether a live pod spec is the one `to_pod_spec` renders,
+    /// so that a reconciler can skip an update that would change nothing.
+    ///
+    /// See `pod_spec_diff` for which differences count.
+    pub fn pod_spec_matches(
+        &self,
+        live: &core::PodSpec,
+        param_vals: ParamMap,
+    ) -> Result<bool, Error> {
+        Ok(self.pod_spec_diff(live, param_vals)?.is_empty())
+    }
+
+    /// pod_spec_diff lists the fields in which a live pod spec differs from the one
+    /// `to_pod_spec` renders, as JSON pointers such as `/containers/0/image`.
+    ///
+    /// Fields the rendered spec leaves unset are ignored, since Kubernetes fills them in
+    /// with defaults such as `dnsPolicy` or a container's `terminationMessagePath`. Lists
+    /// must have the same length, and their items are compared in order. Resource
+    /// quantities are compared by value, so `0.5` matches `500m`.
+    pub fn pod_spec_diff(
+        &self,
+        live: &core::PodSpec,
+        param_vals: ParamMap,
+    ) -> Result<Vec<String>, Error> {
+        let mut desired = serde_json::to_value(self.to_pod_spec(param_vals)?)?;
+        let mut live = serde_json::to_value(live)?;
+        strip_nulls(&mut desired);
+        strip_nulls(&mut live);
+        let mut diffs = vec![];
+        diff_defaulted(&desired, &live, String::new(), &mut diffs);
+        Ok(diffs)
+    }
+
+    /// standard_labels returns the labels that identify the pods of a component instance.
+    ///
+    /// Outside of an application configuration the instance name stands in for the
+    /// configuration name, as it does in `render_all`.
+    pub fn standard_labels(instance_name: &str) -> BTreeMap<String, String> {
+        instance_labels(instance_name, instance_name)
+    }
+
+    /// to_pod_template_spec generates a pod template for an instance of this component.
+    ///
+    /// The template carries the instance's standard labels and the spec hash annotation.
+    /// Its pod spec uses the default parameter values.
+    pub fn to_pod_template_spec(
+        &self,
+        instance_name: &str,
+    ) -> Result<core::PodTemplateSpec, Error> {
+        let params = resolve_parameters(self.parameters.clone(), BTreeMap::new())
+            .map_err(|e| format_err!("failed to resolve default parameters: {}", e))?;
+        Ok(self.to_pod_template_spec_with(instance_name, self.to_pod_spec(params)?))
+    }
+
+    /// to_pod_template_spec_with wraps a pod spec in the pod template of an instance.
+    ///
+    /// Workload types that tailor the pod spec, for example with a restart policy, render
+    /// it themselves and use this to attach the template metadata.
+    pub fn to_pod_template_spec_with(
+        &self,
+        instance_name: &str,
+        pod_spec: core::PodSpec,
+    ) -> core::PodTemplateSpec {
+        let mut annotations = BTreeMap::new();
+        annotations.insert(SPEC_HASH_ANNOTATION.to_string(), self.spec_hash());
+        core::PodTemplateSpec {
+            metadata: Some(meta::ObjectMeta {
+                name: Some(instance_name.to_string()),
+                labels: Some(Component::standard_labels(instance_name)),
+                annotations: Some(annotations),
+                ..Default::default()
+            }),
+            spec: Some(pod_spec),
         }
     }
 
@@ -121,10 +671,10 @@ impl Component {
         &self,
         param_vals: ParamMap,
         restart_policy: String,
-    ) -> core::PodSpec {
-        let mut pod_spec = self.to_pod_spec(param_vals);
+    ) -> Result<core::PodSpec, Error> {
+        let mut pod_spec = self.to_pod_spec(param_vals)?;
         pod_spec.restart_policy = Some(restart_policy);
-        pod_spec
+        Ok(pod_spec)
     }
 
     pub fn evaluate_configs(
@@ -132,7 +682,7 @@ impl Component {
         resolved_vals: ParamMap,
     ) -> BTreeMap<String, BTreeMap<String, String>> {
         let mut configs: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
-        for container in self.containers.iter() {
+        for container in self.all_containers() {
             for (i, conf) in container
                 .config
                 .clone()
@@ -155,35 +705,233 @@ impl Component {
         configs
     }
 
-    pub fn to_containers(&self, resolved_vals: ParamMap) -> Vec<core::Container> {
+    pub fn to_containers(&self, resolved_vals: ParamMap) -> Result<Vec<core::Container>, Error> {
         self.containers
             .iter()
-            .map(|c| core::Container {
-                name: c.name.clone(),
-                image: Some(c.image.clone()),
-                image_pull_policy: Some("Always".to_string()),
-                resources: Some(c.resources.to_resource_requirements()),
-                ports: Some(c.ports.iter().map(|p| p.to_container_port()).collect()),
-                command: c.cmd.clone(),
-                args: c.args.clone(),
-                env: Some(
-                    c.env
-                        .iter()
-                        .map(|e| e.to_env_var(resolved_vals.clone()))
-                        .collect(),
-                ),
-
-                volume_mounts: c.volume_mounts(),
-                liveness_probe: c.liveness_probe.clone().and_then(|p| Some(p.to_probe())),
-                readiness_probe: c.readiness_probe.clone().and_then(|p| Some(p.to_probe())),
-                ..Default::default()
-            })
+            .map(|c| self.with_default_resources(c).to_container(&resolved_vals))
             .collect()
     }
 
-    pub fn image_pull_secrets(&self) -> Vec<core::LocalObjectReference> {
+    /// to_init_containers renders the sidecars followed by the init containers, or None if
+    /// there are neither.
+    ///
+    /// The API version Rudr is built against has no restart policy for containers, so the
+    /// `Always` policy that makes a sidecar keep running is added when the workload is
+    /// serialized.
+    pub fn to_init_containers(
+        &self,
+        resolved_vals: ParamMap,
+    ) -> Result<Option<Vec<core::Container>>, Error> {
+        if self.sidecars.is_empty() && self.init_containers.is_empty() {
+            return Ok(None);
+        }
+        self.sidecars
+            .iter()
+            .chain(self.init_containers.iter())
+            .map(|c| self.with_default_resources(c).to_container(&resolved_vals))
+            .collect::<Result<Vec<_>, Error>>()
+            .map(Some)
+    }
+
+    /// with_default_resources gives a container that omits `resources` the component's
+    /// `defaultResources`, or the defaults of its workload type if it has none.
+    fn with_default_resources(&self, c: &Container) -> Container {
+        let mut c = c.clone();
+        if c.resources.is_none() {
+            c.resources = Some(
+                self.default_resources
+                    .clone()
+                    .unwrap_or_else(|| default_resources_for(self.workload_type.as_str())),
+            );
+        }
+        c
+    }
+
+    /// sidecar_names lists the names of the sidecars, in declaration order.
+    pub fn sidecar_names(&self) -> Vec<&str> {
+        self.sidecars.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    /// container_images lists the image of every container, main containers first, then
+    /// init containers and sidecars, in declaration order. Duplicates are kept.
+    pub fn container_images(&self) -> Vec<String> {
+        self.all_containers().map(|c| c.image.clone()).collect()
+    }
+
+    /// require_digest_pinned checks that every image is referenced by a sha256 digest.
+    ///
+    /// Tags, including `latest`, can be moved to point at different content, so any image
+    /// referenced without a digest is listed in the error.
+    pub fn require_digest_pinned(&self) -> Result<(), Error> {
+        lazy_static! {
+            static ref DIGEST: Regex = Regex::new(r"@sha256:[0-9a-f]{64}$").unwrap();
+        }
+        let unpinned: Vec<String> = self
+            .container_images()
+            .into_iter()
+            .filter(|image| !DIGEST.is_match(image))
+            .collect();
+        if unpinned.is_empty() {
+            return Ok(());
+        }
+        Err(format_err!(
+            "images must be pinned by digest (@sha256:...): {}",
+            unpinned.join(", ")
+        ))
+    }
+
+    /// with_graceful_drain gives load balancers time to stop routing to a terminating pod.
+    ///
+    /// Every container without a preStop hook sleeps for the given number of seconds
+    /// before it is stopped, and the termination grace period is raised to at least five
+    /// seconds more than that. Existing preStop hooks are left alone.
+    pub fn with_graceful_drain(mut self, seconds: u64) -> Self {
+        for c in self.containers.iter_mut() {
+            let lifecycle = c.lifecycle.get_or_insert_with(Lifecycle::default);
+            if lifecycle.pre_stop.is_none() {
+                lifecycle.pre_stop = Some(LifecycleHandler {
+                    exec: Some(Exec {
+                        command: vec![
+                            "sh".to_string(),
+                            "-c".to_string(),
+                            format!("sleep {}", seconds),
+                        ],
+                    }),
+                    ..Default::default()
+                });
+            }
+        }
+        let minimum = (seconds.min(i64::max_value() as u64) as i64).saturating_add(5);
+        let current = self
+            .termination_grace_period_seconds
+            .unwrap_or(DEFAULT_TERMINATION_GRACE_PERIOD_SECONDS);
+        self.termination_grace_period_seconds = Some(current.max(minimum));
+        self
+    }
+
+    /// merge_env adds common environment variables to every container.
+    ///
+    /// A container that already sets a variable of the same name keeps its own value.
+    pub fn merge_env(&mut self, common: &[Env]) {
+        for c in self.containers_mut() {
+            for env in common.iter() {
+                if !c.env.iter().any(|e| e.name == env.name) {
+                    c.env.push(env.clone());
+                }
+            }
+        }
+    }
+
+    /// with_container returns a copy of this component with `f` applied to the named container.
+    ///
+    /// Main containers, init containers and sidecars are all searched. The original
+    /// component is left unchanged.
+    pub fn with_container(
+        &self,
+        name: &str,
+        f: impl FnOnce(&mut Container),
+    ) -> Result<Component, Error> {
+        let mut component = self.clone();
+        let container = component
+            .containers_mut()
+            .find(|c| c.name == name)
+            .ok_or_else(|| format_err!("component has no container named {}", name))?;
+        f(container);
+        Ok(component)
+    }
+
+    /// redacted returns a copy of this component that is safe to log, with the values of
+    /// environment variables matching `DEFAULT_REDACTED_ENV_PATTERNS` replaced.
+    pub fn redacted(&self) -> Component {
+        self.redacted_with(DEFAULT_REDACTED_ENV_PATTERNS)
+    }
+
+    /// redacted_with returns a copy of this component with the values of environment
+    /// variables whose names match any of the patterns replaced by `REDACTED_VALUE`.
+    ///
+    /// Patterns are matched against the whole name, ignoring case, and a `*` matches any
+    /// run of characters. Variables taken from a parameter have no value to redact.
+    pub fn redacted_with(&self, patterns: &[&str]) -> Component {
+        let mut component = self.clone();
+        for c in component.containers_mut() {
+            for env in c.env.iter_mut() {
+                if env.value.is_some() && patterns.iter().any(|p| matches_pattern(p, &env.name)) {
+                    env.value = Some(REDACTED_VALUE.to_string());
+                }
+            }
+        }
+        component
+    }
+
+    /// all_containers iterates over the main containers, the init containers and then the
+    /// sidecars.
+    fn all_containers(&self) -> impl Iterator<Item = &Container> {
         self.containers
             .iter()
+            .chain(self.init_containers.iter())
+            .chain(self.sidecars.iter())
+    }
+
+    /// parameter_bindings maps each declared parameter to the paths of the fields that
+    /// consume it, such as `containers[0].env[DB_HOST]` or `workloadSettings[replicaCount]`.
+    ///
+    /// Parameters that nothing consumes map to an empty list.
+    pub fn parameter_bindings(&self) -> BTreeMap<String, Vec<String>> {
+        let mut bindings: BTreeMap<String, Vec<String>> = self
+            .parameters
+            .iter()
+            .map(|p| (p.name.clone(), vec![]))
+            .collect();
+        for (param, path) in self.parameter_references() {
+            if let Some(paths) = bindings.get_mut(param) {
+                paths.push(path);
+            }
+        }
+        bindings
+    }
+
+    /// parameter_references lists every parameter reference in the component, declared
+    /// or not, with the path of the field that makes it.
+    fn parameter_references(&self) -> Vec<(&str, String)> {
+        let sections = [
+            ("containers", &self.containers),
+            ("initContainers", &self.init_containers),
+            ("sidecars", &self.sidecars),
+        ];
+        let mut refs = vec![];
+        for (section, containers) in sections.iter() {
+            for (i, c) in containers.iter().enumerate() {
+                refs.extend(c.parameter_references(format!("{}[{}]", section, i).as_str()));
+            }
+        }
+        for setting in self.workload_settings.iter() {
+            if let Some(param) = setting.from_param.as_ref() {
+                refs.push((
+                    param.as_str(),
+                    format!("workloadSettings[{}]", setting.name),
+                ));
+            }
+        }
+        refs
+    }
+
+    /// containers_mut iterates mutably over the main containers, the init containers and
+    /// then the sidecars, for editing them in place.
+    pub fn containers_mut(&mut self) -> impl Iterator<Item = &mut Container> {
+        self.containers
+            .iter_mut()
+            .chain(self.init_containers.iter_mut())
+            .chain(self.sidecars.iter_mut())
+    }
+
+    /// running_containers iterates over the containers that run for the life of the pod,
+    /// the main containers and then the sidecars.
+    fn running_containers(&self) -> impl Iterator<Item = &Container> {
+        self.containers.iter().chain(self.sidecars.iter())
+    }
+
+    pub fn image_pull_secrets(&self) -> Vec<core::LocalObjectReference> {
+        self.all_containers()
             .filter_map(|c| {
                 info!("Looking for image pull secret");
                 c.image_pull_secret.clone().and_then(|n| {
@@ -194,70 +942,1696 @@ impl Component {
             .collect()
     }
 
-    pub fn get_workload_setting(&self, key: &str) -> Option<&WorkloadSetting> {
-        self.workload_settings
-            .iter()
-            .find(|&item| item.name.eq(key))
+    /// sanitize_name converts a name into a valid DNS-1123 label.
+    ///
+    /// Letters are lowercased, any other invalid character becomes a dash, and the
+    /// result is trimmed of leading and trailing dashes and truncated to 63 characters.
+    pub fn sanitize_name(name: &str) -> String {
+        let replaced: String = name
+            .to_lowercase()
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | '0'..='9' | '-' => c,
+                _ => '-',
+            })
+            .collect();
+        let trimmed: String = replaced
+            .trim_matches('-')
+            .chars()
+            .take(DNS1123_LABEL_MAX_LENGTH)
+            .collect();
+        trimmed.trim_end_matches('-').to_string()
     }
-}
 
-impl Default for Component {
-    fn default() -> Self {
-        Component {
-            workload_type: DEFAULT_WORKLOAD_TYPE.into(),
-            os_type: None,
-            arch: None,
-            parameters: Vec::new(),
-            containers: Vec::new(),
-            workload_settings: Vec::new(),
+    /// validate_name checks that a name is a valid DNS-1123 label.
+    ///
+    /// Kubernetes requires at most 63 characters of lowercase alphanumerics or '-',
+    /// starting and ending with an alphanumeric.
+    pub fn validate_name(name: &str) -> Result<(), Error> {
+        lazy_static! {
+            static ref DNS1123_LABEL: Regex =
+                Regex::new(r"^[a-z0-9]([-a-z0-9]*[a-z0-9])?$").unwrap();
+        }
+        if name.len() > DNS1123_LABEL_MAX_LENGTH {
+            return Err(format_err!(
+                "name {} must be no more than {} characters",
+                name,
+                DNS1123_LABEL_MAX_LENGTH
+            ));
         }
+        if !DNS1123_LABEL.is_match(name) {
+            return Err(format_err!(
+                "name {:?} must consist of lowercase alphanumeric characters or '-', and must start and end with an alphanumeric character",
+                name
+            ));
+        }
+        Ok(())
     }
-}
 
-impl std::str::FromStr for Component {
-    type Err = failure::Error;
+    /// validate checks the component definition for errors that Kubernetes would
+    /// otherwise only report once the rendered objects are submitted.
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_parameters(&self.parameters)?;
+        self.workload_settings_map()?;
+        for (param, path) in self.parameter_references() {
+            if !self.parameters.iter().any(|p| p.name == param) {
+                return Err(format_err!(
+                    "{} refers to parameter {}, which is not declared",
+                    path,
+                    param
+                ));
+            }
+        }
+        self.effective_os_arch()?;
+        for constraint in self.topology_spread_constraints.iter().flatten() {
+            constraint.validate()?;
+        }
+        for c in self.init_containers.iter() {
+            if c.liveness_probe.is_some() || c.readiness_probe.is_some() {
+                return Err(format_err!(
+                    "init container {} cannot have a liveness or readiness probe",
+                    c.name
+                ));
+            }
+            if c.lifecycle.is_some() {
+                return Err(format_err!(
+                    "init container {} cannot have lifecycle hooks",
+                    c.name
+                ));
+            }
+        }
+        self.validate_unique_container_names()?;
+        for c in self.all_containers() {
+            Component::validate_name(c.name.as_str())
+                .map_err(|e| format_err!("invalid container name: {}", e))?;
+            for env in c.env.iter() {
+                env.validate()
+                    .map_err(|e| format_err!("container {}: {}", c.name, e))?;
+            }
+            c.validate_env_names()?;
+            c.validate_image_ref()?;
+            c.validate_image_pull_policy()?;
+            for p in c.ports.iter() {
+                p.validate()
+                    .map_err(|e| format_err!("container {}: {}", c.name, e))?;
+            }
+            for v in c.resources().volumes.iter().flatten() {
+                v.validate_mount()?;
+                if let Some(content) = v.inline_content.as_ref() {
+                    content
+                        .decode()
+                        .map_err(|e| format_err!("volume {}: {}", v.name, e))?;
+                }
+            }
+        }
+        self.validate_shared_volumes()?;
+        self.validate_volume_access_modes()?;
+        self.validate_gpu_with_limits()?;
+        self.validate_resource_limits()
+    }
 
-    /// Parse JSON data into a Component.
-    fn from_str(json_data: &str) -> Result<Self, Self::Err> {
-        let res: Component = serde_json::from_str(json_data)?;
-        Ok(res)
+    /// validate_resource_limits checks that no container's CPU or memory limit is below
+    /// its request, which Kubernetes rejects.
+    pub fn validate_resource_limits(&self) -> Result<(), Error> {
+        for c in self.all_containers() {
+            self.with_default_resources(c)
+                .resources()
+                .validate_limits()
+                .map_err(|e| format_err!("container {}: {}", c.name, e))?;
+        }
+        Ok(())
     }
-}
 
-/// Container describes the container configuration for a Component.
-#[derive(Serialize, Deserialize, Clone, Debug)]
-#[serde(rename_all = "camelCase")]
-pub struct Container {
-    pub name: String,
-    pub image: String,
-    pub image_pull_secret: Option<String>,
+    /// validate_gpu_with_limits checks that every container requiring GPUs renders them as
+    /// a `nvidia.com/gpu` limit.
+    ///
+    /// Kubernetes only accepts an extended resource such as `nvidia.com/gpu` as a limit,
+    /// so a GPU count that does not render as one, such as a negative or non-finite
+    /// count, would be silently dropped.
+    pub fn validate_gpu_with_limits(&self) -> Result<(), Error> {
+        for c in self.all_containers() {
+            if c.resources()
+                .gpu
+                .as_ref()
+                .map_or(true, |g| g.required == 0.0)
+            {
+                continue;
+            }
+            let rendered = c.resources().to_resource_requirements()?;
+            if !rendered
+                .limits
+                .as_ref()
+                .map_or(false, |l| l.contains_key(GPU_RESOURCE_NAME))
+            {
+                return Err(format_err!(
+                    "container {} requires GPUs, but its resources have no {} limit",
+                    c.name,
+                    GPU_RESOURCE_NAME
+                ));
+            }
+        }
+        Ok(())
+    }
 
-    #[serde(default)]
-    pub resources: Resources,
+    /// validate_unique_container_names checks that no two containers share a name, counting
+    /// the init containers and sidecars, which are rendered into the same pod.
+    pub fn validate_unique_container_names(&self) -> Result<(), Error> {
+        let names: Vec<&str> = self.all_containers().map(|c| c.name.as_str()).collect();
+        for (i, name) in names.iter().enumerate() {
+            if names[..i].contains(name) {
+                return Err(format_err!(
+                    "container name {} is used by more than one container",
+                    name
+                ));
+            }
+        }
+        Ok(())
+    }
 
-    pub cmd: Option<Vec<String>>,
-    pub args: Option<Vec<String>>,
+    /// validate_shared_volumes checks volumes whose name is used by more than one container.
+    ///
+    /// Such volumes are rendered as a single pod volume, so every container using the
+    /// name must declare it Shared. Inline content is stored per container and cannot
+    /// be shared, nor mounted twice.
+    fn validate_shared_volumes(&self) -> Result<(), Error> {
+        let mut users: BTreeMap<String, Vec<(&str, Volume)>> = BTreeMap::new();
+        for c in self.all_containers() {
+            for v in c.resources().volumes.clone().unwrap_or_else(|| vec![]) {
+                users
+                    .entry(v.name.clone())
+                    .or_insert_with(|| vec![])
+                    .push((c.name.as_str(), v));
+            }
+        }
+        for (name, users) in users.iter().filter(|(_, users)| users.len() > 1) {
+            let shared = users.iter().any(|(c, _)| *c != users[0].0);
+            for (container, v) in users.iter() {
+                if v.inline_content.is_some() {
+                    return Err(format_err!(
+                        "volume {} cannot be shared by container {} because it has inline content",
+                        name,
+                        container
+                    ));
+                }
+                if shared && v.sharing_policy != SharingPolicy::Shared {
+                    return Err(format_err!(
+                        "volume {} is shared, but container {} does not declare sharingPolicy Shared",
+                        name,
+                        container
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
 
-    #[serde(default)]
-    pub env: Vec<Env>,
+    /// validate_volume_access_modes checks that a container mounting an Exclusive volume
+    /// more than once, at different paths, declares the same access mode each time.
+    ///
+    /// A Shared volume may be mounted read/write by one container and read-only by
+    /// another, but an Exclusive volume is claimed with a single access mode.
+    fn validate_volume_access_modes(&self) -> Result<(), Error> {
+        for c in self.all_containers() {
+            let mut modes: BTreeMap<&str, &AccessMode> = BTreeMap::new();
+            for v in c.resources().volumes.iter().flatten() {
+                if v.sharing_policy != SharingPolicy::Exclusive {
+                    continue;
+                }
+                match modes.get(v.name.as_str()) {
+                    Some(mode) if *mode != &v.access_mode => {
+                        return Err(format_err!(
+                            "volume {} is Exclusive, but container {} mounts it as both {:?} and {:?}",
+                            v.name,
+                            c.name,
+                            mode,
+                            v.access_mode
+                        ));
+                    }
+                    _ => {
+                        modes.insert(v.name.as_str(), &v.access_mode);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 
-    #[serde(default)]
-    pub config: Option<Vec<ConfigFile>>,
+    /// apply_json_patch applies RFC 6902 JSON Patch operations to a copy of this component.
+    ///
+    /// The patched component must still be a valid component; it is validated before
+    /// it is returned.
+    pub fn apply_json_patch(&self, patch: &serde_json::Value) -> Result<Component, Error> {
+        let patch = json_patch::from_value(patch.clone())
+            .map_err(|e| format_err!("invalid JSON patch: {}", e))?;
+        let mut doc = serde_json::to_value(self)?;
+        json_patch::patch(&mut doc, &patch)
+            .map_err(|e| format_err!("failed to apply JSON patch: {}", e))?;
+        let patched: Component = serde_json::from_value(doc)
+            .map_err(|e| format_err!("patched component is invalid: {}", e))?;
+        patched.validate()?;
+        Ok(patched)
+    }
 
-    #[serde(default)]
-    pub ports: Vec<Port>,
+    /// strip_defaults serializes this component, leaving out fields that hold their default value.
+    ///
+    /// The result deserializes back to an equivalent component once `apply_defaults` has
+    /// run, so it can be stored or displayed without the noise of defaulted fields. An
+    /// `osType` is always kept, since leaving it unset places no constraint on scheduling.
+    pub fn strip_defaults(&self) -> serde_json::Value {
+        let mut component = self.clone();
+        component.apply_defaults();
+        let mut value = serde_json::to_value(&component).unwrap_or_default();
+        strip_nulls(&mut value);
+        for key in &["containers", "initContainers", "sidecars"] {
+            for c in array_items(&mut value, key) {
+                strip_container_defaults(c);
+            }
+        }
+        for c in array_items(&mut value, "topologySpreadConstraints") {
+            strip_default_fields(
+                c,
+                serde_json::json!({ "whenUnsatisfiable": default_when_unsatisfiable() }),
+            );
+        }
+        if let Some(anti_affinity) = value.get_mut("antiAffinity") {
+            strip_default_fields(
+                anti_affinity,
+                serde_json::json!({
+                    "mode": AntiAffinityMode::default(),
+                    "topologyKey": default_anti_affinity_topology_key()
+                }),
+            );
+        }
+        let mut defaults = serde_json::to_value(Component::default()).unwrap_or_default();
+        strip_nulls(&mut defaults);
+        strip_default_fields(&mut value, defaults);
+        value
+    }
 
-    pub liveness_probe: Option<HealthProbe>,
-    pub readiness_probe: Option<HealthProbe>,
-}
+    /// apply_trait applies a trait to the objects rendered for an instance of this component.
+    ///
+    /// The instance is named by the workload object, which `render_all` renders first.
+    /// Traits that change the workload, such as the manual scaler, update the objects in
+    /// place, and objects a trait creates, such as an autoscaler, are appended.
+    pub fn apply_trait(
+        &self,
+        objects: &mut Vec<serde_json::Value>,
+        t: &TraitBinding,
+    ) -> Result<(), Error> {
+        self.apply_traits(objects, std::slice::from_ref(t))
+    }
 
-impl Default for Container {
-    fn default() -> Self {
+    /// apply_traits applies traits to the objects rendered for an instance of this
+    /// component, in the order `traits::TRAIT_APPLICATION_ORDER` gives rather than the order
+    /// they are bound in, so that the result does not depend on the binding order.
+    pub fn apply_traits(
+        &self,
+        objects: &mut Vec<serde_json::Value>,
+        traits: &[TraitBinding],
+    ) -> Result<(), Error> {
+        let instance_name = objects
+            .first()
+            .and_then(|o| o.pointer("/metadata/name"))
+            .and_then(|n| n.as_str())
+            .map(|n| n.to_string())
+            .ok_or_else(|| format_err!("cannot apply traits: no workload to apply them to"))?;
+        // Without a component configuration, the instance name stands in for the
+        // component name.
+        let instance = ComponentConfiguration {
+            component_name: instance_name.clone(),
+            instance_name,
+            parameter_values: None,
+            traits: Some(traits.to_vec()),
+            application_scopes: None,
+            depends_on: vec![],
+        };
+        self.apply_instance_traits(objects, &instance)
+    }
+
+    /// apply_instance_traits applies the traits bound to a component instance to the
+    /// objects rendered for it, in the order `traits::TRAIT_APPLICATION_ORDER` gives.
+    pub fn apply_instance_traits(
+        &self,
+        objects: &mut Vec<serde_json::Value>,
+        instance: &ComponentConfiguration,
+    ) -> Result<(), Error> {
+        // Without an application configuration, the instance name stands in for its name.
+        let traits = TraitManager {
+            config_name: instance.instance_name.clone(),
+            instance_name: instance.instance_name.clone(),
+            component: instance.clone(),
+            parent_params: vec![],
+            owner_ref: None,
+            workload_type: self.workload_type.clone(),
+            component_schematic: self.clone(),
+            traits: vec![],
+        };
+        let bindings = instance.traits.clone().unwrap_or_default();
+        for t in crate::schematic::traits::in_application_order(&bindings) {
+            let created = traits.load_trait(t)?.render(objects)?;
+            objects.extend(created);
+        }
+        Ok(())
+    }
+
+    /// render_all_yaml renders the Kubernetes objects for an instance of this component
+    /// as a multi-document YAML string, ready for `kubectl apply -f -`.
+    ///
+    /// Parameters take their default values. Nothing is sent to the cluster.
+    pub fn render_all_yaml(&self, instance_name: &str) -> Result<String, Error> {
+        let params = resolve_parameters(self.parameters.clone(), BTreeMap::new())?;
+        let mut yaml = String::new();
+        for object in render_all(self, instance_name, params)? {
+            let doc = serde_yaml::to_string(&object)?;
+            yaml.push_str("---\n");
+            yaml.push_str(doc.trim_start_matches("---").trim());
+            yaml.push('\n');
+        }
+        Ok(yaml)
+    }
+
+    /// to_inline_config_maps generates a ConfigMap for every volume with inline content.
+    ///
+    /// Each ConfigMap holds a single file, named after the last element of the volume's
+    /// mount path. Only the name is set in the metadata.
+    pub fn to_inline_config_maps(&self) -> Result<Vec<core::ConfigMap>, Error> {
+        let mut config_maps = vec![];
+        for c in self.all_containers() {
+            for v in c.resources().volumes.iter().flatten() {
+                let content = match v.inline_content.as_ref() {
+                    Some(content) => content
+                        .decode()
+                        .map_err(|e| format_err!("volume {}: {}", v.name, e))?,
+                    None => continue,
+                };
+                let mut config_map = core::ConfigMap {
+                    metadata: Some(meta::ObjectMeta {
+                        name: Some(v.config_map_name(c.name.as_str())),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                };
+                // Text goes in data so it stays readable; anything else is binary data.
+                match String::from_utf8(content) {
+                    Ok(text) => {
+                        let mut data = BTreeMap::new();
+                        data.insert(v.file_name(), text);
+                        config_map.data = Some(data);
+                    }
+                    Err(e) => {
+                        let mut binary_data = BTreeMap::new();
+                        binary_data.insert(v.file_name(), ByteString(e.into_bytes()));
+                        config_map.binary_data = Some(binary_data);
+                    }
+                }
+                config_maps.push(config_map);
+            }
+        }
+        Ok(config_maps)
+    }
+
+    /// apply_defaults fills every defaulted field with its concrete default.
+    ///
+    /// Serde leaves many optional fields unset when they are omitted, so a parsed
+    /// component may differ structurally from an equivalent, fully specified one.
+    /// After apply_defaults, the two compare equal.
+    pub fn apply_defaults(&mut self) {
+        if self.workload_type.is_empty() {
+            self.workload_type = DEFAULT_WORKLOAD_TYPE.into();
+        }
+        for c in self.containers.iter_mut() {
+            c.apply_defaults();
+        }
+        for c in self.init_containers.iter_mut() {
+            c.apply_defaults();
+        }
+        for c in self.sidecars.iter_mut() {
+            c.apply_defaults();
+        }
+    }
+
+    /// total_requests sums the CPU and memory requested by every container.
+    ///
+    /// The result is returned as (CPU, memory), normalized to millicores and
+    /// mebibytes (or bytes, if the sum is not a whole number of mebibytes).
+    pub fn total_requests(&self) -> Result<(Quantity, Quantity), Error> {
+        let mut cpu = 0.0;
+        let mut memory = 0.0;
+        for c in self.containers.iter() {
+            let c = self.with_default_resources(c);
+            cpu += c.resources().cpu.required;
+            memory += quantity::parse_memory(c.resources().memory.required.as_str())
+                .map_err(|e| format_err!("container {}: {}", c.name, e))?;
+        }
+        Ok((
+            quantity::cpu_quantity(cpu),
+            quantity::memory_quantity(memory),
+        ))
+    }
+
+    /// container_count counts every container of a pod: the main containers, the init
+    /// containers and the sidecars.
+    pub fn container_count(&self) -> usize {
+        self.all_containers().count()
+    }
+
+    /// total_gpus sums the GPUs required by every container, including the init containers
+    /// and sidecars, since a GPU is never shared between containers.
+    pub fn total_gpus(&self) -> f64 {
+        self.all_containers()
+            .filter_map(|c| c.resources().gpu.as_ref())
+            .map(|gpu| gpu.required)
+            .sum()
+    }
+
+    /// required_capabilities lists the cluster features the component needs beyond those
+    /// every cluster has, sorted and without duplicates.
+    ///
+    /// These are the extended resources its containers request, the persistent volume
+    /// access modes other than ReadWriteOnce that its durable volumes need, and the API
+    /// versions of the objects it renders that a cluster may not serve.
+    pub fn required_capabilities(&self) -> Vec<Capability> {
+        let mut capabilities = vec![];
+        for c in self.all_containers() {
+            if c.resources()
+                .gpu
+                .as_ref()
+                .map_or(false, |g| g.required > 0.0)
+            {
+                capabilities.push(Capability::ExtendedResource(GPU_RESOURCE_NAME.to_string()));
+            }
+            for v in c.resources().volumes.iter().flatten() {
+                let ephemeral = v.disk.as_ref().map_or(false, |d| d.ephemeral);
+                let mode = v.to_access_mode();
+                if !ephemeral && v.inline_content.is_none() && mode != "ReadWriteOnce" {
+                    capabilities.push(Capability::AccessMode(mode));
+                }
+            }
+        }
+        if self.workload_type == SCHEDULED_TASK_NAME {
+            capabilities.push(Capability::ApiVersion("batch/v1beta1".to_string()));
+        }
+        if self
+            .get_workload_setting(POD_DISRUPTION_BUDGET_SETTING)
+            .is_some()
+        {
+            capabilities.push(Capability::ApiVersion("policy/v1beta1".to_string()));
+        }
+        if let Some(gvk) = self.workload_gvk().ok().filter(|gvk| !gvk.is_core_oam()) {
+            capabilities.push(Capability::ApiVersion(gvk.api_version()));
+        }
+        capabilities.sort();
+        capabilities.dedup();
+        capabilities
+    }
+
+    /// validate_instance runs every check of the component against the parameter values of
+    /// an instance, returning the problems that do not stop it from being deployed as
+    /// warnings.
+    pub fn validate_instance(&self, resolved_vals: ParamMap) -> Result<Vec<String>, Error> {
+        self.validate()?;
+        self.validate_probe_params(resolved_vals.clone())?;
+        self.validate_ports(resolved_vals.clone())?;
+        self.validate_service_ports(resolved_vals.clone())?;
+        self.validate_workload_settings(resolved_vals.clone())?;
+        self.validate_service_settings(resolved_vals.clone())?;
+        let mut warnings = self.validate_probe_ports(resolved_vals);
+        warnings.extend(self.validate_port_count());
+        Ok(warnings)
+    }
+
+    /// validate_probe_params checks that every probe setting sourced from a parameter
+    /// resolves to a value that can be used as a probe threshold.
+    pub fn validate_probe_params(&self, resolved_vals: ParamMap) -> Result<(), Error> {
+        for c in self.running_containers() {
+            for probe in c.liveness_probe.iter() {
+                probe
+                    .resolve_thresholds(resolved_vals.clone())?
+                    .validate_liveness()
+                    .map_err(|e| {
+                        format_err!("invalid liveness probe for container {}: {}", c.name, e)
+                    })?;
+            }
+            for probe in c.readiness_probe.iter() {
+                probe
+                    .resolve_thresholds(resolved_vals.clone())?
+                    .validate()
+                    .map_err(|e| {
+                        format_err!("invalid readiness probe for container {}: {}", c.name, e)
+                    })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// validate_ports checks that every container and sidecar port resolves to a port
+    /// number, and that no container declares the same port number twice for one protocol.
+    ///
+    /// A port number may be declared once for each protocol, as DNS does for TCP and UDP.
+    pub fn validate_ports(&self, resolved_vals: ParamMap) -> Result<(), Error> {
+        for c in self.running_containers() {
+            let mut declared: Vec<(i32, PortProtocol)> = vec![];
+            for port in c.ports.iter().flat_map(|p| p.expand()) {
+                let number = port.resolve_port(&resolved_vals)?;
+                if declared.contains(&(number, port.protocol.clone())) {
+                    return Err(format_err!(
+                        "container {} declares port {}/{} more than once",
+                        c.name,
+                        number,
+                        port.protocol.as_str()
+                    ));
+                }
+                declared.push((number, port.protocol));
+            }
+        }
+        Ok(())
+    }
+
+    /// validate_service_ports checks that the ports the component's Service exposes,
+    /// across all of its containers, can be accepted by Kubernetes: each port/protocol
+    /// pair and each name is used once, and every port is named when there is more
+    /// than one.
+    pub fn validate_service_ports(&self, resolved_vals: ParamMap) -> Result<(), Error> {
+        let ports = self.listening_ports();
+        let mut names: Vec<&str> = vec![];
+        let mut declared: Vec<(i32, PortProtocol)> = vec![];
+        for port in ports.iter() {
+            let number = port.resolve_port(&resolved_vals)?;
+            if port.name.is_empty() {
+                if ports.len() > 1 {
+                    return Err(format_err!(
+                        "port {}/{} must be named when the component listens on more than one port",
+                        number,
+                        port.protocol.as_str()
+                    ));
+                }
+            } else if names.contains(&port.name.as_str()) {
+                return Err(format_err!(
+                    "port name {} is used more than once",
+                    port.name
+                ));
+            } else {
+                names.push(port.name.as_str());
+            }
+            if declared.contains(&(number, port.protocol.clone())) {
+                return Err(format_err!(
+                    "port {}/{} is exposed by more than one container",
+                    number,
+                    port.protocol.as_str()
+                ));
+            }
+            declared.push((number, port.protocol.clone()));
+        }
+        Ok(())
+    }
+
+    /// validate_port_count warns when the component declares more than 50 ports across
+    /// its containers, counting each port of a range, which is almost always a mistake.
+    pub fn validate_port_count(&self) -> Vec<String> {
+        let count: usize = self
+            .running_containers()
+            .flat_map(|c| c.ports.iter())
+            .map(|p| p.expand().len())
+            .sum();
+        if count <= MAX_DECLARED_PORTS {
+            return vec![];
+        }
+        vec![format!(
+            "component declares {} ports, more than {}",
+            count, MAX_DECLARED_PORTS
+        )]
+    }
+
+    /// validate_probe_ports checks that HTTP and TCP probes target a port their container declares.
+    ///
+    /// A mismatch is returned as a warning rather than an error, since a pod on the host
+    /// network may legitimately probe a port its containers do not declare.
+    pub fn validate_probe_ports(&self, resolved_vals: ParamMap) -> Vec<String> {
+        let mut warnings = vec![];
+        for c in self.running_containers() {
+            let declared: Vec<i32> = c
+                .ports
+                .iter()
+                .flat_map(|p| p.expand())
+                .filter_map(|p| p.resolve_port(&resolved_vals).ok())
+                .collect();
+            let probes = vec![
+                ("liveness", c.liveness_probe.as_ref()),
+                ("readiness", c.readiness_probe.as_ref()),
+            ];
+            for (kind, probe) in probes {
+                let probe = match probe {
+                    Some(probe) => probe,
+                    None => continue,
+                };
+                let port = probe
+                    .http_get
+                    .as_ref()
+                    .map(|h| h.port)
+                    .or_else(|| probe.tcp_socket.as_ref().map(|t| t.port));
+                if let Some(port) = port {
+                    if !declared.contains(&port) {
+                        warnings.push(format!(
+                            "{} probe for container {} targets port {}, which the container does not declare",
+                            kind, c.name, port
+                        ));
+                    }
+                }
+            }
+        }
+        warnings
+    }
+
+    /// probe_summary lists, for each main container and sidecar, its liveness and readiness
+    /// probes and their handlers, in declaration order.
+    ///
+    /// Components have no startup probes, so none are listed.
+    pub fn probe_summary(&self) -> Vec<ContainerProbes> {
+        self.running_containers()
+            .map(|c| ContainerProbes {
+                container: c.name.clone(),
+                liveness: c.liveness_probe.as_ref().map(|p| p.handlers()),
+                readiness: c.readiness_probe.as_ref().map(|p| p.handlers()),
+            })
+            .collect()
+    }
+
+    /// workload_settings_map returns the workload settings keyed by name.
+    ///
+    /// A name that is defined more than once is an error.
+    pub fn workload_settings_map(&self) -> Result<BTreeMap<String, &WorkloadSetting>, Error> {
+        let mut settings = BTreeMap::new();
+        for setting in self.workload_settings.iter() {
+            if settings.insert(setting.name.clone(), setting).is_some() {
+                return Err(format_err!(
+                    "workload setting {} is defined more than once",
+                    setting.name
+                ));
+            }
+        }
+        Ok(settings)
+    }
+
+    pub fn get_workload_setting(&self, key: &str) -> Option<&WorkloadSetting> {
+        self.workload_settings
+            .iter()
+            .find(|&item| item.name.eq(key))
+    }
+
+    /// to_volume_claim_templates generates a PersistentVolumeClaim template for every durable volume.
+    ///
+    /// Ephemeral volumes are backed by EmptyDir and volumes with inline content by a
+    /// ConfigMap, so neither is included.
+    pub fn to_volume_claim_templates(&self) -> Vec<core::PersistentVolumeClaim> {
+        let mut claimed: Vec<String> = vec![];
+        self.all_containers()
+            .flat_map(|c| c.resources().volumes.clone().unwrap_or_else(|| vec![]))
+            .filter(|v| v.inline_content.is_none())
+            .filter(|v| !v.disk.as_ref().map_or(false, |d| d.ephemeral))
+            .filter(|v| {
+                // A volume shared between containers is claimed once.
+                if claimed.contains(&v.name) {
+                    return false;
+                }
+                claimed.push(v.name.clone());
+                true
+            })
+            .map(|v| v.to_volume_claim_template())
+            .collect()
+    }
+
+    /// to_topology_spread_constraints renders the topology spread constraints of the pod spec.
+    ///
+    /// Constraints without a label selector select the given pod labels. The PodSpec of the
+    /// Kubernetes API version Rudr is built against predates topology spread constraints,
+    /// so they are rendered as JSON to be added to the serialized pod spec.
+    pub fn to_topology_spread_constraints(
+        &self,
+        labels: &BTreeMap<String, String>,
+    ) -> Option<serde_json::Value> {
+        self.topology_spread_constraints
+            .as_ref()
+            .map(|constraints| {
+                serde_json::Value::Array(constraints.iter().map(|c| c.to_json(labels)).collect())
+            })
+    }
+
+    /// to_affinity renders the pod anti-affinity that keeps replicas apart.
+    ///
+    /// The affinity term selects pods carrying the given labels, so replicas of this
+    /// component instance avoid sharing a domain of the anti-affinity's topology key.
+    pub fn to_affinity(&self, labels: &BTreeMap<String, String>) -> Option<core::Affinity> {
+        self.anti_affinity.as_ref().map(|a| a.to_affinity(labels))
+    }
+
+    /// is_headless reports whether the `headless` workload setting is enabled.
+    ///
+    /// A headless component gets a Service with no cluster IP, which gives each
+    /// pod a stable DNS entry.
+    pub fn is_headless(&self, resolved_vals: ParamMap) -> bool {
+        self.get_workload_setting("headless")
+            .and_then(|s| s.resolve_param(resolved_vals))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    }
+
+    /// targets_port_by_name reports whether the `targetPortByName` workload setting is
+    /// enabled.
+    ///
+    /// The Service then targets each container port by name rather than by number, so it
+    /// keeps working when the container port is renumbered.
+    pub fn targets_port_by_name(&self, resolved_vals: ParamMap) -> bool {
+        self.get_workload_setting("targetPortByName")
+            .and_then(|s| s.resolve_param(resolved_vals))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    }
+
+    /// session_affinity returns the `sessionAffinity` workload setting for the Service.
+    ///
+    /// Kubernetes only accepts `None` and `ClientIP`.
+    pub fn session_affinity(&self, resolved_vals: ParamMap) -> Result<Option<String>, Error> {
+        self.enum_setting("sessionAffinity", SESSION_AFFINITIES, resolved_vals)
+    }
+
+    /// service_type returns the `serviceType` workload setting for the Service.
+    pub fn service_type(&self, resolved_vals: ParamMap) -> Result<Option<String>, Error> {
+        self.enum_setting("serviceType", SERVICE_TYPES, resolved_vals)
+    }
+
+    /// external_traffic_policy returns the `externalTrafficPolicy` workload setting.
+    ///
+    /// The policy only applies to Services reachable from outside the cluster, so it
+    /// requires a NodePort or LoadBalancer service type.
+    pub fn external_traffic_policy(
+        &self,
+        resolved_vals: ParamMap,
+    ) -> Result<Option<String>, Error> {
+        let policy = self.enum_setting(
+            "externalTrafficPolicy",
+            EXTERNAL_TRAFFIC_POLICIES,
+            resolved_vals.clone(),
+        )?;
+        if policy.is_some() {
+            match self
+                .service_type(resolved_vals)?
+                .as_ref()
+                .map(|t| t.as_str())
+            {
+                Some("NodePort") | Some("LoadBalancer") => {}
+                _ => {
+                    return Err(format_err!(
+                        "externalTrafficPolicy requires serviceType NodePort or LoadBalancer"
+                    ))
+                }
+            }
+        }
+        Ok(policy)
+    }
+
+    /// completion_mode returns the `completionMode` workload setting of a Task or
+    /// ReplicableTask, `NonIndexed` or `Indexed`.
+    ///
+    /// Other workload types do not run a Job, so setting it on them is an error.
+    pub fn completion_mode(&self, resolved_vals: ParamMap) -> Result<Option<String>, Error> {
+        let mode = self.enum_setting(COMPLETION_MODE_SETTING, COMPLETION_MODES, resolved_vals)?;
+        let is_task = [TASK_NAME, REPLICABLE_TASK_NAME].contains(&self.workload_type.as_str());
+        if mode.is_some() && !is_task {
+            return Err(format_err!(
+                "{} is not supported by workload type {}",
+                COMPLETION_MODE_SETTING,
+                self.workload_type
+            ));
+        }
+        Ok(mode)
+    }
+
+    /// Resolve a string workload setting that must be one of the allowed values.
+    fn enum_setting(
+        &self,
+        key: &str,
+        allowed: &[&str],
+        resolved_vals: ParamMap,
+    ) -> Result<Option<String>, Error> {
+        match self
+            .get_workload_setting(key)
+            .and_then(|s| s.resolve_param(resolved_vals))
+        {
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(serde_json::Value::String(ref value)) if allowed.contains(&value.as_str()) => {
+                Ok(Some(value.clone()))
+            }
+            Some(other) => Err(format_err!(
+                "{} must be one of {:?}, got {}",
+                key,
+                allowed,
+                other
+            )),
+        }
+    }
+
+    /// session_affinity_timeout returns the `sessionAffinityTimeoutSeconds` workload setting.
+    ///
+    /// The timeout only applies to ClientIP affinity, and Kubernetes limits it to one day.
+    pub fn session_affinity_timeout(&self, resolved_vals: ParamMap) -> Result<Option<i32>, Error> {
+        let timeout = match self
+            .get_workload_setting("sessionAffinityTimeoutSeconds")
+            .and_then(|s| s.resolve_param(resolved_vals.clone()))
+        {
+            None | Some(serde_json::Value::Null) => return Ok(None),
+            Some(v) => match v.as_i64() {
+                Some(t) if t > 0 && t <= MAX_SESSION_AFFINITY_TIMEOUT_SECONDS => t as i32,
+                _ => {
+                    return Err(format_err!(
+                        "sessionAffinityTimeoutSeconds must be between 1 and {}, got {}",
+                        MAX_SESSION_AFFINITY_TIMEOUT_SECONDS,
+                        v
+                    ))
+                }
+            },
+        };
+        if self.session_affinity(resolved_vals)? != Some("ClientIP".to_string()) {
+            return Err(format_err!(
+                "sessionAffinityTimeoutSeconds requires sessionAffinity ClientIP"
+            ));
+        }
+        Ok(Some(timeout))
+    }
+
+    /// validate_workload_settings checks that every workload setting resolves to a value
+    /// of its declared type.
+    ///
+    /// The settings Rudr reads are checked further for the workload types that render
+    /// them. Extended workload types are left to interpret their settings themselves.
+    pub fn validate_workload_settings(&self, resolved_vals: ParamMap) -> Result<(), Error> {
+        for setting in self.workload_settings.iter() {
+            setting.resolve(&resolved_vals)?;
+        }
+        if !is_core_workload_type(self.workload_type.as_str()) {
+            return Ok(());
+        }
+        if self.is_scalable() {
+            self.replica_count(resolved_vals.clone())?;
+        }
+        if DEPLOYMENT_WORKLOAD_TYPES.contains(&self.workload_type.as_str()) {
+            self.deployment_strategy(resolved_vals.clone())?;
+            self.progress_deadline_seconds(resolved_vals.clone())?;
+        }
+        self.disruption_budget(resolved_vals.clone())?;
+        self.completion_mode(resolved_vals)?;
+        Ok(())
+    }
+
+    /// is_scalable reports whether the workload type runs a number of replicas set by the
+    /// `replicaCount` workload setting: the replicable types that run until they are removed.
+    pub fn is_scalable(&self) -> bool {
+        workload_info(self.workload_type.as_str())
+            .map(|info| info.replicable && !info.run_to_completion)
+            .unwrap_or(false)
+    }
+
+    /// scale sets the number of replicas, stored as the `replicaCount` workload setting.
+    ///
+    /// Only scalable workload types can be scaled; singletons, tasks and extended workload
+    /// types are an error. A workload may be scaled to zero to idle it without removing it.
+    /// The stored count replaces any parameter the setting referred to.
+    pub fn scale(&mut self, replicas: i32) -> Result<(), Error> {
+        if !self.is_scalable() {
+            return Err(format_err!(
+                "workload type {} cannot be scaled",
+                self.workload_type
+            ));
+        }
+        if replicas < 0 {
+            return Err(format_err!(
+                "replica count must not be negative, got {}",
+                replicas
+            ));
+        }
+        let setting = WorkloadSetting {
+            name: REPLICA_COUNT_SETTING.to_string(),
+            description: None,
+            parameter_type: ParameterType::Number,
+            required: false,
+            value: Some(serde_json::json!(replicas)),
+            from_param: None,
+        };
+        match self
+            .workload_settings
+            .iter_mut()
+            .find(|s| s.name == REPLICA_COUNT_SETTING)
+        {
+            Some(existing) => *existing = setting,
+            None => self.workload_settings.push(setting),
+        }
+        Ok(())
+    }
+
+    /// replica_count returns the `replicaCount` workload setting, if it is set.
+    pub fn replica_count(&self, resolved_vals: ParamMap) -> Result<Option<i32>, Error> {
+        self.non_negative_setting(REPLICA_COUNT_SETTING, resolved_vals)
+    }
+
+    /// effective_replica_count returns the number of replicas the workload runs.
+    ///
+    /// Scalable workload types run the `replicaCount` workload setting, or one replica if
+    /// it is not set, and the other long-running types always run one. Tasks run to
+    /// completion rather than keeping replicas, and extended workload types are not known
+    /// to Rudr, so both are None.
+    pub fn effective_replica_count(&self, resolved_vals: ParamMap) -> Result<Option<i32>, Error> {
+        let info = match workload_info(self.workload_type.as_str()) {
+            Ok(info) => info,
+            Err(_) => return Ok(None),
+        };
+        if info.run_to_completion {
+            return Ok(None);
+        }
+        if !self.is_scalable() {
+            return Ok(Some(1));
+        }
+        Ok(Some(self.replica_count(resolved_vals)?.unwrap_or(1)))
+    }
+
+    /// min_ready_seconds returns the `minReadySeconds` workload setting, how long a new
+    /// pod of a Deployment must be ready before it counts as available.
+    pub fn min_ready_seconds(&self, resolved_vals: ParamMap) -> Result<Option<i32>, Error> {
+        self.non_negative_setting(MIN_READY_SECONDS_SETTING, resolved_vals)
+    }
+
+    /// progress_deadline_seconds returns the `progressDeadlineSeconds` workload setting, how
+    /// long a Deployment may go without progress before its rollout is reported as failed.
+    ///
+    /// The deadline must be longer than `minReadySeconds`, otherwise every rollout would
+    /// fail before its pods became available. The deadline Kubernetes uses when none is set
+    /// is checked the same way.
+    pub fn progress_deadline_seconds(&self, resolved_vals: ParamMap) -> Result<Option<i32>, Error> {
+        let deadline =
+            self.non_negative_setting(PROGRESS_DEADLINE_SECONDS_SETTING, resolved_vals.clone())?;
+        if let Some(min_ready) = self.min_ready_seconds(resolved_vals)? {
+            let effective = deadline.unwrap_or(DEFAULT_PROGRESS_DEADLINE_SECONDS);
+            if effective <= min_ready {
+                return Err(format_err!(
+                    "{} ({}) must be greater than {} ({})",
+                    PROGRESS_DEADLINE_SECONDS_SETTING,
+                    effective,
+                    MIN_READY_SECONDS_SETTING,
+                    min_ready
+                ));
+            }
+        }
+        Ok(deadline)
+    }
+
+    /// Resolve a workload setting that must be a non-negative integer.
+    fn non_negative_setting(
+        &self,
+        key: &str,
+        resolved_vals: ParamMap,
+    ) -> Result<Option<i32>, Error> {
+        match self
+            .get_workload_setting(key)
+            .and_then(|s| s.resolve_param(resolved_vals))
+        {
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(value) => value
+                .as_u64()
+                .filter(|n| *n <= i32::max_value() as u64)
+                .map(|n| Some(n as i32))
+                .ok_or_else(|| {
+                    format_err!("{} must be a non-negative integer, got {}", key, value)
+                }),
+        }
+    }
+
+    /// deployment_strategy returns the update strategy of a Deployment, set by the
+    /// `deploymentStrategy` workload setting.
+    ///
+    /// The setting is an object whose `type` is `RollingUpdate` or `Recreate`. A rolling
+    /// update may set `maxSurge` and `maxUnavailable` to a count or a percentage, which
+    /// default to 25% and must not both be zero. Without the setting, a Deployment rolls
+    /// out with the default rolling update.
+    pub fn deployment_strategy(
+        &self,
+        resolved_vals: ParamMap,
+    ) -> Result<apps::DeploymentStrategy, Error> {
+        let value = match self
+            .get_workload_setting(DEPLOYMENT_STRATEGY_SETTING)
+            .and_then(|s| s.resolve_param(resolved_vals))
+        {
+            None | Some(serde_json::Value::Null) => serde_json::json!({}),
+            Some(value) => value,
+        };
+        let strategy = value.as_object().ok_or_else(|| {
+            format_err!(
+                "{} must be an object, got {}",
+                DEPLOYMENT_STRATEGY_SETTING,
+                value
+            )
+        })?;
+        let strategy_type = match strategy.get("type") {
+            None => "RollingUpdate",
+            Some(t) => t
+                .as_str()
+                .filter(|t| DEPLOYMENT_STRATEGY_TYPES.contains(t))
+                .ok_or_else(|| {
+                    format_err!(
+                        "{} type must be one of {:?}, got {}",
+                        DEPLOYMENT_STRATEGY_SETTING,
+                        DEPLOYMENT_STRATEGY_TYPES,
+                        t
+                    )
+                })?,
+        };
+        if strategy_type == "Recreate" {
+            if strategy.contains_key("maxSurge") || strategy.contains_key("maxUnavailable") {
+                return Err(format_err!(
+                    "{} Recreate does not take maxSurge or maxUnavailable",
+                    DEPLOYMENT_STRATEGY_SETTING
+                ));
+            }
+            return Ok(apps::DeploymentStrategy {
+                type_: Some(strategy_type.to_string()),
+                rolling_update: None,
+            });
+        }
+        let max_surge = rollout_limit("maxSurge", strategy.get("maxSurge"))?;
+        let max_unavailable = rollout_limit("maxUnavailable", strategy.get("maxUnavailable"))?;
+        if is_zero_rollout_limit(&max_surge) && is_zero_rollout_limit(&max_unavailable) {
+            return Err(format_err!(
+                "{} maxSurge and maxUnavailable must not both be zero",
+                DEPLOYMENT_STRATEGY_SETTING
+            ));
+        }
+        Ok(apps::DeploymentStrategy {
+            type_: Some(strategy_type.to_string()),
+            rolling_update: Some(apps::RollingUpdateDeployment {
+                max_surge: Some(max_surge),
+                max_unavailable: Some(max_unavailable),
+            }),
+        })
+    }
+
+    /// to_pod_disruption_budget renders the PodDisruptionBudget set by the `pdb` workload
+    /// setting, or None if the setting is not set.
+    ///
+    /// The setting is an object with exactly one of `minAvailable` and `maxUnavailable`,
+    /// each a count or a percentage of pods. The budget selects the pods of the given
+    /// instance. Only replicated workload types that run until they are removed take a
+    /// budget.
+    pub fn to_pod_disruption_budget(
+        &self,
+        instance_name: &str,
+        resolved_vals: ParamMap,
+    ) -> Result<Option<policy::PodDisruptionBudget>, Error> {
+        let (min_available, max_unavailable) = match self.disruption_budget(resolved_vals)? {
+            Some(budget) => budget,
+            None => return Ok(None),
+        };
+        let mut selector = BTreeMap::new();
+        selector.insert(
+            "oam.dev/instance-name".to_string(),
+            instance_name.to_string(),
+        );
+        Ok(Some(policy::PodDisruptionBudget {
+            metadata: Some(meta::ObjectMeta {
+                name: Some(instance_name.to_string()),
+                labels: Some(Component::standard_labels(instance_name)),
+                ..Default::default()
+            }),
+            spec: Some(policy::PodDisruptionBudgetSpec {
+                min_available,
+                max_unavailable,
+                selector: Some(meta::LabelSelector {
+                    match_labels: Some(selector),
+                    ..Default::default()
+                }),
+            }),
+            ..Default::default()
+        }))
+    }
+
+    /// Resolve the `pdb` workload setting into its minAvailable and maxUnavailable.
+    fn disruption_budget(
+        &self,
+        resolved_vals: ParamMap,
+    ) -> Result<Option<(Option<IntOrString>, Option<IntOrString>)>, Error> {
+        let value = match self
+            .get_workload_setting(POD_DISRUPTION_BUDGET_SETTING)
+            .and_then(|s| s.resolve_param(resolved_vals))
+        {
+            None | Some(serde_json::Value::Null) => return Ok(None),
+            Some(value) => value,
+        };
+        if !self.is_scalable() {
+            return Err(format_err!(
+                "{} is not supported by workload type {}",
+                POD_DISRUPTION_BUDGET_SETTING,
+                self.workload_type
+            ));
+        }
+        let budget = value.as_object().ok_or_else(|| {
+            format_err!(
+                "{} must be an object, got {}",
+                POD_DISRUPTION_BUDGET_SETTING,
+                value
+            )
+        })?;
+        let limit = |key: &str| {
+            budget
+                .get(key)
+                .filter(|v| !v.is_null())
+                .map(|v| count_or_percentage(key, v))
+                .transpose()
+        };
+        match (limit("minAvailable")?, limit("maxUnavailable")?) {
+            (Some(_), Some(_)) | (None, None) => Err(format_err!(
+                "{} must set exactly one of minAvailable and maxUnavailable",
+                POD_DISRUPTION_BUDGET_SETTING
+            )),
+            budget => Ok(Some(budget)),
+        }
+    }
+
+    /// validate_service_settings checks the workload settings that configure the Service.
+    pub fn validate_service_settings(&self, resolved_vals: ParamMap) -> Result<(), Error> {
+        self.session_affinity(resolved_vals.clone())?;
+        self.session_affinity_timeout(resolved_vals.clone())?;
+        let service_type = self.service_type(resolved_vals.clone())?;
+        if self.is_headless(resolved_vals.clone())
+            && service_type.is_some()
+            && service_type != Some("ClusterIP".to_string())
+        {
+            return Err(format_err!(
+                "a headless Service must have serviceType ClusterIP"
+            ));
+        }
+        self.external_traffic_policy(resolved_vals)?;
+        Ok(())
+    }
+}
+
+impl Default for Component {
+    fn default() -> Self {
+        Component {
+            workload_type: DEFAULT_WORKLOAD_TYPE.into(),
+            os_type: None,
+            arch: None,
+            parameters: Vec::new(),
+            containers: Vec::new(),
+            init_containers: Vec::new(),
+            sidecars: Vec::new(),
+            workload_settings: Vec::new(),
+            topology_spread_constraints: None,
+            anti_affinity: None,
+            termination_grace_period_seconds: None,
+            default_resources: None,
+            runtime_class_name: None,
+            priority_class_name: None,
+            host_network: false,
+            host_pid: false,
+            host_ipc: false,
+            automount_service_account_token: None,
+        }
+    }
+}
+
+impl std::str::FromStr for Component {
+    type Err = failure::Error;
+
+    /// Parse JSON data into a Component.
+    fn from_str(json_data: &str) -> Result<Self, Self::Err> {
+        let res: Component = serde_json::from_str(json_data)?;
+        Ok(res)
+    }
+}
+
+/// Map a value, ignoring case, onto one of the known Kubernetes names, directly or
+/// through an alias.
+fn normalize_alias(
+    key: &str,
+    value: &str,
+    known: &[&str],
+    aliases: &[(&str, &str)],
+) -> Result<String, Error> {
+    let lower = value.to_lowercase();
+    if let Some((_, name)) = aliases.iter().find(|(alias, _)| *alias == lower) {
+        return Ok(name.to_string());
+    }
+    if !known.contains(&lower.as_str()) {
+        return Err(format_err!(
+            "{} {:?} must be one of {}",
+            key,
+            value,
+            known.join(", ")
+        ));
+    }
+    Ok(lower)
+}
+
+/// Parse the maxSurge or maxUnavailable of a rolling update, a non-negative count or a
+/// percentage, defaulting to 25%.
+fn rollout_limit(key: &str, value: Option<&serde_json::Value>) -> Result<IntOrString, Error> {
+    match value {
+        None | Some(serde_json::Value::Null) => {
+            Ok(IntOrString::String(DEFAULT_ROLLOUT_LIMIT.into()))
+        }
+        Some(v) => count_or_percentage(key, v),
+    }
+}
+
+/// Rebuild a value with the keys of every object in sorted order, whatever order the JSON
+/// map type keeps them in.
+fn sort_keys(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<(String, serde_json::Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            serde_json::Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, sort_keys(v)))
+                    .collect(),
+            )
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(sort_keys).collect())
+        }
+        other => other,
+    }
+}
+
+/// Parse YAML into JSON, resolving aliases and merge keys.
+///
+/// The YAML parser replaces each alias with a copy of its anchored value, but leaves a
+/// `<<` merge key as an ordinary key, so merges are applied here.
+fn yaml_value(data: &str) -> Result<serde_json::Value, Error> {
+    let mut value: serde_json::Value = serde_yaml::from_str(data)?;
+    resolve_merge_keys(&mut value);
+    Ok(value)
+}
+
+/// Apply YAML `<<` merge keys throughout a value. Keys set on the mapping itself win over
+/// merged ones, and earlier mappings in a merged list win over later ones.
+fn resolve_merge_keys(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            if let Some(merged) = map.remove("<<") {
+                let sources = match merged {
+                    serde_json::Value::Array(sources) => sources,
+                    source => vec![source],
+                };
+                for source in sources {
+                    if let serde_json::Value::Object(source) = source {
+                        for (k, v) in source {
+                            map.entry(k).or_insert(v);
+                        }
+                    }
+                }
+            }
+            for v in map.values_mut() {
+                resolve_merge_keys(v);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for v in items.iter_mut() {
+                resolve_merge_keys(v);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Wrap a list in Some, or None if it is empty, so that empty lists are left out of the
+/// rendered object.
+fn non_empty<T>(items: Vec<T>) -> Option<Vec<T>> {
+    if items.is_empty() {
+        None
+    } else {
+        Some(items)
+    }
+}
+
+/// Match a name against a pattern in which `*` matches any run of characters, ignoring case.
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_uppercase();
+    let name = name.to_uppercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if name.len() < first.len() + last.len() || !name.starts_with(first) || !name.ends_with(last) {
+        return false;
+    }
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in parts[1..parts.len() - 1].iter() {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Parse a number of pods, given as a non-negative count or a percentage.
+fn count_or_percentage(key: &str, value: &serde_json::Value) -> Result<IntOrString, Error> {
+    match value {
+        serde_json::Value::String(s)
+            if s.ends_with('%') && s.trim_end_matches('%').parse::<u32>().is_ok() =>
+        {
+            Ok(IntOrString::String(s.clone()))
+        }
+        v => v
+            .as_u64()
+            .filter(|n| *n <= i32::max_value() as u64)
+            .map(|n| IntOrString::Int(n as i32))
+            .ok_or_else(|| {
+                format_err!(
+                    "{} must be a non-negative integer or a percentage, got {}",
+                    key,
+                    v
+                )
+            }),
+    }
+}
+
+/// Whether a rolling update limit allows no pods, as a count or a percentage.
+fn is_zero_rollout_limit(limit: &IntOrString) -> bool {
+    match limit {
+        IntOrString::Int(n) => *n == 0,
+        IntOrString::String(s) => s.trim_end_matches('%').parse::<u32>() == Ok(0),
+    }
+}
+
+/// Remove the defaulted fields of a serialized container and the objects it holds.
+fn strip_container_defaults(container: &mut serde_json::Value) {
+    if let Some(resources) = container.get_mut("resources") {
+        for v in array_items(resources, "volumes") {
+            strip_default_fields(
+                v,
+                serde_json::json!({
+                    "accessMode": AccessMode::default(),
+                    "sharingPolicy": SharingPolicy::default(),
+                    "disk": Disk::default()
+                }),
+            );
+        }
+        let defaults = Resources::default();
+        let mut defaults = serde_json::json!({
+            "cpu": defaults.cpu,
+            "memory": defaults.memory,
+            "volumes": [],
+            "extended": []
+        });
+        strip_nulls(&mut defaults);
+        strip_default_fields(resources, defaults);
+    }
+    for p in array_items(container, "ports") {
+        strip_default_fields(
+            p,
+            serde_json::json!({ "protocol": PortProtocol::default() }),
+        );
+    }
+    for key in &["livenessProbe", "readinessProbe"] {
+        if let Some(probe) = container.get_mut(*key) {
+            let mut defaults = serde_json::to_value(HealthProbe::default()).unwrap_or_default();
+            strip_nulls(&mut defaults);
+            strip_default_fields(probe, defaults);
+        }
+    }
+    // A declared `resources` is kept even when empty: unlike an omitted one, it does not
+    // take the component's default resources.
+    strip_default_fields(
+        container,
+        serde_json::json!({
+            "env": [],
+            "config": [],
+            "ports": []
+        }),
+    );
+}
+
+/// Remove the fields of a serialized object that are equal to the same field of `defaults`.
+fn strip_default_fields(value: &mut serde_json::Value, defaults: serde_json::Value) {
+    if let Some(object) = value.as_object_mut() {
+        let stripped: Vec<String> = object
+            .iter()
+            .filter(|(key, field)| defaults.get(key.as_str()) == Some(*field))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in stripped {
+            object.remove(&key);
+        }
+    }
+}
+
+/// Record the JSON pointers at which `live` differs from `desired`, ignoring the fields
+/// only `live` sets.
+fn diff_defaulted(
+    desired: &serde_json::Value,
+    live: &serde_json::Value,
+    path: String,
+    diffs: &mut Vec<String>,
+) {
+    match (desired, live) {
+        (serde_json::Value::Object(desired), serde_json::Value::Object(live)) => {
+            for (key, field) in desired.iter() {
+                let field_path = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                match live.get(key) {
+                    Some(live_field) => diff_defaulted(field, live_field, field_path, diffs),
+                    None => diffs.push(field_path),
+                }
+            }
+        }
+        (serde_json::Value::Array(desired), serde_json::Value::Array(live))
+            if desired.len() == live.len() =>
+        {
+            for (i, (item, live_item)) in desired.iter().zip(live.iter()).enumerate() {
+                diff_defaulted(item, live_item, format!("{}/{}", path, i), diffs);
+            }
+        }
+        _ => {
+            if desired != live && !(is_quantity_path(&path) && same_quantity(desired, live)) {
+                diffs.push(path);
+            }
+        }
+    }
+}
+
+/// Whether a pod spec field holds a resource quantity, which the API server returns in
+/// canonical form, such as `500m` for a rendered `0.5`.
+fn is_quantity_path(path: &str) -> bool {
+    path.contains("/resources/requests/")
+        || path.contains("/resources/limits/")
+        || path.ends_with("/sizeLimit")
+        || path.ends_with("/divisor")
+}
+
+/// Whether two serialized quantities have the same value, however they are written.
+fn same_quantity(desired: &serde_json::Value, live: &serde_json::Value) -> bool {
+    let parse = |v: &serde_json::Value| v.as_str().and_then(|q| quantity::parse_quantity(q).ok());
+    match (parse(desired), parse(live)) {
+        (Some(desired), Some(live)) => {
+            (desired - live).abs() <= desired.abs().max(live.abs()) * std::f64::EPSILON * 4.0
+        }
+        _ => false,
+    }
+}
+
+/// Remove null fields, which stand for unset optional values, from a serialized value.
+fn strip_nulls(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(object) => {
+            let nulls: Vec<String> = object
+                .iter()
+                .filter(|(_, field)| field.is_null())
+                .map(|(key, _)| key.clone())
+                .collect();
+            for key in nulls {
+                object.remove(&key);
+            }
+            object.values_mut().for_each(strip_nulls);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+/// The items of the array held by a field of a serialized object, if there is one.
+fn array_items<'a>(
+    value: &'a mut serde_json::Value,
+    key: &str,
+) -> impl Iterator<Item = &'a mut serde_json::Value> {
+    value
+        .get_mut(key)
+        .and_then(|items| items.as_array_mut())
+        .into_iter()
+        .flatten()
+}
+
+/// TopologySpreadConstraint describes how replicas are spread across a topology domain.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TopologySpreadConstraint {
+    pub max_skew: i32,
+    pub topology_key: String,
+
+    #[serde(default = "default_when_unsatisfiable")]
+    pub when_unsatisfiable: String,
+    pub label_selector: Option<meta::LabelSelector>,
+}
+
+impl TopologySpreadConstraint {
+    fn validate(&self) -> Result<(), Error> {
+        if self.max_skew < 1 {
+            return Err(format_err!(
+                "topology spread constraint on {} must have a maxSkew of at least 1",
+                self.topology_key
+            ));
+        }
+        if self.topology_key.is_empty() {
+            return Err(format_err!(
+                "topology spread constraint must have a topologyKey"
+            ));
+        }
+        if !WHEN_UNSATISFIABLE.contains(&self.when_unsatisfiable.as_str()) {
+            return Err(format_err!(
+                "whenUnsatisfiable must be one of {:?}, got {}",
+                WHEN_UNSATISFIABLE,
+                self.when_unsatisfiable
+            ));
+        }
+        Ok(())
+    }
+
+    fn to_json(&self, labels: &BTreeMap<String, String>) -> serde_json::Value {
+        let label_selector = self
+            .label_selector
+            .clone()
+            .unwrap_or_else(|| meta::LabelSelector {
+                match_labels: Some(labels.clone()),
+                ..Default::default()
+            });
+        serde_json::json!({
+            "maxSkew": self.max_skew,
+            "topologyKey": self.topology_key,
+            "whenUnsatisfiable": self.when_unsatisfiable,
+            "labelSelector": label_selector,
+        })
+    }
+}
+
+fn default_when_unsatisfiable() -> String {
+    "DoNotSchedule".to_string()
+}
+
+/// AntiAffinity keeps the replicas of a component off the same topology domain.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AntiAffinity {
+    #[serde(default)]
+    pub mode: AntiAffinityMode,
+
+    #[serde(default = "default_anti_affinity_topology_key")]
+    pub topology_key: String,
+}
+
+impl AntiAffinity {
+    fn to_affinity(&self, labels: &BTreeMap<String, String>) -> core::Affinity {
+        let term = core::PodAffinityTerm {
+            label_selector: Some(meta::LabelSelector {
+                match_labels: Some(labels.clone()),
+                ..Default::default()
+            }),
+            topology_key: self.topology_key.clone(),
+            ..Default::default()
+        };
+        let pod_anti_affinity = match self.mode {
+            AntiAffinityMode::Required => core::PodAntiAffinity {
+                required_during_scheduling_ignored_during_execution: Some(vec![term]),
+                ..Default::default()
+            },
+            AntiAffinityMode::Preferred => core::PodAntiAffinity {
+                preferred_during_scheduling_ignored_during_execution: Some(vec![
+                    core::WeightedPodAffinityTerm {
+                        pod_affinity_term: term,
+                        weight: 100,
+                    },
+                ]),
+                ..Default::default()
+            },
+        };
+        core::Affinity {
+            pod_anti_affinity: Some(pod_anti_affinity),
+            ..Default::default()
+        }
+    }
+}
+
+/// AntiAffinityMode describes whether the scheduler must or should keep replicas apart.
+///
+/// Required replicas stay pending when no domain is free; preferred replicas fall back
+/// to sharing a domain.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AntiAffinityMode {
+    Preferred,
+    Required,
+}
+
+impl Default for AntiAffinityMode {
+    fn default() -> Self {
+        AntiAffinityMode::Preferred
+    }
+}
+
+fn default_anti_affinity_topology_key() -> String {
+    "kubernetes.io/hostname".to_string()
+}
+
+/// Container describes the container configuration for a Component.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Container {
+    pub name: String,
+    pub image: String,
+    pub image_pull_secret: Option<String>,
+    /// When to pull the image: `Always`, `IfNotPresent` or `Never`. Left unset, it
+    /// follows the image tag, as Kubernetes does.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_pull_policy: Option<String>,
+
+    /// The resources the container asks for. Left unset, the container gets the defaults of
+    /// its component; see `Component::default_resources`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<Resources>,
+
+    pub cmd: Option<Vec<String>>,
+    pub args: Option<Vec<String>>,
+
+    #[serde(default)]
+    pub env: Vec<Env>,
+
+    #[serde(default)]
+    pub config: Option<Vec<ConfigFile>>,
+
+    #[serde(default)]
+    pub ports: Vec<Port>,
+
+    pub liveness_probe: Option<HealthProbe>,
+    pub readiness_probe: Option<HealthProbe>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lifecycle: Option<Lifecycle>,
+}
+
+impl Default for Container {
+    fn default() -> Self {
         Container {
             name: "".to_string(),
             image: "".to_string(),
             image_pull_secret: None,
-            resources: Default::default(),
+            image_pull_policy: None,
+            resources: None,
             cmd: None,
             args: None,
             env: vec![],
@@ -265,11 +2639,197 @@ impl Default for Container {
             ports: vec![],
             liveness_probe: None,
             readiness_probe: None,
+            lifecycle: None,
         }
     }
 }
 
 impl Container {
+    /// Render this container, resolving parameter-sourced values.
+    fn to_container(&self, resolved_vals: &ParamMap) -> Result<core::Container, Error> {
+        Ok(core::Container {
+            name: self.name.clone(),
+            image: Some(self.image.clone()),
+            image_pull_policy: Some(self.effective_image_pull_policy()),
+            resources: Some(self.resources().to_resource_requirements()?),
+            ports: non_empty(
+                self.ports
+                    .iter()
+                    .flat_map(|p| p.expand())
+                    .map(|p| p.to_container_port(resolved_vals))
+                    .collect::<Result<Vec<_>, Error>>()?,
+            ),
+            command: self.cmd.clone(),
+            args: self.args.clone(),
+            env: non_empty(self.to_env_vars(resolved_vals.clone())?),
+
+            volume_mounts: self.volume_mounts(),
+            liveness_probe: self
+                .liveness_probe
+                .as_ref()
+                .map(|p| p.to_resolved_probe(resolved_vals.clone()))
+                .transpose()?,
+            readiness_probe: self
+                .readiness_probe
+                .as_ref()
+                .map(|p| p.to_resolved_probe(resolved_vals.clone()))
+                .transpose()?,
+            lifecycle: self.lifecycle.as_ref().map(|l| l.to_lifecycle()),
+            ..Default::default()
+        })
+    }
+
+    /// parameter_references lists the parameters this container refers to, each with the
+    /// path of the field that refers to it under the given container path.
+    fn parameter_references(&self, path: &str) -> Vec<(&str, String)> {
+        let mut refs = vec![];
+        for e in self.env.iter() {
+            if let Some(param) = e.from_param.as_ref() {
+                refs.push((param.as_str(), format!("{}.env[{}]", path, e.name)));
+            }
+        }
+        for c in self.config.iter().flatten() {
+            if let Some(param) = c.from_param.as_ref() {
+                refs.push((param.as_str(), format!("{}.config[{}]", path, c.path)));
+            }
+        }
+        for p in self.ports.iter() {
+            if let Some(IntOrString::String(param)) = p.container_port.as_ref() {
+                if param.parse::<i32>().is_err() {
+                    refs.push((param.as_str(), format!("{}.ports[{}]", path, p.name)));
+                }
+            }
+        }
+        let probes = [
+            ("livenessProbe", self.liveness_probe.as_ref()),
+            ("readinessProbe", self.readiness_probe.as_ref()),
+        ];
+        for (kind, probe) in probes.iter() {
+            let params = match probe.and_then(|p| p.from_param.as_ref()) {
+                Some(params) => params,
+                None => continue,
+            };
+            let fields = [
+                ("initialDelaySeconds", params.initial_delay_seconds.as_ref()),
+                ("periodSeconds", params.period_seconds.as_ref()),
+                ("timeoutSeconds", params.timeout_seconds.as_ref()),
+                ("successThreshold", params.success_threshold.as_ref()),
+                ("failureThreshold", params.failure_threshold.as_ref()),
+            ];
+            for (field, param) in fields.iter() {
+                if let Some(param) = *param {
+                    refs.push((param.as_str(), format!("{}.{}.{}", path, kind, field)));
+                }
+            }
+        }
+        refs
+    }
+
+    /// env_names returns the name of every environment variable, in declaration order.
+    pub fn env_names(&self) -> Vec<&str> {
+        self.env.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    /// validate_env_names checks that no two environment variables share a name.
+    ///
+    /// A variable set from a parameter collides with a plain one of the same name just
+    /// the same, since only one of them could take effect.
+    pub fn validate_env_names(&self) -> Result<(), Error> {
+        let names = self.env_names();
+        for (i, name) in names.iter().enumerate() {
+            if names[..i].contains(name) {
+                return Err(format_err!(
+                    "environment variable {} is set more than once in container {}",
+                    name,
+                    self.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// effective_image_pull_policy returns the image pull policy, defaulting it from the
+    /// image tag when it is unset.
+    ///
+    /// As in Kubernetes, an image tagged `latest`, or not tagged at all, is always pulled,
+    /// and any other image only if it is not present. An image pinned by digest is never
+    /// `latest`, whatever its tag.
+    pub fn effective_image_pull_policy(&self) -> String {
+        if let Some(policy) = self.image_pull_policy.as_ref() {
+            return policy.clone();
+        }
+        let latest = match self.image.parse::<ImageRef>() {
+            Ok(image) => image.digest.is_none() && image.tag.map_or(true, |tag| tag == "latest"),
+            Err(_) => true,
+        };
+        if latest { "Always" } else { "IfNotPresent" }.to_string()
+    }
+
+    /// validate_image_pull_policy checks that the image pull policy, if set, is one
+    /// Kubernetes accepts.
+    fn validate_image_pull_policy(&self) -> Result<(), Error> {
+        match self.image_pull_policy.as_ref() {
+            Some(policy) if !IMAGE_PULL_POLICIES.contains(&policy.as_str()) => Err(format_err!(
+                "container {}: image pull policy {} must be one of {}",
+                self.name,
+                policy,
+                IMAGE_PULL_POLICIES.join(", ")
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// validate_image_ref parses the image reference, rejecting malformed ones before
+    /// Kubernetes fails to pull them.
+    pub fn validate_image_ref(&self) -> Result<ImageRef, Error> {
+        self.image
+            .parse()
+            .map_err(|e| format_err!("container {}: {}", self.name, e))
+    }
+
+    /// Fill unset optional fields with their concrete defaults.
+    pub fn apply_defaults(&mut self) {
+        if self.config.is_none() {
+            self.config = Some(vec![]);
+        }
+        if let Some(resources) = self.resources.as_mut() {
+            if resources.extended.is_none() {
+                resources.extended = Some(vec![]);
+            }
+            let volumes = resources.volumes.get_or_insert_with(Vec::new);
+            for v in volumes.iter_mut() {
+                if v.disk.is_none() {
+                    v.disk = Some(Disk::default());
+                }
+            }
+        }
+    }
+
+    /// resources returns the resources the container asks for, or `Resources::default()` if
+    /// it omits them.
+    pub fn resources(&self) -> &Resources {
+        lazy_static! {
+            static ref DEFAULT_RESOURCES: Resources = Resources::default();
+        }
+        self.resources.as_ref().unwrap_or(&DEFAULT_RESOURCES)
+    }
+
+    /// Generate the environment variables for a container.
+    ///
+    /// Variables are always emitted in the order they are declared, whatever the source of
+    /// their value, so that rendering the same component twice yields an identical pod spec
+    /// and does not trigger a spurious rollout.
+    ///
+    /// What resolving the variables overrode is not reported here; see
+    /// `Component::to_pod_spec_with_report`.
+    pub fn to_env_vars(&self, params: ParamMap) -> Result<Vec<core::EnvVar>, Error> {
+        let mut report = ConversionReport::default();
+        self.env
+            .iter()
+            .map(|e| e.resolve(&params, &mut report))
+            .collect()
+    }
+
     /// Generate volume mounts for a container.
     pub fn volume_mounts(&self) -> Option<Vec<core::VolumeMount>> {
         let configured_volumes: std::vec::Vec<core::VolumeMount> =
@@ -280,7 +2840,7 @@ impl Container {
                     .collect()
             });
         let resource_volumes: std::vec::Vec<core::VolumeMount> =
-            self.resources.volumes.clone().map_or(vec![], |vols| {
+            self.resources().volumes.clone().map_or(vec![], |vols| {
                 vols.iter().map(|vol| self.resource_volume(vol)).collect()
             });
         let volumes = [configured_volumes, resource_volumes].concat();
@@ -305,10 +2865,22 @@ impl Container {
     }
 
     fn resource_volume(&self, vol: &Volume) -> core::VolumeMount {
+        // Inline content is a single file, mounted exactly at the mount path.
+        if vol.inline_content.is_some() {
+            return core::VolumeMount {
+                mount_path: vol.mount_path.clone(),
+                name: vol.name.clone(),
+                read_only: Some(true),
+                sub_path: Some(vol.file_name()),
+                ..Default::default()
+            };
+        }
         core::VolumeMount {
             mount_path: vol.mount_path.clone(),
             name: vol.name.clone(),
             read_only: Some(vol.access_mode == AccessMode::RO),
+            sub_path: vol.sub_path.clone(),
+            mount_propagation: vol.mount_propagation.clone(),
             ..Default::default()
         }
     }
@@ -317,7 +2889,7 @@ impl Container {
 /// Workload settings describe the configuration for a workload.
 ///
 /// This information is passed to the underlying workload defined by Component::worload_type.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkloadSetting {
     pub name: String,
@@ -337,10 +2909,166 @@ impl WorkloadSetting {
     pub fn resolve_param(&self, params: ParamMap) -> Option<serde_json::Value> {
         resolve_value(params, self.from_param.clone(), self.value.clone())
     }
+
+    /// resolve returns the value of this setting, checked against its declared type.
+    ///
+    /// A `fromParam` takes the value from the resolved parameters, falling back to the
+    /// setting's own `value`. A required setting with neither is an error.
+    pub fn resolve(&self, params: &ParamMap) -> Result<Option<serde_json::Value>, Error> {
+        match self.resolve_param(params.clone()) {
+            Some(value) => {
+                self.parameter_type
+                    .validate(format!("workload setting {}", self.name).as_str(), &value)?;
+                Ok(Some(value))
+            }
+            None if self.required => match self.from_param.as_ref() {
+                Some(param) => Err(format_err!(
+                    "workload setting {} requires a value for parameter {}",
+                    self.name,
+                    param
+                )),
+                None => Err(format_err!(
+                    "workload setting {} requires a value",
+                    self.name
+                )),
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+/// ConversionReport records the information a conversion dropped and the assumptions it
+/// made, in the order they were found.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConversionReport {
+    pub warnings: Vec<String>,
+}
+
+impl ConversionReport {
+    /// is_empty reports whether the conversion was lossless.
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    fn warn(&mut self, warning: String) {
+        self.warnings.push(warning);
+    }
+}
+
+/// Capability is a cluster feature that a component, or a trait attached to it, needs.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Capability {
+    /// An extended resource, such as `nvidia.com/gpu`.
+    ExtendedResource(String),
+    /// A persistent volume access mode, such as `ReadWriteMany`.
+    AccessMode(String),
+    /// An API group and version, such as `autoscaling/v2beta1`.
+    ApiVersion(String),
+}
+
+impl std::fmt::Display for Capability {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Capability::ExtendedResource(name) => write!(f, "extended resource {}", name),
+            Capability::AccessMode(mode) => write!(f, "access mode {}", mode),
+            Capability::ApiVersion(version) => write!(f, "API version {}", version),
+        }
+    }
+}
+
+/// ContainerProbes summarizes the health probes of a container.
+///
+/// A probe that is not set is None, otherwise it lists the handlers the probe declares.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerProbes {
+    pub container: String,
+    pub liveness: Option<Vec<ProbeHandler>>,
+    pub readiness: Option<Vec<ProbeHandler>>,
+}
+
+/// ProbeHandler is the kind of check a health probe performs.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ProbeHandler {
+    Exec,
+    HttpGet,
+    TcpSocket,
+}
+
+/// ImageRef is a container image reference, split into its parts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageRef {
+    /// The registry host, with an optional port, or None for the default registry.
+    pub registry: Option<String>,
+    pub repository: String,
+    pub tag: Option<String>,
+    pub digest: Option<String>,
+}
+
+impl std::str::FromStr for ImageRef {
+    type Err = failure::Error;
+
+    /// Parse an image reference such as `nginx`, `docker.io/library/nginx:1.25` or
+    /// `nginx@sha256:...`.
+    fn from_str(image: &str) -> Result<Self, Self::Err> {
+        lazy_static! {
+            static ref REGISTRY: Regex = Regex::new(
+                r"^[A-Za-z0-9]([A-Za-z0-9-]*[A-Za-z0-9])?(\.[A-Za-z0-9]([A-Za-z0-9-]*[A-Za-z0-9])?)*(:[0-9]+)?$"
+            )
+            .unwrap();
+            static ref PATH_COMPONENT: Regex =
+                Regex::new(r"^[a-z0-9]+((\.|_|__|-+)[a-z0-9]+)*$").unwrap();
+            static ref TAG: Regex = Regex::new(r"^[A-Za-z0-9_][A-Za-z0-9_.-]{0,127}$").unwrap();
+            static ref DIGEST: Regex =
+                Regex::new(r"^[A-Za-z][A-Za-z0-9]*([+._-][A-Za-z][A-Za-z0-9]*)*:[0-9a-fA-F]{32,}$")
+                    .unwrap();
+        }
+        let invalid =
+            |part: &str| format_err!("invalid image reference {}: malformed {}", image, part);
+        let (name, digest) = match image.find('@') {
+            Some(i) => (&image[..i], Some(&image[i + 1..])),
+            None => (image, None),
+        };
+        if digest.map_or(false, |d| !DIGEST.is_match(d)) {
+            return Err(invalid("digest"));
+        }
+        // A colon after the last slash starts the tag; an earlier one is a registry port.
+        let (name, tag) = match name.rfind(':') {
+            Some(i) if !name[i + 1..].contains('/') => (&name[..i], Some(&name[i + 1..])),
+            _ => (name, None),
+        };
+        if tag.map_or(false, |t| !TAG.is_match(t)) {
+            return Err(invalid("tag"));
+        }
+        let mut components: Vec<&str> = name.split('/').collect();
+        // The first component names a registry only if it looks like a host.
+        let first = components[0];
+        let registry = if components.len() > 1
+            && (first.contains('.') || first.contains(':') || first == "localhost")
+        {
+            if !REGISTRY.is_match(first) {
+                return Err(invalid("registry"));
+            }
+            components.remove(0);
+            Some(first.to_string())
+        } else {
+            None
+        };
+        if components.iter().any(|c| !PATH_COMPONENT.is_match(c)) {
+            return Err(invalid("repository"));
+        }
+        Ok(ImageRef {
+            registry,
+            repository: components.join("/"),
+            tag: tag.map(|t| t.to_string()),
+            digest: digest.map(|d| d.to_string()),
+        })
+    }
 }
 
 /// ConfigFile describes locations to write configuration as files accessible within the container
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfigFile {
     pub path: String,
@@ -356,78 +3084,503 @@ impl ConfigFile {
 }
 
 /// Env describes an environment variable for a container.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Env {
     pub name: String,
     pub value: Option<String>,
     pub from_param: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_from: Option<EnvSource>,
 }
 impl Env {
-    pub(crate) fn to_env_var(&self, params: ParamMap) -> core::EnvVar {
-        let value = resolve_value_string(params, self.from_param.clone(), self.value.clone());
-        // FIXME: This needs to support fromParam
-        core::EnvVar {
+    /// validate checks that the name is one Kubernetes accepts for an environment variable.
+    pub fn validate(&self) -> Result<(), Error> {
+        lazy_static! {
+            static ref ENV_NAME: Regex = Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap();
+        }
+        if self.name.is_empty() {
+            return Err(format_err!("environment variable name must not be empty"));
+        }
+        if !ENV_NAME.is_match(self.name.as_str()) {
+            return Err(format_err!(
+                "environment variable name {} must consist of letters, digits and '_', and must not start with a digit",
+                self.name
+            ));
+        }
+        if let Some(source) = self.value_from.as_ref() {
+            source
+                .validate()
+                .map_err(|e| format_err!("environment variable {}: {}", self.name, e))?;
+        }
+        Ok(())
+    }
+
+    /// resolve renders the environment variable, taking its value from the first of these
+    /// that is set: `valueFrom`, the parameter `fromParam` names, and `value`.
+    ///
+    /// `value` is the fallback for a parameter with no value, which is reported. A
+    /// `valueFrom` that overrides `fromParam` or `value` is reported too. It is an error if
+    /// `valueFrom` is invalid.
+    pub fn resolve(
+        &self,
+        params: &ParamMap,
+        report: &mut ConversionReport,
+    ) -> Result<core::EnvVar, Error> {
+        if let Some(source) = self.value_from.as_ref() {
+            source
+                .validate()
+                .map_err(|e| format_err!("environment variable {}: {}", self.name, e))?;
+            let overridden = [
+                ("fromParam", self.from_param.is_some()),
+                ("value", self.value.is_some()),
+            ];
+            for (field, set) in overridden.iter() {
+                if *set {
+                    report.warn(format!(
+                        "environment variable {} takes its value from valueFrom, which overrides its {}",
+                        self.name, field
+                    ));
+                }
+            }
+            return Ok(core::EnvVar {
+                name: self.name.clone(),
+                value: None,
+                value_from: Some(source.to_env_var_source()),
+            });
+        }
+        let param_value = match self.from_param.as_ref() {
+            None => None,
+            Some(param) => match params.get(param) {
+                Some(serde_json::Value::String(s)) => Some(s.clone()),
+                Some(value) => Some(value.to_string()),
+                None => {
+                    let fallback = match self.value {
+                        Some(_) => "its value is used instead",
+                        None => "it is left empty",
+                    };
+                    report.warn(format!(
+                        "environment variable {} refers to parameter {}, which has no value, so {}",
+                        self.name, param, fallback
+                    ));
+                    None
+                }
+            },
+        };
+        Ok(core::EnvVar {
             name: self.name.clone(),
-            value,
+            value: param_value.or_else(|| self.value.clone()),
             value_from: None,
+        })
+    }
+}
+
+/// env_references returns the variables an environment variable value refers to as
+/// `$(VAR)`, which Kubernetes expands when it starts the container.
+///
+/// Values are rendered as they are written, so `$$` stays an escaped `$` for Kubernetes
+/// to unescape, and `$$(VAR)` is the literal text `$(VAR)` rather than a reference.
+pub fn env_references(value: &str) -> Vec<String> {
+    let mut references = vec![];
+    let mut rest = value;
+    while let Some(i) = rest.find('$') {
+        let after = &rest[i + 1..];
+        if after.starts_with('$') {
+            rest = &after[1..];
+            continue;
+        }
+        if after.starts_with('(') {
+            if let Some(end) = after.find(')') {
+                references.push(after[1..end].to_string());
+                rest = &after[end + 1..];
+                continue;
+            }
+        }
+        rest = after;
+    }
+    references
+}
+
+/// EnvSource describes an environment variable whose value the pod supplies through the
+/// downward API.
+///
+/// Exactly one of `fieldRef` and `resourceFieldRef` must be set.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvSource {
+    pub field_ref: Option<FieldRef>,
+    pub resource_field_ref: Option<ResourceFieldRef>,
+}
+impl EnvSource {
+    fn validate(&self) -> Result<(), Error> {
+        match (self.field_ref.as_ref(), self.resource_field_ref.as_ref()) {
+            (Some(field), None) => field.validate(),
+            (None, Some(resource)) => resource.validate(),
+            _ => Err(format_err!(
+                "valueFrom must set exactly one of fieldRef and resourceFieldRef"
+            )),
+        }
+    }
+
+    fn to_env_var_source(&self) -> core::EnvVarSource {
+        core::EnvVarSource {
+            field_ref: self.field_ref.as_ref().map(|f| core::ObjectFieldSelector {
+                api_version: None,
+                field_path: f.field_path.clone(),
+            }),
+            resource_field_ref: self.resource_field_ref.as_ref().map(|r| {
+                core::ResourceFieldSelector {
+                    container_name: r.container_name.clone(),
+                    divisor: r.divisor.clone().map(Quantity),
+                    resource: r.resource.clone(),
+                }
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+/// FieldRef selects a field of the pod, such as `metadata.name` or `status.podIP`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldRef {
+    pub field_path: String,
+}
+impl FieldRef {
+    fn validate(&self) -> Result<(), Error> {
+        let path = self.field_path.as_str();
+        let selectable = DOWNWARD_API_FIELDS.contains(&path)
+            || path.starts_with("metadata.labels['")
+            || path.starts_with("metadata.annotations['");
+        if !selectable {
+            return Err(format_err!(
+                "fieldRef {} is not a field the downward API supplies",
+                path
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// ResourceFieldRef selects a resource request or limit of a container, such as
+/// `limits.memory`. It defaults to the container the variable is set in.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceFieldRef {
+    pub resource: String,
+    pub container_name: Option<String>,
+    pub divisor: Option<String>,
+}
+impl ResourceFieldRef {
+    fn validate(&self) -> Result<(), Error> {
+        if !DOWNWARD_API_RESOURCES.contains(&self.resource.as_str()) {
+            return Err(format_err!(
+                "resourceFieldRef {} must be one of {}",
+                self.resource,
+                DOWNWARD_API_RESOURCES.join(", ")
+            ));
         }
+        Ok(())
     }
 }
 
 /// Port describes a port on a Container.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+///
+/// The container port may be given as an integer, or as a string. A string is either
+/// a number, or the name of a parameter that supplies the port number.
+///
+/// A port may instead give a `containerPortRange`, which is rendered as one port per
+/// number in the range, each named `<name>-<number>`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Port {
     pub name: String,
-    pub container_port: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_port: Option<IntOrString>,
 
     #[serde(default)]
     pub protocol: PortProtocol,
+
+    /// The application protocol carried on the port, such as `http` or `grpc`, for
+    /// service meshes that route by protocol.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_protocol: Option<String>,
+
+    /// A contiguous range of ports to expose in place of `container_port`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_port_range: Option<PortRange>,
 }
 impl Port {
     pub fn basic(name: String, container_port: i32) -> Self {
         Port {
             name,
-            container_port,
+            container_port: Some(IntOrString::Int(container_port)),
             protocol: PortProtocol::TCP,
+            app_protocol: None,
+            container_port_range: None,
+        }
+    }
+    /// validate checks that the application protocol, if any, is one Rudr knows, and that
+    /// the port range, if any, is in order, no larger than Rudr expands, and only names its
+    /// ports with valid port names.
+    pub fn validate(&self) -> Result<(), Error> {
+        if let Some(p) = self.app_protocol.as_ref() {
+            if !APP_PROTOCOLS.contains(&p.as_str()) {
+                return Err(format_err!(
+                    "port {} has appProtocol {}, which must be one of {}",
+                    self.name,
+                    p,
+                    APP_PROTOCOLS.join(", ")
+                ));
+            }
         }
+        let has_port = self.container_port.is_some();
+        match self.container_port_range.as_ref() {
+            Some(_) if has_port => {
+                return Err(format_err!(
+                    "port {} cannot set both containerPort and containerPortRange",
+                    self.name
+                ))
+            }
+            None if !has_port => {
+                return Err(format_err!(
+                    "port {} must set containerPort or containerPortRange",
+                    self.name
+                ))
+            }
+            _ => {}
+        }
+        if let Some(range) = self.container_port_range.as_ref() {
+            if range.start < 1 || range.end > 65535 {
+                return Err(format_err!(
+                    "port {} has a range of {}-{}, which must be within 1-65535",
+                    self.name,
+                    range.start,
+                    range.end
+                ));
+            }
+            if range.start > range.end {
+                return Err(format_err!(
+                    "port {} has a range of {}-{}, whose start is after its end",
+                    self.name,
+                    range.start,
+                    range.end
+                ));
+            }
+            if range.end - range.start + 1 > MAX_PORT_RANGE_SIZE {
+                return Err(format_err!(
+                    "port {} has a range of {}-{}, which is more than {} ports",
+                    self.name,
+                    range.start,
+                    range.end,
+                    MAX_PORT_RANGE_SIZE
+                ));
+            }
+            // Kubernetes limits a port name to 15 characters, which the longest number of
+            // the range may push a name past.
+            let longest = format!("{}-{}", self.name, range.end);
+            if longest.len() > MAX_PORT_NAME_LENGTH {
+                return Err(format_err!(
+                    "port {} has a range of {}-{}, which names its ports up to {}, longer than {} characters",
+                    self.name,
+                    range.start,
+                    range.end,
+                    longest,
+                    MAX_PORT_NAME_LENGTH
+                ));
+            }
+        }
+        Ok(())
     }
-    fn to_container_port(&self) -> core::ContainerPort {
-        core::ContainerPort {
-            container_port: self.container_port,
+    /// expand returns the individual ports this port stands for: the port itself, or one
+    /// port per number in its range.
+    pub fn expand(&self) -> Vec<Port> {
+        match self.container_port_range.as_ref() {
+            Some(range) => (range.start..=range.end)
+                .map(|n| Port {
+                    name: format!("{}-{}", self.name, n),
+                    container_port: Some(IntOrString::Int(n)),
+                    container_port_range: None,
+                    ..self.clone()
+                })
+                .collect(),
+            None => vec![self.clone()],
+        }
+    }
+    /// resolve_port returns the numeric container port, resolving string ports.
+    ///
+    /// A port outside 1-65535 is an error.
+    pub fn resolve_port(&self, params: &ParamMap) -> Result<i32, Error> {
+        let port = match self
+            .container_port
+            .as_ref()
+            .ok_or_else(|| format_err!("port {} has no containerPort", self.name))?
+        {
+            IntOrString::Int(port) => i64::from(*port),
+            IntOrString::String(s) => s
+                .parse::<i64>()
+                .ok()
+                .or_else(|| {
+                    params
+                        .get(s.as_str())
+                        .and_then(|v| v.as_i64().or_else(|| v.as_str()?.parse().ok()))
+                })
+                .ok_or_else(|| {
+                    format_err!("port {} references unresolved name {}", self.name, s)
+                })?,
+        };
+        match i32::try_from(port) {
+            Ok(number) if (1..=65535).contains(&number) => Ok(number),
+            _ => Err(format_err!(
+                "port {} has container port {}, which is not between 1 and 65535",
+                self.name,
+                port
+            )),
+        }
+    }
+    fn to_container_port(&self, params: &ParamMap) -> Result<core::ContainerPort, Error> {
+        Ok(core::ContainerPort {
+            container_port: self.resolve_port(params)?,
             name: Some(self.name.clone()),
             protocol: Some(self.protocol.to_string()),
             ..Default::default()
-        }
+        })
     }
-    pub fn to_service_port(&self) -> core::ServicePort {
-        let port = self.container_port;
-        core::ServicePort {
+    pub fn to_service_port(&self, params: &ParamMap) -> Result<core::ServicePort, Error> {
+        let port = self.resolve_port(params)?;
+        Ok(core::ServicePort {
             port,
             target_port: Some(IntOrString::Int(port)),
             name: Some(self.name.clone()),
             protocol: Some(self.protocol.to_string()),
             ..Default::default()
+        })
+    }
+    /// to_named_service_port targets the container port by name rather than by number.
+    ///
+    /// Unnamed ports fall back to the numeric container port.
+    pub fn to_named_service_port(&self, params: &ParamMap) -> Result<core::ServicePort, Error> {
+        let mut svc_port = self.to_service_port(params)?;
+        if !self.name.is_empty() {
+            svc_port.target_port = Some(IntOrString::String(self.name.clone()));
         }
+        Ok(svc_port)
     }
 }
 
+/// PortRange describes a contiguous range of ports, including both ends.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PortRange {
+    pub start: i32,
+    pub end: i32,
+}
+
 /// HealthProbe describes a probe used to check on the health of a Container.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+///
+/// Each threshold has its own default, so a probe that only specifies a handler
+/// still gets the documented Kubernetes values.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
-#[serde(default)]
 pub struct HealthProbe {
     pub exec: Option<Exec>,
     pub http_get: Option<HttpGet>,
     pub tcp_socket: Option<TcpSocket>,
+    #[serde(default = "default_initial_delay_seconds")]
     pub initial_delay_seconds: i32,
+    #[serde(default = "default_period_seconds")]
     pub period_seconds: i32,
+    #[serde(default = "default_timeout_seconds")]
     pub timeout_seconds: i32,
+    #[serde(default = "default_success_threshold")]
     pub success_threshold: i32,
+    #[serde(default = "default_failure_threshold")]
     pub failure_threshold: i32,
+    pub from_param: Option<ProbeParams>,
 }
 impl HealthProbe {
+    /// resolve_thresholds returns a copy of this probe with any parameter-sourced
+    /// thresholds replaced by their resolved values.
+    ///
+    /// Parameters that were not supplied leave the static value in place. Supplied
+    /// values that are not integers are an error.
+    pub fn resolve_thresholds(&self, params: ParamMap) -> Result<HealthProbe, Error> {
+        let mut probe = self.clone();
+        let refs = match self.from_param.as_ref() {
+            Some(refs) => refs,
+            None => return Ok(probe),
+        };
+        probe.initial_delay_seconds = resolve_i32(
+            &params,
+            &refs.initial_delay_seconds,
+            self.initial_delay_seconds,
+        )?;
+        probe.period_seconds = resolve_i32(&params, &refs.period_seconds, self.period_seconds)?;
+        probe.timeout_seconds = resolve_i32(&params, &refs.timeout_seconds, self.timeout_seconds)?;
+        probe.success_threshold =
+            resolve_i32(&params, &refs.success_threshold, self.success_threshold)?;
+        probe.failure_threshold =
+            resolve_i32(&params, &refs.failure_threshold, self.failure_threshold)?;
+        Ok(probe)
+    }
+    /// validate checks the numeric relationships Kubernetes enforces between thresholds.
+    ///
+    /// Thresholds must not be negative, and a probe must time out before the next
+    /// probe is due.
+    pub fn validate(&self) -> Result<(), Error> {
+        let thresholds = [
+            ("initialDelaySeconds", self.initial_delay_seconds),
+            ("periodSeconds", self.period_seconds),
+            ("timeoutSeconds", self.timeout_seconds),
+            ("successThreshold", self.success_threshold),
+            ("failureThreshold", self.failure_threshold),
+        ];
+        for (name, value) in thresholds.iter() {
+            if *value < 0 {
+                return Err(format_err!("{} must not be negative, got {}", name, value));
+            }
+        }
+        if self.timeout_seconds > self.period_seconds {
+            return Err(format_err!(
+                "timeoutSeconds ({}) must not exceed periodSeconds ({})",
+                self.timeout_seconds,
+                self.period_seconds
+            ));
+        }
+        Ok(())
+    }
+
+    /// validate_liveness checks a liveness probe, which Kubernetes additionally
+    /// requires to have a successThreshold of 1.
+    pub fn validate_liveness(&self) -> Result<(), Error> {
+        self.validate()?;
+        if self.success_threshold != 1 {
+            return Err(format_err!(
+                "successThreshold must be 1 for a liveness probe, got {}",
+                self.success_threshold
+            ));
+        }
+        Ok(())
+    }
+
+    /// handlers lists the kinds of handler the probe declares.
+    pub fn handlers(&self) -> Vec<ProbeHandler> {
+        let mut handlers = vec![];
+        if self.exec.is_some() {
+            handlers.push(ProbeHandler::Exec);
+        }
+        if self.http_get.is_some() {
+            handlers.push(ProbeHandler::HttpGet);
+        }
+        if self.tcp_socket.is_some() {
+            handlers.push(ProbeHandler::TcpSocket);
+        }
+        handlers
+    }
+
+    fn to_resolved_probe(&self, params: ParamMap) -> Result<core::Probe, Error> {
+        Ok(self.resolve_thresholds(params)?.to_probe())
+    }
     fn to_probe(&self) -> core::Probe {
         core::Probe {
             failure_threshold: Some(self.failure_threshold),
@@ -457,39 +3610,135 @@ impl Default for HealthProbe {
             exec: None,
             http_get: None,
             tcp_socket: None,
-            initial_delay_seconds: 0,
-            period_seconds: 10,
-            timeout_seconds: 1,
-            success_threshold: 1,
-            failure_threshold: 3,
+            initial_delay_seconds: default_initial_delay_seconds(),
+            period_seconds: default_period_seconds(),
+            timeout_seconds: default_timeout_seconds(),
+            success_threshold: default_success_threshold(),
+            failure_threshold: default_failure_threshold(),
+            from_param: None,
+        }
+    }
+}
+
+/// Supplies the default delay before a probe is first run.
+fn default_initial_delay_seconds() -> i32 {
+    0
+}
+
+/// Supplies the default interval between probes.
+fn default_period_seconds() -> i32 {
+    10
+}
+
+/// Supplies the default probe timeout.
+fn default_timeout_seconds() -> i32 {
+    1
+}
+
+/// Supplies the default number of successes for a probe to be considered passing.
+fn default_success_threshold() -> i32 {
+    1
+}
+
+/// Supplies the default number of failures for a probe to be considered failing.
+fn default_failure_threshold() -> i32 {
+    3
+}
+
+/// ProbeParams names the parameters that a HealthProbe's thresholds are sourced from.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbeParams {
+    pub initial_delay_seconds: Option<String>,
+    pub period_seconds: Option<String>,
+    pub timeout_seconds: Option<String>,
+    pub success_threshold: Option<String>,
+    pub failure_threshold: Option<String>,
+}
+
+/// Resolve an integer probe setting from the named parameter, falling back to the given value.
+fn resolve_i32(params: &ParamMap, from_param: &Option<String>, value: i32) -> Result<i32, Error> {
+    let name = match from_param {
+        Some(name) => name,
+        None => return Ok(value),
+    };
+    match params.get(name.as_str()) {
+        None | Some(serde_json::Value::Null) => Ok(value),
+        Some(v) => v
+            .as_i64()
+            .or_else(|| v.as_f64().filter(|f| f.fract() == 0.0).map(|f| f as i64))
+            .filter(|i| *i >= i64::from(std::i32::MIN) && *i <= i64::from(std::i32::MAX))
+            .map(|i| i as i32)
+            .ok_or_else(|| format_err!("expected integer value for parameter {}", name)),
+    }
+}
+
+/// Lifecycle describes the hooks run after a container starts and before it stops.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Lifecycle {
+    pub post_start: Option<LifecycleHandler>,
+    pub pre_stop: Option<LifecycleHandler>,
+}
+impl Lifecycle {
+    fn to_lifecycle(&self) -> core::Lifecycle {
+        core::Lifecycle {
+            post_start: self.post_start.as_ref().map(|h| h.to_handler()),
+            pre_stop: self.pre_stop.as_ref().map(|h| h.to_handler()),
+        }
+    }
+}
+
+/// LifecycleHandler describes the action taken by a lifecycle hook.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LifecycleHandler {
+    pub exec: Option<Exec>,
+    pub http_get: Option<HttpGet>,
+    pub tcp_socket: Option<TcpSocket>,
+}
+impl LifecycleHandler {
+    fn to_handler(&self) -> core::Handler {
+        core::Handler {
+            exec: self.exec.as_ref().map(|c| core::ExecAction {
+                command: Some(c.command.clone()),
+            }),
+            http_get: self.http_get.as_ref().map(|a| a.to_http_get_action()),
+            tcp_socket: self.tcp_socket.as_ref().map(|t| t.to_tcp_socket_action()),
         }
     }
 }
 
 /// Exec describes a shell command, as an array, for execution in a Container.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Exec {
     pub command: Vec<String>,
 }
 
 /// HttpGet describes an HTTP GET request used to probe a container.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct HttpGet {
     pub path: String,
     pub port: i32,
+    #[serde(default)]
     pub http_headers: Vec<HttpHeader>,
 }
 impl HttpGet {
     fn to_http_get_action(&self) -> core::HTTPGetAction {
-        core::HTTPGetAction {
-            http_headers: Some(
+        let http_headers = if self.http_headers.is_empty() {
+            None
+        } else {
+            Some(
                 self.http_headers
                     .iter()
                     .map(|h| h.to_kube_header())
                     .collect(),
-            ),
+            )
+        };
+        core::HTTPGetAction {
+            http_headers,
             path: Some(self.path.clone()),
             port: IntOrString::Int(self.port),
             ..Default::default()
@@ -501,7 +3750,7 @@ impl HttpGet {
 ///
 /// Headers are not stored as a map of name/value because the same header is allowed
 /// multiple times.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct HttpHeader {
     pub name: String,
@@ -517,7 +3766,7 @@ impl HttpHeader {
 }
 
 /// TcpSocket defines a socket used for health probing.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct TcpSocket {
     pub port: i32,
@@ -534,7 +3783,7 @@ impl TcpSocket {
 type ExtendedResources = Vec<ExtendedResource>;
 
 /// Resources defines the resources required by a container.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 #[serde(default)]
 pub struct Resources {
@@ -546,7 +3795,9 @@ pub struct Resources {
 }
 
 impl Resources {
-    fn to_resource_requirements(&self) -> core::ResourceRequirements {
+    /// to_resource_requirements renders the requests and limits. Memory is a count of
+    /// mebibytes or a quantity with a suffix, and is rendered as a quantity either way.
+    fn to_resource_requirements(&self) -> Result<core::ResourceRequirements, Error> {
         let mut requests = BTreeMap::new();
         requests.insert(
             "cpu".to_string(),
@@ -554,22 +3805,117 @@ impl Resources {
         );
         requests.insert(
             "memory".to_string(),
-            Quantity(self.memory.required.clone() + "Mi"),
+            quantity::memory_quantity(quantity::parse_memory(self.memory.required.as_str())?),
         );
-        // TODO: Kubernetes does not have a built-in type for GPUs. What do we use?
-        core::ResourceRequirements {
+        let mut limits = BTreeMap::new();
+        if let Some(cpu) = self.cpu.limit {
+            limits.insert("cpu".to_string(), Quantity(cpu.to_string()));
+        }
+        if let Some(memory) = self.memory.limit.as_ref() {
+            limits.insert(
+                "memory".to_string(),
+                quantity::memory_quantity(quantity::parse_memory(memory.as_str())?),
+            );
+        }
+        // GPUs are an extended resource, so they are set as a limit, and as a request only
+        // when asked to. Kubernetes requires the two to be equal.
+        if let Some(gpu) = self.gpu.as_ref() {
+            if let Some(quantity) = gpu.quantity() {
+                limits.insert(GPU_RESOURCE_NAME.to_string(), quantity.clone());
+                if gpu.mirror_requests {
+                    requests.insert(GPU_RESOURCE_NAME.to_string(), quantity);
+                }
+            }
+        }
+        Ok(core::ResourceRequirements {
             requests: Some(requests),
-            limits: None,
+            limits: if limits.is_empty() {
+                None
+            } else {
+                Some(limits)
+            },
+        })
+    }
+
+    /// validate_limits checks that the CPU and memory limits, when set, are at least the
+    /// requests.
+    pub fn validate_limits(&self) -> Result<(), Error> {
+        if let Some(limit) = self.cpu.limit {
+            if limit < self.cpu.required {
+                return Err(format_err!(
+                    "CPU limit {} is less than the request of {}",
+                    limit,
+                    self.cpu.required
+                ));
+            }
+        }
+        if let Some(limit) = self.memory.limit.as_ref() {
+            let required = quantity::parse_memory(self.memory.required.as_str())?;
+            if quantity::parse_memory(limit.as_str())? < required {
+                return Err(format_err!(
+                    "memory limit {} is less than the request of {}",
+                    limit,
+                    self.memory.required
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// with_overcommit returns a copy of the resources whose CPU and memory limits are
+    /// the requests multiplied by the ratio.
+    ///
+    /// A ratio below 1 would limit a container to less than it requests, and is an
+    /// error. The CPU limit is rounded to a millicore and the memory limit up to a
+    /// mebibyte, so both render as valid quantities.
+    pub fn with_overcommit(&self, ratio: f64) -> Result<Resources, Error> {
+        if !ratio.is_finite() || ratio < 1.0 {
+            return Err(format_err!(
+                "overcommit ratio must be at least 1, got {}",
+                ratio
+            ));
         }
+        let memory = quantity::parse_memory(self.memory.required.as_str())?;
+        let mut resources = self.clone();
+        resources.cpu.limit = Some((self.cpu.required * ratio * 1000.0).round() / 1000.0);
+        resources.memory.limit = Some((memory * ratio / quantity::MEBIBYTE).ceil().to_string());
+        Ok(resources)
+    }
+}
+
+/// default_resources_for returns the resources of a container of the given workload type
+/// that omits `resources`.
+///
+/// Tasks run to completion, so they get more CPU and memory to finish sooner. Every other
+/// workload type gets `Resources::default()`. A component overrides these with its
+/// `defaultResources`, and a container by declaring its own `resources`.
+pub fn default_resources_for(workload_type: &str) -> Resources {
+    match workload_info(workload_type) {
+        Ok(ref info) if info.run_to_completion => Resources {
+            cpu: CPU {
+                required: 0.5,
+                limit: None,
+            },
+            memory: Memory {
+                required: "256".into(),
+                limit: None,
+            },
+            ..Default::default()
+        },
+        _ => Resources::default(),
     }
 }
 
 impl Default for Resources {
     fn default() -> Self {
         Resources {
-            cpu: CPU { required: 0.1 },
+            cpu: CPU {
+                required: 0.1,
+                limit: None,
+            },
             memory: Memory {
                 required: "128".into(),
+                limit: None,
             },
             gpu: None,
             volumes: None,
@@ -580,35 +3926,58 @@ impl Default for Resources {
 
 /// CPU describes a CPU resource allocation for a container.
 ///
-/// It indicates how much CPU (core count) is required for this container to operate.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// It indicates how much CPU (core count) is required for this container to operate,
+/// and optionally the most it may use.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct CPU {
     pub required: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<f64>,
 }
 
 /// Memory describes the memory allocation for a container.
 ///
-/// It indicates the required amount of memory for a container to operate.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// It indicates the required amount of memory for a container to operate, and
+/// optionally the most it may use, in the same notation.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Memory {
     pub required: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<String>,
 }
 
 /// GPU describes a Container's need for a GPU.
 ///
-/// It indicates how many (if any) GPU cores a container needs to operate.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// It indicates how many (if any) GPU cores a container needs to operate, and whether
+/// that count is set as a resource request as well as a limit.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct GPU {
     pub required: f64,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mirror_requests: bool,
+}
+
+impl GPU {
+    /// quantity returns the number of GPUs as a resource quantity, or None when none
+    /// are required or the count is not a finite number.
+    ///
+    /// An extended resource cannot be fractional, so a fractional count is rounded up.
+    fn quantity(&self) -> Option<Quantity> {
+        if self.required.is_finite() && self.required > 0.0 {
+            Some(Quantity((self.required.ceil() as i64).to_string()))
+        } else {
+            None
+        }
+    }
 }
 
 /// Volume describes a path that is attached to a Container.
 ///
 /// It specifies not only the location, but also the requirements.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Volume {
     pub name: String,
@@ -620,10 +3989,160 @@ pub struct Volume {
     #[serde(default)]
     pub sharing_policy: SharingPolicy,
     pub disk: Option<Disk>,
+
+    /// Content of a file to mount at the mount path instead of a disk.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inline_content: Option<InlineContent>,
+
+    /// A path within the volume to mount instead of its root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub_path: Option<String>,
+
+    /// How mounts are propagated between the host and the container: `None`,
+    /// `HostToContainer`, or `Bidirectional`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mount_propagation: Option<String>,
+}
+
+impl Volume {
+    /// Generate a PersistentVolumeClaim template requesting the disk size of this volume.
+    pub fn to_volume_claim_template(&self) -> core::PersistentVolumeClaim {
+        let mut requests = BTreeMap::new();
+        requests.insert(
+            "storage".to_string(),
+            Quantity(self.disk.clone().unwrap_or_default().required),
+        );
+        core::PersistentVolumeClaim {
+            metadata: Some(meta::ObjectMeta {
+                name: Some(self.name.clone()),
+                ..Default::default()
+            }),
+            spec: Some(core::PersistentVolumeClaimSpec {
+                access_modes: Some(vec![self.to_access_mode()]),
+                resources: Some(core::ResourceRequirements {
+                    requests: Some(requests),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// The name of the ConfigMap holding this volume's inline content.
+    pub fn config_map_name(&self, container_name: &str) -> String {
+        format!("{}-{}", container_name, self.name)
+    }
+
+    /// validate_mount checks the sub path and mount propagation of the volume's mount.
+    pub fn validate_mount(&self) -> Result<(), Error> {
+        if let Some(sub_path) = self.sub_path.as_ref() {
+            if self.inline_content.is_some() {
+                return Err(format_err!(
+                    "volume {}: a sub path cannot be used with inline content",
+                    self.name
+                ));
+            }
+            let path = Path::new(sub_path.as_str());
+            if path.is_absolute() || path.components().any(|c| c.as_os_str() == "..") {
+                return Err(format_err!(
+                    "volume {}: sub path {} must be relative and must not contain '..'",
+                    self.name,
+                    sub_path
+                ));
+            }
+        }
+        if let Some(propagation) = self.mount_propagation.as_ref() {
+            if !MOUNT_PROPAGATION_MODES.contains(&propagation.as_str()) {
+                return Err(format_err!(
+                    "volume {}: mount propagation {} must be one of {}",
+                    self.name,
+                    propagation,
+                    MOUNT_PROPAGATION_MODES.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The file name that inline content is mounted as.
+    fn file_name(&self) -> String {
+        Path::new(self.mount_path.as_str())
+            .file_name()
+            .and_then(|f| f.to_str())
+            .unwrap_or_else(|| self.name.as_str())
+            .to_string()
+    }
+
+    /// Map the access mode and sharing policy onto a Kubernetes persistent volume access mode.
+    pub(crate) fn to_access_mode(&self) -> String {
+        match (&self.access_mode, &self.sharing_policy) {
+            (AccessMode::RO, _) => "ReadOnlyMany",
+            (AccessMode::RWX, _) => "ReadWriteMany",
+            (AccessMode::RW, SharingPolicy::Shared) => "ReadWriteMany",
+            (AccessMode::RW, SharingPolicy::Exclusive) => "ReadWriteOnce",
+        }
+        .to_string()
+    }
+}
+
+/// InlineContent is a small file embedded directly in a component.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineContent {
+    pub value: String,
+
+    #[serde(default)]
+    pub encoding: ContentEncoding,
+}
+
+impl InlineContent {
+    /// Decode the content into the bytes of the file.
+    ///
+    /// The decoded content must fit in a ConfigMap, which is limited to 1MiB.
+    pub fn decode(&self) -> Result<Vec<u8>, Error> {
+        let content = match self.encoding {
+            ContentEncoding::Plain => self.value.clone().into_bytes(),
+            ContentEncoding::Base64 => base64::decode(self.value.trim())?,
+            ContentEncoding::Gzip => {
+                let compressed = base64::decode(self.value.trim())?;
+                let mut content = vec![];
+                // Read one byte past the limit so oversized content is detected without
+                // decompressing all of it.
+                GzDecoder::new(compressed.as_slice())
+                    .take(CONFIG_MAP_MAX_BYTES as u64 + 1)
+                    .read_to_end(&mut content)?;
+                content
+            }
+        };
+        if content.len() > CONFIG_MAP_MAX_BYTES {
+            return Err(format_err!(
+                "inline content must be no more than {} bytes",
+                CONFIG_MAP_MAX_BYTES
+            ));
+        }
+        Ok(content)
+    }
+}
+
+/// ContentEncoding describes how inline content is encoded.
+///
+/// Gzip content is compressed with gzip and then base64 encoded.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentEncoding {
+    Plain,
+    Base64,
+    Gzip,
+}
+impl Default for ContentEncoding {
+    fn default() -> Self {
+        ContentEncoding::Plain
+    }
 }
 
 // Disk describes the disk requirements for backing a Volume.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Disk {
     pub required: String,
@@ -640,12 +4159,14 @@ impl Default for Disk {
 
 /// AccessMode defines the access modes for file systems.
 ///
-/// Currently, only read/write and read-only are supported.
+/// Read/write, read-only and read/write by many nodes are supported. A read/write volume
+/// with a Shared sharing policy is also mounted read/write by many nodes.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum AccessMode {
     RW,
     RO,
+    RWX,
 }
 impl Default for AccessMode {
     fn default() -> Self {
@@ -667,7 +4188,7 @@ impl Default for SharingPolicy {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ExtendedResource {
     pub name: String,
@@ -1,9 +1,33 @@
 use crate::schematic::parameter::resolve_parameters;
-use crate::schematic::{component::*, parameter::ParameterType, GroupVersionKind};
-use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
+use crate::schematic::{
+    component::*, configuration::ComponentConfiguration, parameter::ParameterType,
+    parameter::ParameterValue, traits::TraitBinding, Application, GroupVersionKind, OAMStatus,
+};
+use crate::workload_type::{render_all, render_all_in_namespace, set_namespace};
+use k8s_openapi::apimachinery::pkg::{api::resource::Quantity, util::intstr::IntOrString};
 use std::collections::BTreeMap;
 use std::str::FromStr;
 
+/// workload_setting returns an optional setting with a fixed value, typed after that value.
+pub(crate) fn workload_setting(name: &str, value: serde_json::Value) -> WorkloadSetting {
+    let parameter_type = match value {
+        serde_json::Value::Null => ParameterType::Null,
+        serde_json::Value::Bool(_) => ParameterType::Boolean,
+        serde_json::Value::Number(_) => ParameterType::Number,
+        serde_json::Value::String(_) => ParameterType::String,
+        serde_json::Value::Array(_) => ParameterType::Array,
+        serde_json::Value::Object(_) => ParameterType::Object,
+    };
+    WorkloadSetting {
+        name: name.into(),
+        description: None,
+        parameter_type,
+        required: false,
+        value: Some(value),
+        from_param: None,
+    }
+}
+
 #[test]
 fn test_group_version_kind() {
     let gvk = GroupVersionKind::from_str("core.oam.dev/v1alpha1.Singleton");
@@ -18,6 +42,37 @@ fn test_group_version_kind() {
     assert_eq!("core.oam.dev/v1alpha1.Singleton", format!("{}", o))
 }
 
+#[test]
+fn test_group_version_kind_helpers() {
+    let singleton = GroupVersionKind::from_str(DEFAULT_WORKLOAD_TYPE).expect("default gvk");
+    assert!(singleton.is_core_oam());
+    assert_eq!("core.oam.dev/v1alpha1", singleton.api_version());
+
+    let function =
+        GroupVersionKind::from_str("openfaas.com/v1alpha2.Function").expect("custom gvk");
+    assert!(!function.is_core_oam());
+    assert_eq!("openfaas.com/v1alpha2", function.api_version());
+}
+
+#[test]
+fn test_workload_gvk() {
+    let gvk = Component::default()
+        .workload_gvk()
+        .expect("default workload type");
+    assert_eq!("core.oam.dev", gvk.group);
+    assert_eq!("v1alpha1", gvk.version);
+    assert_eq!("Singleton", gvk.kind);
+
+    let malformed = Component {
+        workload_type: "Singleton".into(),
+        ..Default::default()
+    };
+    let err = malformed
+        .workload_gvk()
+        .expect_err("malformed workload type");
+    assert_eq!("missing version and kind", err.to_string());
+}
+
 #[test]
 fn test_component_deserialize() {
     let data = Component::from_str(
@@ -209,7 +264,7 @@ fn test_container_deserialize() {
     assert_eq!(2, container.ports.len());
     let http_port = container.ports.get(0).unwrap();
     assert_eq!("http", http_port.name);
-    assert_eq!(8080, http_port.container_port);
+    assert_eq!(Some(IntOrString::Int(8080)), http_port.container_port);
     assert_eq!(PortProtocol::TCP, http_port.protocol);
 
     assert_eq!(2, container.env.len());
@@ -221,7 +276,7 @@ fn test_container_deserialize() {
     assert!(container.liveness_probe.is_some());
     assert!(container.readiness_probe.is_none());
 
-    let res = &container.resources;
+    let res = container.resources();
 
     assert_eq!("128", res.memory.required);
     assert_eq!(0.1, res.cpu.required);
@@ -503,10 +558,11 @@ fn test_to_env_vars() {
     valmap.insert("two".to_string(), serde_json::json!("2"));
     valmap.insert("three".to_string(), serde_json::json!("3"));
 
-    let one = env[0].to_env_var(valmap.clone());
-    let two = env[1].to_env_var(valmap.clone());
-    let three = env[2].to_env_var(valmap.clone());
-    let four = env[3].to_env_var(valmap.clone());
+    let mut report = ConversionReport::default();
+    let one = env[0].resolve(&valmap, &mut report).expect("one");
+    let two = env[1].resolve(&valmap, &mut report).expect("two");
+    let three = env[2].resolve(&valmap, &mut report).expect("three");
+    let four = env[3].resolve(&valmap, &mut report).expect("four");
 
     assert_eq!("hello one", one.value.expect("found one val").as_str());
     assert_eq!("2", two.value.expect("found two val").as_str());
@@ -520,13 +576,36 @@ fn test_to_env_vars() {
 fn test_to_service_port() {
     let port = Port {
         name: "test".into(),
-        container_port: 443,
+        container_port: Some(IntOrString::Int(443)),
         protocol: PortProtocol::TCP,
+        app_protocol: None,
+        container_port_range: None,
     };
-    assert_eq!(443, port.to_service_port().port);
+    let params = BTreeMap::new();
+    let svc_port = port.to_service_port(&params).expect("service port");
+    assert_eq!(443, svc_port.port);
+    assert_eq!(IntOrString::Int(443), svc_port.target_port.expect("port"));
+}
+
+#[test]
+fn test_to_named_service_port() {
+    let named = Port::basic("web".into(), 8080);
+    let params = BTreeMap::new();
+    let svc_port = named.to_named_service_port(&params).expect("service port");
+    assert_eq!(8080, svc_port.port);
     assert_eq!(
-        IntOrString::Int(443),
-        port.to_service_port().target_port.expect("port")
+        IntOrString::String("web".into()),
+        svc_port.target_port.expect("port")
+    );
+
+    let unnamed = Port::basic("".into(), 8080);
+    assert_eq!(
+        IntOrString::Int(8080),
+        unnamed
+            .to_named_service_port(&params)
+            .expect("service port")
+            .target_port
+            .expect("port")
     );
 }
 
@@ -552,14 +631,19 @@ fn test_to_volume_mounts() {
     let container = Container {
         name: "test_container".into(),
         image: "test/image".into(),
-        resources: Resources {
+        resources: Some(Resources {
             cpu: CPU {
                 required: 0.1.into(),
+                limit: None,
             },
             memory: Memory {
                 required: "128".into(),
+                limit: None,
             },
-            gpu: Some(GPU { required: 0.into() }),
+            gpu: Some(GPU {
+                required: 0.into(),
+                mirror_requests: false,
+            }),
             volumes: Some(vec![
                 Volume {
                     name: "myvol".into(),
@@ -569,6 +653,9 @@ fn test_to_volume_mounts() {
                         ephemeral: true,
                         required: "200M".into(),
                     }),
+                    inline_content: None,
+                    sub_path: None,
+                    mount_propagation: None,
                     sharing_policy: SharingPolicy::Exclusive,
                 },
                 Volume {
@@ -579,11 +666,14 @@ fn test_to_volume_mounts() {
                         ephemeral: false,
                         required: "123M".into(),
                     }),
+                    inline_content: None,
+                    sub_path: None,
+                    mount_propagation: None,
                     sharing_policy: SharingPolicy::Exclusive,
                 },
             ]),
             ..Default::default()
-        },
+        }),
         env: vec![],
         ports: vec![],
         args: None,
@@ -594,8 +684,10 @@ fn test_to_volume_mounts() {
             from_param: None,
         }]),
         image_pull_secret: None,
+        image_pull_policy: None,
         liveness_probe: None,
         readiness_probe: None,
+        lifecycle: None,
     };
     let mounts = container.volume_mounts();
     assert_eq!(mounts.as_ref().expect("at least one mount").len(), 3);
@@ -634,7 +726,8 @@ fn test_to_pod_spec_with_policy() {
         let map = BTreeMap::new();
         let pod = component
             .clone()
-            .to_pod_spec_with_policy(map, "Always".to_string());
+            .to_pod_spec_with_policy(map, "Always".to_string())
+            .expect("pod spec");
         let node_selector = pod.node_selector.clone().expect("node selector btree");
         assert_eq!(
             "linux".to_string(),
@@ -656,7 +749,7 @@ fn test_to_pod_spec_with_policy() {
 
     {
         let map = BTreeMap::new();
-        let pod = component.to_pod_spec(map);
+        let pod = component.to_pod_spec(map).expect("pod spec");
         let node_selector = pod.node_selector.clone().expect("node selector btree");
         assert_eq!(
             "linux".to_string(),
@@ -734,3 +827,4035 @@ fn test_evaluate_configs() {
     exp.insert("container30".to_string(), c30);
     assert_eq!(exp, configs);
 }
+
+#[test]
+fn test_health_probe_validate() {
+    let probe = HealthProbe {
+        tcp_socket: Some(TcpSocket { port: 8080 }),
+        ..Default::default()
+    };
+    assert!(probe.validate().is_ok());
+    assert!(probe.validate_liveness().is_ok());
+
+    let negative = HealthProbe {
+        failure_threshold: -1,
+        ..probe.clone()
+    };
+    let err = negative.validate().expect_err("negative threshold");
+    assert!(err.to_string().contains("failureThreshold"));
+
+    let slow = HealthProbe {
+        timeout_seconds: 20,
+        period_seconds: 10,
+        ..probe.clone()
+    };
+    let err = slow.validate().expect_err("timeout beyond period");
+    assert!(err.to_string().contains("timeoutSeconds"));
+
+    // Readiness probes may require several successes, liveness probes may not.
+    let repeated = HealthProbe {
+        success_threshold: 3,
+        ..probe.clone()
+    };
+    assert!(repeated.validate().is_ok());
+    let err = repeated
+        .validate_liveness()
+        .expect_err("liveness success threshold");
+    assert!(err.to_string().contains("successThreshold"));
+
+    let component = Component {
+        containers: vec![Container {
+            name: "web".into(),
+            image: "nginx:latest".into(),
+            liveness_probe: Some(repeated.clone()),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    assert!(component.validate_probe_params(BTreeMap::new()).is_err());
+    let component = Component {
+        containers: vec![Container {
+            name: "web".into(),
+            image: "nginx:latest".into(),
+            readiness_probe: Some(repeated),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    assert!(component.validate_probe_params(BTreeMap::new()).is_ok());
+}
+
+#[test]
+fn test_validate_probe_ports() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "ports": [
+                        {
+                            "name": "http",
+                            "containerPort": 8080
+                        }
+                    ],
+                    "livenessProbe": {
+                        "httpGet": {
+                            "path": "/healthz",
+                            "port": 8080,
+                            "httpHeaders": []
+                        }
+                    },
+                    "readinessProbe": {
+                        "tcpSocket": {
+                            "port": 8080
+                        }
+                    }
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    assert!(component.validate_probe_ports(BTreeMap::new()).is_empty());
+
+    let mut mismatched = component.clone();
+    mismatched.containers[0].readiness_probe = Some(HealthProbe {
+        tcp_socket: Some(TcpSocket { port: 9090 }),
+        ..Default::default()
+    });
+    let warnings = mismatched.validate_probe_ports(BTreeMap::new());
+    assert_eq!(1, warnings.len());
+    assert!(warnings[0].contains("readiness probe for container web"));
+    assert!(warnings[0].contains("9090"));
+    // A mismatch is only a warning, so the component is still valid.
+    assert!(mismatched.validate().is_ok());
+}
+
+#[test]
+fn test_probe_thresholds_from_param() {
+    let component = Component::from_str(
+        r#"{
+            "parameters": [
+                {
+                    "name": "delay",
+                    "type": "number"
+                }
+            ],
+            "containers": [
+                {
+                    "name": "my_container",
+                    "image": "nginx:latest",
+                    "readinessProbe": {
+                        "tcpSocket": {
+                            "port": 8080
+                        },
+                        "initialDelaySeconds": 5,
+                        "fromParam": {
+                            "initialDelaySeconds": "delay"
+                        }
+                    }
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+
+    let mut params = BTreeMap::new();
+    params.insert("delay".to_string(), serde_json::json!(30));
+    let pod = component.to_pod_spec(params.clone()).expect("pod spec");
+    let probe = pod.containers[0]
+        .readiness_probe
+        .clone()
+        .expect("readiness probe");
+    assert_eq!(Some(30), probe.initial_delay_seconds);
+    assert_eq!(Some(10), probe.period_seconds);
+    assert!(component.validate_probe_params(params).is_ok());
+
+    // An unset parameter leaves the static value in place.
+    let pod = component.to_pod_spec(BTreeMap::new()).expect("pod spec");
+    let probe = pod.containers[0]
+        .readiness_probe
+        .clone()
+        .expect("readiness probe");
+    assert_eq!(Some(5), probe.initial_delay_seconds);
+
+    let mut bad_params = BTreeMap::new();
+    bad_params.insert("delay".to_string(), serde_json::json!("soon"));
+    assert!(component.validate_probe_params(bad_params.clone()).is_err());
+    // Rendering fails too, rather than falling back to the default thresholds.
+    assert!(component.to_pod_spec(bad_params).is_err());
+}
+
+#[test]
+fn test_total_requests() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "one",
+                    "image": "nginx:latest",
+                    "resources": {
+                        "cpu": {"required": 0.5},
+                        "memory": {"required": "512"}
+                    }
+                },
+                {
+                    "name": "two",
+                    "image": "nginx:latest",
+                    "resources": {
+                        "cpu": {"required": 1},
+                        "memory": {"required": "1Gi"}
+                    }
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+
+    let (cpu, memory) = component.total_requests().expect("requests must sum");
+    assert_eq!(Quantity("1500m".into()), cpu);
+    assert_eq!(Quantity("1536Mi".into()), memory);
+
+    // Suffixed memory is rendered as the quantity it stands for.
+    let containers = component
+        .to_containers(BTreeMap::new())
+        .expect("containers");
+    let requests = |i: usize| {
+        containers[i]
+            .resources
+            .clone()
+            .and_then(|r| r.requests)
+            .expect("requests must be set")
+    };
+    assert_eq!(Some(&Quantity("512Mi".into())), requests(0).get("memory"));
+    assert_eq!(Some(&Quantity("1024Mi".into())), requests(1).get("memory"));
+
+    let mut bad = component.clone();
+    bad.containers[1]
+        .resources
+        .as_mut()
+        .expect("resources")
+        .memory
+        .required = "plenty".into();
+    assert!(bad.total_requests().is_err());
+}
+
+#[test]
+fn test_port_int_or_string() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "my_container",
+                    "image": "nginx:latest",
+                    "ports": [
+                        {
+                            "name": "http",
+                            "containerPort": 8080
+                        },
+                        {
+                            "name": "admin",
+                            "containerPort": "adminPort"
+                        },
+                        {
+                            "name": "metrics",
+                            "containerPort": "9102"
+                        }
+                    ]
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    let ports = &component.containers[0].ports;
+    assert_eq!(Some(IntOrString::Int(8080)), ports[0].container_port);
+    assert_eq!(
+        Some(IntOrString::String("adminPort".into())),
+        ports[1].container_port
+    );
+
+    let mut params = BTreeMap::new();
+    params.insert("adminPort".to_string(), serde_json::json!(9000));
+    assert_eq!(8080, ports[0].resolve_port(&params).expect("int port"));
+    assert_eq!(9000, ports[1].resolve_port(&params).expect("named port"));
+    assert_eq!(
+        9102,
+        ports[2].resolve_port(&params).expect("numeric string")
+    );
+    assert!(component.validate_ports(params.clone()).is_ok());
+
+    let pod = component.to_pod_spec(params).expect("pod spec");
+    let container_ports = pod.containers[0].ports.clone().expect("ports");
+    assert_eq!(9000, container_ports[1].container_port);
+
+    assert!(ports[1].resolve_port(&BTreeMap::new()).is_err());
+    assert!(component.validate_ports(BTreeMap::new()).is_err());
+
+    // Out-of-range ports are rejected rather than wrapped or rendered as 0.
+    params.insert(
+        "adminPort".to_string(),
+        serde_json::json!(4_294_976_296_i64),
+    );
+    assert!(ports[1].resolve_port(&params).is_err());
+    params.insert("adminPort".to_string(), serde_json::json!(65536));
+    assert!(ports[1].resolve_port(&params).is_err());
+    assert!(Port::basic("zero".into(), 0)
+        .resolve_port(&BTreeMap::new())
+        .is_err());
+}
+
+#[test]
+fn test_apply_defaults() {
+    let mut minimal = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "my_container",
+                    "image": "nginx:latest",
+                    "ports": [
+                        {
+                            "name": "http",
+                            "containerPort": 8080
+                        }
+                    ],
+                    "livenessProbe": {
+                        "tcpSocket": {
+                            "port": 8080
+                        }
+                    },
+                    "resources": {
+                        "volumes": [
+                            {
+                                "name": "data",
+                                "mountPath": "/data"
+                            }
+                        ]
+                    }
+                }
+            ]
+        }"#,
+    )
+    .expect("minimal component must parse");
+    let mut full = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Singleton",
+            "parameters": [],
+            "workloadSettings": [],
+            "containers": [
+                {
+                    "name": "my_container",
+                    "image": "nginx:latest",
+                    "env": [],
+                    "config": [],
+                    "ports": [
+                        {
+                            "name": "http",
+                            "containerPort": 8080,
+                            "protocol": "TCP"
+                        }
+                    ],
+                    "livenessProbe": {
+                        "tcpSocket": {
+                            "port": 8080
+                        },
+                        "initialDelaySeconds": 0,
+                        "periodSeconds": 10,
+                        "timeoutSeconds": 1,
+                        "successThreshold": 1,
+                        "failureThreshold": 3
+                    },
+                    "resources": {
+                        "cpu": {
+                            "required": 0.1
+                        },
+                        "memory": {
+                            "required": "128"
+                        },
+                        "volumes": [
+                            {
+                                "name": "data",
+                                "mountPath": "/data",
+                                "accessMode": "RW",
+                                "sharingPolicy": "Exclusive",
+                                "disk": {
+                                    "required": "1G",
+                                    "ephemeral": false
+                                }
+                            }
+                        ],
+                        "extended": []
+                    }
+                }
+            ]
+        }"#,
+    )
+    .expect("full component must parse");
+
+    assert_ne!(minimal, full);
+    minimal.apply_defaults();
+    full.apply_defaults();
+    assert_eq!(full, minimal);
+}
+
+#[test]
+fn test_health_probe_partial_defaults() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "my_container",
+                    "image": "nginx:latest",
+                    "livenessProbe": {
+                        "httpGet": {
+                            "path": "/healthz",
+                            "port": 9000,
+                            "httpHeaders": []
+                        }
+                    },
+                    "readinessProbe": {
+                        "tcpSocket": {
+                            "port": 9000
+                        },
+                        "periodSeconds": 30
+                    }
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+
+    let liveness = component.containers[0]
+        .liveness_probe
+        .clone()
+        .expect("liveness probe");
+    assert_eq!(0, liveness.initial_delay_seconds);
+    assert_eq!(10, liveness.period_seconds);
+    assert_eq!(1, liveness.timeout_seconds);
+    assert_eq!(1, liveness.success_threshold);
+    assert_eq!(3, liveness.failure_threshold);
+
+    let readiness = component.containers[0]
+        .readiness_probe
+        .clone()
+        .expect("readiness probe");
+    assert_eq!(30, readiness.period_seconds);
+    assert_eq!(1, readiness.timeout_seconds);
+}
+
+#[test]
+fn test_sanitize_and_validate_name() {
+    assert!(Component::validate_name("web-frontend-2").is_ok());
+
+    let sanitized = Component::sanitize_name("My_Container");
+    assert_eq!("my-container", sanitized);
+    assert!(Component::validate_name("My_Container").is_err());
+    assert!(Component::validate_name(sanitized.as_str()).is_ok());
+
+    assert_eq!("frontend", Component::sanitize_name("-Frontend-"));
+
+    let long = "a".repeat(64);
+    assert!(Component::validate_name(long.as_str()).is_err());
+    assert_eq!(63, Component::sanitize_name(long.as_str()).len());
+
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "my_container",
+                    "image": "nginx:latest"
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    assert!(component.validate().is_err());
+}
+
+#[test]
+fn test_inline_content_config_map() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "resources": {
+                        "volumes": [
+                            {
+                                "name": "nginx-conf",
+                                "mountPath": "/etc/nginx/nginx.conf",
+                                "inlineContent": {
+                                    "value": "worker_processes 1;"
+                                }
+                            },
+                            {
+                                "name": "favicon",
+                                "mountPath": "/usr/share/nginx/html/favicon.ico",
+                                "inlineContent": {
+                                    "value": "AAEC/w==",
+                                    "encoding": "base64"
+                                }
+                            }
+                        ]
+                    }
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    assert!(component.validate().is_ok());
+
+    let config_maps = component
+        .to_inline_config_maps()
+        .expect("inline config maps");
+    assert_eq!(2, config_maps.len());
+    let conf = config_maps.get(0).expect("nginx config map");
+    assert_eq!(
+        Some("web-nginx-conf".to_string()),
+        conf.metadata.clone().expect("metadata").name
+    );
+    assert_eq!(
+        Some(&"worker_processes 1;".to_string()),
+        conf.data.as_ref().expect("data").get("nginx.conf")
+    );
+    let favicon = config_maps.get(1).expect("favicon config map");
+    assert_eq!(
+        vec![0u8, 1, 2, 255],
+        favicon
+            .binary_data
+            .as_ref()
+            .expect("binary data")
+            .get("favicon.ico")
+            .expect("favicon.ico")
+            .0
+    );
+
+    let pod_spec = component.to_pod_spec(BTreeMap::new()).expect("pod spec");
+    let volumes = pod_spec.volumes.expect("volumes");
+    // Volumes are sorted by name.
+    assert_eq!(
+        Some("web-nginx-conf".to_string()),
+        volumes
+            .get(1)
+            .and_then(|v| v.config_map.clone())
+            .expect("config map volume")
+            .name
+    );
+    assert!(volumes
+        .get(1)
+        .expect("volume")
+        .persistent_volume_claim
+        .is_none());
+
+    let mounts = component.containers[0]
+        .volume_mounts()
+        .expect("volume mounts");
+    let mount = mounts.get(0).expect("nginx mount");
+    assert_eq!("/etc/nginx/nginx.conf", mount.mount_path);
+    assert_eq!(Some("nginx.conf".to_string()), mount.sub_path);
+}
+
+#[test]
+fn test_inline_content_gzip() {
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(b"key = value").expect("compress");
+    let content = InlineContent {
+        value: base64::encode(&encoder.finish().expect("gzip")),
+        encoding: ContentEncoding::Gzip,
+    };
+    assert_eq!(b"key = value".to_vec(), content.decode().expect("decode"));
+}
+
+#[test]
+fn test_inline_content_too_large() {
+    let content = InlineContent {
+        value: "a".repeat(1_048_577),
+        encoding: ContentEncoding::Plain,
+    };
+    assert!(content.decode().is_err());
+
+    let component = Component {
+        containers: vec![Container {
+            name: "web".into(),
+            image: "nginx:latest".into(),
+            resources: Some(Resources {
+                volumes: Some(vec![Volume {
+                    name: "big".into(),
+                    mount_path: "/etc/big.txt".into(),
+                    access_mode: AccessMode::RO,
+                    sharing_policy: SharingPolicy::Exclusive,
+                    disk: None,
+                    inline_content: Some(content),
+                    sub_path: None,
+                    mount_propagation: None,
+                }]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    assert!(component.validate().is_err());
+    assert!(component.to_inline_config_maps().is_err());
+}
+
+#[test]
+fn test_apply_json_patch() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:1.16",
+                    "ports": [
+                        {
+                            "name": "http",
+                            "containerPort": 80
+                        }
+                    ]
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+
+    let patched = component
+        .apply_json_patch(&serde_json::json!([
+            {"op": "replace", "path": "/containers/0/image", "value": "nginx:1.17"},
+            {"op": "add", "path": "/containers/0/env/-", "value": {"name": "MODE", "value": "staging"}}
+        ]))
+        .expect("patch must apply");
+    assert_eq!("nginx:1.17", patched.containers[0].image);
+    assert_eq!("MODE", patched.containers[0].env[0].name);
+    // The original is left untouched.
+    assert_eq!("nginx:1.16", component.containers[0].image);
+
+    let err = component
+        .apply_json_patch(&serde_json::json!([
+            {"op": "replace", "path": "/containers/3/image", "value": "nginx:1.17"}
+        ]))
+        .expect_err("an invalid path must fail");
+    assert!(err.to_string().contains("failed to apply JSON patch"));
+
+    let err = component
+        .apply_json_patch(&serde_json::json!([
+            {"op": "replace", "path": "/containers/0/ports", "value": "http"}
+        ]))
+        .expect_err("a patch that breaks the schema must fail");
+    assert!(err.to_string().contains("patched component is invalid"));
+
+    assert!(component
+        .apply_json_patch(&serde_json::json!([
+            {"op": "replace", "path": "/containers/0/name", "value": "Web_Frontend"}
+        ]))
+        .is_err());
+}
+
+#[test]
+fn test_strip_defaults() {
+    let component = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Server",
+            "osType": "linux",
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "resources": {
+                        "cpu": {
+                            "required": 0.1
+                        },
+                        "memory": {
+                            "required": "128"
+                        },
+                        "volumes": [
+                            {
+                                "name": "cache",
+                                "mountPath": "/cache",
+                                "accessMode": "RW",
+                                "sharingPolicy": "Exclusive",
+                                "disk": {
+                                    "required": "1G",
+                                    "ephemeral": false
+                                }
+                            }
+                        ]
+                    },
+                    "env": [],
+                    "ports": [
+                        {
+                            "name": "http",
+                            "containerPort": 80,
+                            "protocol": "TCP"
+                        }
+                    ],
+                    "livenessProbe": {
+                        "tcpSocket": {
+                            "port": 80
+                        },
+                        "initialDelaySeconds": 0,
+                        "periodSeconds": 10,
+                        "timeoutSeconds": 1,
+                        "successThreshold": 1,
+                        "failureThreshold": 5
+                    }
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+
+    let minimal = serde_json::json!({
+        "workloadType": "core.oam.dev/v1alpha1.Server",
+        "osType": "linux",
+        "containers": [
+            {
+                "name": "web",
+                "image": "nginx:latest",
+                "resources": {
+                    "volumes": [
+                        {
+                            "name": "cache",
+                            "mountPath": "/cache"
+                        }
+                    ]
+                },
+                "ports": [
+                    {
+                        "name": "http",
+                        "containerPort": 80
+                    }
+                ],
+                "livenessProbe": {
+                    "tcpSocket": {
+                        "port": 80
+                    },
+                    "failureThreshold": 5
+                }
+            }
+        ]
+    });
+    let stripped = component.strip_defaults();
+    assert_eq!(minimal, stripped);
+    assert_eq!(
+        serde_json::json!({ "containers": [{ "name": "web", "image": "nginx:latest" }] }),
+        Component {
+            containers: vec![Container {
+                name: "web".into(),
+                image: "nginx:latest".into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+        .strip_defaults()
+    );
+
+    let mut restored: Component =
+        serde_json::from_value(stripped).expect("stripped component must parse");
+    restored.apply_defaults();
+    let mut expected = component.clone();
+    expected.apply_defaults();
+    assert_eq!(expected, restored);
+}
+
+#[test]
+fn test_render_all_yaml() {
+    let component = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Server",
+            "parameters": [
+                {
+                    "name": "greeting",
+                    "type": "string",
+                    "default": "hello"
+                }
+            ],
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "ports": [
+                        {
+                            "name": "http",
+                            "containerPort": 80
+                        }
+                    ],
+                    "config": [
+                        {
+                            "path": "/etc/web/greeting.txt",
+                            "fromParam": "greeting"
+                        }
+                    ]
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+
+    let yaml = component.render_all_yaml("web").expect("render yaml");
+    assert_eq!(yaml, component.render_all_yaml("web").expect("render yaml"));
+
+    let docs: Vec<serde_json::Value> = yaml
+        .split("---\n")
+        .filter(|doc| !doc.trim().is_empty())
+        .map(|doc| serde_yaml::from_str(doc).expect("document must parse"))
+        .collect();
+    let kinds: Vec<&str> = docs
+        .iter()
+        .map(|doc| doc["kind"].as_str().expect("kind"))
+        .collect();
+    assert_eq!(vec!["Deployment", "Service", "ConfigMap"], kinds);
+    assert_eq!("hello", docs[2]["data"]["greeting.txt"]);
+
+    let mut unknown = component.clone();
+    unknown.workload_type = "example.com/v1.Function".into();
+    assert!(unknown.render_all_yaml("web").is_err());
+}
+
+#[test]
+fn test_env_var_order_is_stable() {
+    let component = Component::from_str(
+        r#"{
+            "parameters": [
+                {
+                    "name": "zeta",
+                    "type": "string",
+                    "default": "z"
+                },
+                {
+                    "name": "alpha",
+                    "type": "string",
+                    "default": "a"
+                }
+            ],
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "env": [
+                        {
+                            "name": "ZETA",
+                            "fromParam": "zeta"
+                        },
+                        {
+                            "name": "MIDDLE",
+                            "value": "m"
+                        },
+                        {
+                            "name": "ALPHA",
+                            "fromParam": "alpha"
+                        }
+                    ]
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    let params = resolve_parameters(component.parameters.clone(), BTreeMap::new())
+        .expect("parameters must resolve");
+
+    let render = || {
+        serde_json::to_string(
+            &component
+                .to_pod_spec(params.clone())
+                .expect("pod spec")
+                .containers[0]
+                .env,
+        )
+        .expect("env must serialize")
+    };
+    assert_eq!(render(), render());
+
+    let names: Vec<String> = component.containers[0]
+        .to_env_vars(params.clone())
+        .expect("env vars")
+        .into_iter()
+        .map(|e| e.name)
+        .collect();
+    assert_eq!(vec!["ZETA", "MIDDLE", "ALPHA"], names);
+}
+
+#[test]
+fn test_validate_session_affinity() {
+    let mut component = Component::default();
+    component.workload_settings = vec![workload_setting(
+        "sessionAffinity",
+        serde_json::json!("ClientIP"),
+    )];
+    assert!(component.validate_service_settings(BTreeMap::new()).is_ok());
+
+    component.workload_settings = vec![workload_setting(
+        "sessionAffinity",
+        serde_json::json!("Cookie"),
+    )];
+    assert!(component
+        .validate_service_settings(BTreeMap::new())
+        .is_err());
+
+    // A timeout needs ClientIP affinity and must fit within a day.
+    component.workload_settings = vec![
+        workload_setting("sessionAffinity", serde_json::json!("None")),
+        workload_setting("sessionAffinityTimeoutSeconds", serde_json::json!(3600)),
+    ];
+    assert!(component
+        .validate_service_settings(BTreeMap::new())
+        .is_err());
+    component.workload_settings = vec![
+        workload_setting("sessionAffinity", serde_json::json!("ClientIP")),
+        workload_setting("sessionAffinityTimeoutSeconds", serde_json::json!(86_401)),
+    ];
+    assert!(component
+        .validate_service_settings(BTreeMap::new())
+        .is_err());
+}
+
+#[test]
+fn test_validate_service_type() {
+    let mut component = Component::default();
+    component.workload_settings = vec![
+        workload_setting("serviceType", serde_json::json!("NodePort")),
+        workload_setting("externalTrafficPolicy", serde_json::json!("Cluster")),
+    ];
+    assert!(component.validate_service_settings(BTreeMap::new()).is_ok());
+
+    component.workload_settings = vec![workload_setting(
+        "serviceType",
+        serde_json::json!("ExternalName"),
+    )];
+    assert!(component
+        .validate_service_settings(BTreeMap::new())
+        .is_err());
+
+    component.workload_settings = vec![
+        workload_setting("serviceType", serde_json::json!("LoadBalancer")),
+        workload_setting("externalTrafficPolicy", serde_json::json!("Nearest")),
+    ];
+    assert!(component
+        .validate_service_settings(BTreeMap::new())
+        .is_err());
+
+    // The traffic policy only applies to externally reachable Services.
+    component.workload_settings = vec![workload_setting(
+        "externalTrafficPolicy",
+        serde_json::json!("Local"),
+    )];
+    assert!(component
+        .validate_service_settings(BTreeMap::new())
+        .is_err());
+
+    component.workload_settings = vec![
+        workload_setting("serviceType", serde_json::json!("LoadBalancer")),
+        workload_setting("headless", serde_json::json!(true)),
+    ];
+    assert!(component
+        .validate_service_settings(BTreeMap::new())
+        .is_err());
+}
+
+#[test]
+fn test_component_from_file() {
+    let write = |extension: &str, data: &str| {
+        let path = std::env::temp_dir().join(format!(
+            "rudr-component-{}.{}",
+            std::process::id(),
+            extension
+        ));
+        std::fs::write(&path, data).expect("write temp file");
+        path
+    };
+
+    let json = write(
+        "json",
+        r#"{"containers": [{"name": "web", "image": "nginx:latest"}]}"#,
+    );
+    let yaml = write(
+        "yaml",
+        r#"
+apiVersion: core.oam.dev/v1alpha1
+kind: ComponentSchematic
+metadata:
+  name: web
+spec:
+  workloadType: core.oam.dev/v1alpha1.Server
+  containers:
+    - name: web
+      image: nginx:latest
+"#,
+    );
+    let yml = write(
+        "yml",
+        r#"
+containers:
+  - name: web
+    image: nginx:latest
+"#,
+    );
+    let txt = write("txt", r#"{"containers": []}"#);
+
+    let from_json = Component::from_file(&json).expect("json component");
+    assert_eq!("nginx:latest", from_json.containers[0].image);
+    let from_yaml = Component::from_file(&yaml).expect("yaml component");
+    assert_eq!("core.oam.dev/v1alpha1.Server", from_yaml.workload_type);
+    assert_eq!("web", from_yaml.containers[0].name);
+    let from_yml = Component::from_file(&yml).expect("yml component");
+    assert_eq!(from_json, from_yml);
+    assert!(Component::from_file(&txt).is_err());
+
+    for path in vec![json, yaml, yml, txt] {
+        std::fs::remove_file(path).expect("remove temp file");
+    }
+}
+
+#[test]
+fn test_resolve_workload_setting() {
+    let replicas = WorkloadSetting {
+        name: "replicaCount".into(),
+        description: None,
+        parameter_type: ParameterType::Number,
+        required: true,
+        value: None,
+        from_param: Some("replicas".into()),
+    };
+    let mut params = BTreeMap::new();
+    params.insert("replicas".to_string(), serde_json::json!(3));
+    assert_eq!(
+        Some(serde_json::json!(3)),
+        replicas.resolve(&params).expect("replica count")
+    );
+
+    // Without the parameter, a required setting falls back to its value or fails.
+    let err = replicas
+        .resolve(&BTreeMap::new())
+        .expect_err("a missing parameter must fail");
+    assert!(err.to_string().contains("replicas"));
+    let with_default = WorkloadSetting {
+        value: Some(serde_json::json!(1)),
+        ..replicas.clone()
+    };
+    assert_eq!(
+        Some(serde_json::json!(1)),
+        with_default.resolve(&BTreeMap::new()).expect("default")
+    );
+
+    params.insert("replicas".to_string(), serde_json::json!("three"));
+    assert!(replicas.resolve(&params).is_err());
+
+    let component = Component {
+        workload_settings: vec![replicas],
+        ..Default::default()
+    };
+    assert!(component
+        .validate_workload_settings(BTreeMap::new())
+        .is_err());
+}
+
+#[test]
+fn test_spec_hash() {
+    let component = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Server",
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "env": [
+                        {
+                            "name": "GREETING",
+                            "value": "hello"
+                        }
+                    ]
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+
+    let reserialized = Component::from_str(
+        serde_json::to_string(&component)
+            .expect("component must serialize")
+            .as_str(),
+    )
+    .expect("component must parse");
+    assert_eq!(component.spec_hash(), reserialized.spec_hash());
+
+    // A component that uses none of the fields added since must keep the hash it had, or
+    // upgrading would roll out every pod.
+    assert_eq!("6c058eac4f36d005", component.spec_hash());
+
+    let mut changed = component.clone();
+    changed.containers[0].env[0].value = Some("goodbye".into());
+    assert_ne!(component.spec_hash(), changed.spec_hash());
+
+    let mut changed = component.clone();
+    changed.containers[0].image_pull_policy = Some("Always".into());
+    assert_ne!(component.spec_hash(), changed.spec_hash());
+}
+
+#[test]
+fn test_pod_template_spec() {
+    let component = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Server",
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest"
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+
+    let template = component
+        .to_pod_template_spec("frontend")
+        .expect("pod template");
+    let metadata = template.metadata.expect("template must have metadata");
+    let labels = metadata.labels.expect("template must have labels");
+    assert_eq!(
+        Some(&"frontend".to_string()),
+        labels.get("app.kubernetes.io/name")
+    );
+    assert_eq!(
+        Some(&"frontend".to_string()),
+        labels.get("oam.dev/instance-name")
+    );
+    assert_eq!(
+        Some(&component.spec_hash()),
+        metadata
+            .annotations
+            .expect("template must have annotations")
+            .get(SPEC_HASH_ANNOTATION)
+    );
+    let spec = template.spec.expect("template must have a pod spec");
+    assert_eq!("nginx:latest", spec.containers[0].image.clone().unwrap());
+}
+
+#[test]
+fn test_volume_shared_between_containers() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "writer",
+                    "image": "writer:latest",
+                    "resources": {
+                        "volumes": [
+                            {
+                                "name": "data",
+                                "mountPath": "/var/data",
+                                "sharingPolicy": "Shared"
+                            }
+                        ]
+                    }
+                },
+                {
+                    "name": "reader",
+                    "image": "reader:latest",
+                    "resources": {
+                        "volumes": [
+                            {
+                                "name": "data",
+                                "mountPath": "/srv/data",
+                                "accessMode": "RO",
+                                "sharingPolicy": "Shared"
+                            }
+                        ]
+                    }
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    assert!(component.validate().is_ok());
+
+    let pod = component.to_pod_spec(BTreeMap::new()).expect("pod spec");
+    let volumes = pod.volumes.expect("volumes");
+    assert_eq!(1, volumes.len());
+    assert_eq!("data", volumes[0].name);
+    let mount_paths: Vec<String> = pod
+        .containers
+        .iter()
+        .flat_map(|c| c.volume_mounts.clone().unwrap_or_default())
+        .filter(|m| m.name == "data")
+        .map(|m| m.mount_path)
+        .collect();
+    assert_eq!(vec!["/var/data", "/srv/data"], mount_paths);
+    assert_eq!(1, component.to_volume_claim_templates().len());
+
+    let mut exclusive = component.clone();
+    if let Some(volumes) = exclusive.containers[1]
+        .resources
+        .as_mut()
+        .and_then(|r| r.volumes.as_mut())
+    {
+        volumes[0].sharing_policy = SharingPolicy::Exclusive;
+    }
+    let err = exclusive
+        .validate()
+        .expect_err("exclusive volume is shared");
+    assert!(err.to_string().contains("reader"));
+}
+
+#[test]
+fn test_container_images() {
+    let component = Component::from_str(
+        r#"{
+            "initContainers": [
+                {
+                    "name": "migrate",
+                    "image": "example.com/migrate:1.0"
+                }
+            ],
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest"
+                },
+                {
+                    "name": "proxy",
+                    "image": "envoyproxy/envoy:v1.12.0"
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    assert_eq!(
+        vec![
+            "nginx:latest".to_string(),
+            "envoyproxy/envoy:v1.12.0".to_string(),
+            "example.com/migrate:1.0".to_string(),
+        ],
+        component.container_images()
+    );
+
+    let pod = component.to_pod_spec(BTreeMap::new()).expect("pod spec");
+    let init = pod.init_containers.expect("init containers");
+    assert_eq!(1, init.len());
+    assert_eq!("migrate", init[0].name);
+    assert_eq!(2, pod.containers.len());
+
+    let mut hooked = component.clone();
+    hooked.init_containers[0].lifecycle = Some(Lifecycle::default());
+    let err = hooked
+        .validate()
+        .expect_err("init containers cannot have lifecycle hooks");
+    assert!(err.to_string().contains("migrate"));
+}
+
+#[test]
+fn test_sidecars() {
+    let component = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Server",
+            "initContainers": [
+                {
+                    "name": "migrate",
+                    "image": "example.com/migrate:1.0"
+                }
+            ],
+            "sidecars": [
+                {
+                    "name": "log-shipper",
+                    "image": "fluent/fluent-bit:2.1",
+                    "readinessProbe": {
+                        "exec": {
+                            "command": ["true"]
+                        }
+                    }
+                }
+            ],
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest"
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    // Unlike init containers, sidecars may be probed.
+    assert!(component.validate().is_ok());
+    assert!(component.validate_probe_params(BTreeMap::new()).is_ok());
+
+    let objects = render_all(&component, "web", BTreeMap::new()).expect("render");
+    let init = objects[0]
+        .pointer("/spec/template/spec/initContainers")
+        .and_then(|c| c.as_array())
+        .expect("init containers");
+    assert_eq!(2, init.len());
+    assert_eq!("log-shipper", init[0]["name"]);
+    assert_eq!("Always", init[0]["restartPolicy"]);
+    assert_eq!("migrate", init[1]["name"]);
+    assert!(init[1].get("restartPolicy").is_none());
+
+    let mut clash = component.clone();
+    clash.sidecars[0].name = "migrate".into();
+    let err = clash.validate().expect_err("sidecar name clash");
+    assert!(err.to_string().contains("container name migrate"));
+}
+
+#[test]
+fn test_validate_image_ref() {
+    let image_ref = |image: &str| {
+        Container {
+            name: "web".into(),
+            image: image.into(),
+            ..Default::default()
+        }
+        .validate_image_ref()
+    };
+
+    let nginx = image_ref("nginx").expect("bare repository");
+    assert_eq!(None, nginx.registry);
+    assert_eq!("nginx", nginx.repository);
+    assert_eq!(None, nginx.tag);
+
+    let qualified = image_ref("docker.io/library/nginx:1.25").expect("qualified reference");
+    assert_eq!(Some("docker.io".to_string()), qualified.registry);
+    assert_eq!("library/nginx", qualified.repository);
+    assert_eq!(Some("1.25".to_string()), qualified.tag);
+    assert_eq!(None, qualified.digest);
+
+    let digest = "sha256:8f9a2b1c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8";
+    let pinned = image_ref(format!("localhost:5000/envoy:v1.12.0@{}", digest).as_str())
+        .expect("digest-pinned reference");
+    assert_eq!(Some("localhost:5000".to_string()), pinned.registry);
+    assert_eq!("envoy", pinned.repository);
+    assert_eq!(Some("v1.12.0".to_string()), pinned.tag);
+    assert_eq!(Some(digest.to_string()), pinned.digest);
+
+    let err = image_ref("nginx::latest").expect_err("double colon");
+    assert!(err.to_string().contains("container web"));
+    assert!(image_ref("").is_err());
+    assert!(image_ref("nginx:").is_err());
+    assert!(image_ref("nginx@sha256:abc").is_err());
+
+    let component = Component {
+        containers: vec![Container {
+            name: "web".into(),
+            image: "nginx::latest".into(),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    assert!(component.validate().is_err());
+}
+
+#[test]
+fn test_volume_sub_path_and_mount_propagation() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "agent",
+                    "image": "example.com/agent:1.0",
+                    "resources": {
+                        "volumes": [
+                            {
+                                "name": "config",
+                                "mountPath": "/etc/agent",
+                                "subPath": "agent/conf.d"
+                            },
+                            {
+                                "name": "host-mounts",
+                                "mountPath": "/mnt/host",
+                                "mountPropagation": "Bidirectional"
+                            }
+                        ]
+                    }
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    assert!(component.validate().is_ok());
+
+    let mounts = component.containers[0]
+        .volume_mounts()
+        .expect("volume mounts");
+    assert_eq!(Some("agent/conf.d".to_string()), mounts[0].sub_path);
+    assert_eq!(None, mounts[0].mount_propagation);
+    assert_eq!(None, mounts[1].sub_path);
+    assert_eq!(
+        Some("Bidirectional".to_string()),
+        mounts[1].mount_propagation
+    );
+
+    let invalid = component
+        .with_container("agent", |c| {
+            c.resources
+                .as_mut()
+                .and_then(|r| r.volumes.as_mut())
+                .unwrap()[1]
+                .mount_propagation = Some("Shared".into())
+        })
+        .expect("agent container");
+    let err = invalid.validate().expect_err("unknown propagation mode");
+    assert!(err.to_string().contains("mount propagation Shared"));
+
+    let escaping = component
+        .with_container("agent", |c| {
+            c.resources
+                .as_mut()
+                .and_then(|r| r.volumes.as_mut())
+                .unwrap()[0]
+                .sub_path = Some("../etc".into())
+        })
+        .expect("agent container");
+    assert!(escaping.validate().is_err());
+}
+
+#[test]
+fn test_merge_env() {
+    let env = |name: &str, value: &str| Env {
+        name: name.into(),
+        value: Some(value.into()),
+        from_param: None,
+        value_from: None,
+    };
+    let mut component = Component {
+        containers: vec![
+            Container {
+                name: "web".into(),
+                image: "nginx:latest".into(),
+                ..Default::default()
+            },
+            Container {
+                name: "api".into(),
+                image: "example.com/api:1.0".into(),
+                env: vec![env("OTEL_EXPORTER", "zipkin")],
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+
+    component.merge_env(&[
+        env("OTEL_EXPORTER", "otlp"),
+        env("DEPLOY_ENV", "production"),
+    ]);
+    assert_eq!(
+        vec![
+            env("OTEL_EXPORTER", "otlp"),
+            env("DEPLOY_ENV", "production")
+        ],
+        component.containers[0].env
+    );
+    // The container's own value wins.
+    assert_eq!(
+        vec![
+            env("OTEL_EXPORTER", "zipkin"),
+            env("DEPLOY_ENV", "production")
+        ],
+        component.containers[1].env
+    );
+}
+
+#[test]
+fn test_with_container() {
+    let component = Component {
+        containers: vec![
+            Container {
+                name: "web".into(),
+                image: "nginx:1.16".into(),
+                ..Default::default()
+            },
+            Container {
+                name: "proxy".into(),
+                image: "envoyproxy/envoy:v1.12.0".into(),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+
+    let updated = component
+        .with_container("web", |c| c.image = "nginx:1.17".into())
+        .expect("web container");
+    assert_eq!("nginx:1.17", updated.containers[0].image);
+    assert_eq!(component.containers[1], updated.containers[1]);
+    // The original is unchanged.
+    assert_eq!("nginx:1.16", component.containers[0].image);
+
+    let err = component
+        .with_container("missing", |c| c.image = "nginx:1.17".into())
+        .expect_err("missing container");
+    assert!(err.to_string().contains("no container named missing"));
+}
+
+#[test]
+fn test_require_digest_pinned() {
+    let digest = "sha256:8f9a2b1c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8";
+    let pinned = Component {
+        containers: vec![
+            Container {
+                name: "web".into(),
+                image: format!("nginx@{}", digest),
+                ..Default::default()
+            },
+            Container {
+                name: "proxy".into(),
+                image: format!("envoyproxy/envoy:v1.12.0@{}", digest),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+    assert!(pinned.require_digest_pinned().is_ok());
+
+    let mut tagged = pinned.clone();
+    tagged.containers[1].image = "envoyproxy/envoy:v1.12.0".into();
+    tagged.init_containers = vec![Container {
+        name: "migrate".into(),
+        image: "example.com/migrate:latest".into(),
+        ..Default::default()
+    }];
+    let err = tagged
+        .require_digest_pinned()
+        .expect_err("tagged images are not pinned");
+    assert!(err.to_string().contains("envoyproxy/envoy:v1.12.0"));
+    assert!(err.to_string().contains("example.com/migrate:latest"));
+    assert!(!err.to_string().contains("nginx"));
+}
+
+#[test]
+fn test_env_name_validation() {
+    let env = |name: &str| Env {
+        name: name.into(),
+        value: Some("value".into()),
+        from_param: None,
+        value_from: None,
+    };
+    assert!(env("LOG_LEVEL").validate().is_ok());
+    assert!(env("_private2").validate().is_ok());
+    assert!(env("").validate().is_err());
+    assert!(env("2FAST").validate().is_err());
+    assert!(env("LOG-LEVEL").validate().is_err());
+
+    let component = Component {
+        containers: vec![Container {
+            name: "web".into(),
+            image: "nginx:latest".into(),
+            env: vec![env("LOG-LEVEL")],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let err = component.validate().expect_err("invalid env name");
+    assert!(err.to_string().contains("LOG-LEVEL"));
+}
+
+#[test]
+fn test_scale() {
+    let mut component = Component {
+        workload_type: "core.oam.dev/v1alpha1.Server".into(),
+        containers: vec![Container {
+            name: "web".into(),
+            image: "nginx:latest".into(),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    component.scale(2).expect("scale a server");
+    component.scale(3).expect("scale a server again");
+    assert_eq!(1, component.workload_settings.len());
+    assert_eq!(Some(3), component.replica_count(BTreeMap::new()).unwrap());
+    assert!(component
+        .validate_workload_settings(BTreeMap::new())
+        .is_ok());
+
+    let objects = render_all(&component, "web", BTreeMap::new()).expect("render");
+    assert_eq!("Deployment", objects[0]["kind"]);
+    assert_eq!(3, objects[0]["spec"]["replicas"]);
+
+    assert!(component.scale(-1).is_err());
+
+    let mut stateful = Component {
+        workload_type: "core.oam.dev/v1alpha1.StatefulService".into(),
+        ..component.clone()
+    };
+    stateful.scale(4).expect("scale a stateful service");
+    let objects = render_all(&stateful, "db", BTreeMap::new()).expect("render");
+    assert_eq!("StatefulSet", objects[0]["kind"]);
+    assert_eq!(4, objects[0]["spec"]["replicas"]);
+
+    let mut singleton = Component::default();
+    let err = singleton
+        .scale(2)
+        .expect_err("a singleton cannot be scaled");
+    assert!(err.to_string().contains("Singleton"));
+    assert!(singleton.workload_settings.is_empty());
+}
+
+#[test]
+fn test_render_target_port_by_name() {
+    let component = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Server",
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "ports": [{"name": "http", "containerPort": 8080}]
+                }
+            ],
+            "workloadSettings": [
+                {
+                    "name": "targetPortByName",
+                    "type": "boolean",
+                    "value": true
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    let objects = render_all(&component, "web", BTreeMap::new()).expect("render");
+    let service = objects
+        .iter()
+        .find(|o| o["kind"] == "Service")
+        .expect("service");
+    assert_eq!(8080, service["spec"]["ports"][0]["port"]);
+    assert_eq!("http", service["spec"]["ports"][0]["targetPort"]);
+
+    let mut by_number = component.clone();
+    by_number.workload_settings.clear();
+    let objects = render_all(&by_number, "web", BTreeMap::new()).expect("render");
+    let service = objects
+        .iter()
+        .find(|o| o["kind"] == "Service")
+        .expect("service");
+    assert_eq!(8080, service["spec"]["ports"][0]["targetPort"]);
+}
+
+#[test]
+fn test_with_graceful_drain() {
+    let hook = LifecycleHandler {
+        http_get: Some(HttpGet {
+            path: "/drain".into(),
+            port: 8080,
+            http_headers: vec![],
+        }),
+        ..Default::default()
+    };
+    let component = Component {
+        containers: vec![
+            Container {
+                name: "web".into(),
+                image: "nginx:latest".into(),
+                ..Default::default()
+            },
+            Container {
+                name: "api".into(),
+                image: "example.com/api:1.0".into(),
+                lifecycle: Some(Lifecycle {
+                    pre_stop: Some(hook.clone()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    }
+    .with_graceful_drain(40);
+
+    let pre_stop = |c: &Container| c.lifecycle.clone().and_then(|l| l.pre_stop);
+    assert_eq!(
+        Some(vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "sleep 40".to_string()
+        ]),
+        pre_stop(&component.containers[0]).and_then(|h| h.exec.map(|e| e.command))
+    );
+    // An existing preStop hook is kept.
+    assert_eq!(Some(hook), pre_stop(&component.containers[1]));
+    assert_eq!(Some(45), component.termination_grace_period_seconds);
+
+    let pod = component.to_pod_spec(BTreeMap::new()).expect("pod spec");
+    assert_eq!(Some(45), pod.termination_grace_period_seconds);
+    assert!(pod.containers[0]
+        .lifecycle
+        .as_ref()
+        .and_then(|l| l.pre_stop.as_ref())
+        .is_some());
+
+    // A short drain keeps the longer default grace period.
+    let short = Component::default().with_graceful_drain(5);
+    assert_eq!(Some(30), short.termination_grace_period_seconds);
+}
+
+#[test]
+fn test_runtime_class_name() {
+    let component = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Server",
+            "runtimeClassName": "gvisor",
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest"
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    assert_eq!(Some("gvisor".to_string()), component.runtime_class_name);
+
+    let pod = component.to_pod_spec(BTreeMap::new()).expect("pod spec");
+    assert_eq!(Some("gvisor".to_string()), pod.runtime_class_name);
+    let rendered = serde_json::to_value(&pod).expect("pod spec must serialize");
+    assert_eq!("gvisor", rendered["runtimeClassName"]);
+
+    // The field is omitted when unset.
+    let pod = Component::default()
+        .to_pod_spec(BTreeMap::new())
+        .expect("pod spec");
+    let rendered = serde_json::to_value(&pod).expect("pod spec must serialize");
+    assert!(rendered.get("runtimeClassName").is_none());
+}
+
+#[test]
+fn test_priority_class_name() {
+    let component = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Server",
+            "priorityClassName": "high-priority",
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest"
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+
+    let pod = component.to_pod_spec(BTreeMap::new()).expect("pod spec");
+    assert_eq!(Some("high-priority".to_string()), pod.priority_class_name);
+    let rendered = serde_json::to_value(&pod).expect("pod spec must serialize");
+    assert_eq!("high-priority", rendered["priorityClassName"]);
+
+    let pod = Component::default()
+        .to_pod_spec(BTreeMap::new())
+        .expect("pod spec");
+    assert_eq!(None, pod.priority_class_name);
+}
+
+#[test]
+fn test_apply_trait() {
+    let component = Component {
+        workload_type: "core.oam.dev/v1alpha1.Server".into(),
+        containers: vec![Container {
+            name: "web".into(),
+            image: "nginx:latest".into(),
+            ports: vec![Port::basic("http".into(), 80)],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let mut objects = render_all(&component, "web", BTreeMap::new()).expect("render");
+    let rendered = objects.len();
+
+    component
+        .apply_trait(
+            &mut objects,
+            &TraitBinding {
+                name: "manual-scaler".into(),
+                parameter_values: None,
+                properties: Some(serde_json::json!({ "replicaCount": 3 })),
+            },
+        )
+        .expect("apply manual scaler");
+    // The manual scaler changes the workload rather than creating objects.
+    assert_eq!(rendered, objects.len());
+    assert_eq!(3, objects[0]["spec"]["replicas"]);
+
+    let err = component
+        .apply_trait(
+            &mut objects,
+            &TraitBinding {
+                name: "rate-limiter".into(),
+                parameter_values: None,
+                properties: None,
+            },
+        )
+        .expect_err("unknown trait");
+    assert!(err.to_string().contains("unknown trait rate-limiter"));
+    assert!(err.to_string().contains("manual-scaler"));
+    assert!(err.to_string().contains("volume-mounter"));
+}
+
+#[test]
+fn test_host_namespaces() {
+    let component = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Worker",
+            "hostNetwork": true,
+            "hostPID": true,
+            "containers": [
+                {
+                    "name": "node-exporter",
+                    "image": "prom/node-exporter:v1.6.0",
+                    "ports": [
+                        {
+                            "name": "metrics",
+                            "containerPort": 9100
+                        }
+                    ]
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+
+    let pod = component.to_pod_spec(BTreeMap::new()).expect("pod spec");
+    assert_eq!(Some(true), pod.host_network);
+    assert_eq!(Some(true), pod.host_pid);
+    assert_eq!(None, pod.host_ipc);
+    let port = &pod.containers[0].ports.as_ref().expect("ports")[0];
+    assert_eq!(9100, port.container_port);
+    assert_eq!(Some(9100), port.host_port);
+
+    // Host namespaces are off by default, and ports are not bound on the host.
+    let mut isolated = component.clone();
+    isolated.host_network = false;
+    isolated.host_pid = false;
+    let pod = isolated.to_pod_spec(BTreeMap::new()).expect("pod spec");
+    assert_eq!(None, pod.host_network);
+    assert_eq!(None, pod.host_pid);
+    assert_eq!(
+        None,
+        pod.containers[0].ports.as_ref().expect("ports")[0].host_port
+    );
+}
+
+#[test]
+fn test_application_render() {
+    let web = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Server",
+            "parameters": [
+                {
+                    "name": "greeting",
+                    "type": "string",
+                    "default": "hello"
+                }
+            ],
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "env": [
+                        {
+                            "name": "GREETING",
+                            "fromParam": "greeting"
+                        }
+                    ],
+                    "ports": [
+                        {
+                            "name": "http",
+                            "containerPort": 80
+                        }
+                    ]
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    let mut components = std::collections::HashMap::new();
+    components.insert("web".to_string(), web);
+
+    let instance = |name: &str, greeting: &str| ComponentConfiguration {
+        component_name: "web".into(),
+        instance_name: name.into(),
+        parameter_values: Some(vec![ParameterValue {
+            name: "greeting".into(),
+            value: Some(serde_json::json!(greeting)),
+            from_param: None,
+        }]),
+        traits: None,
+        application_scopes: None,
+        depends_on: vec![],
+    };
+    let mut scaled = instance("web-scaled", "hi");
+    scaled.traits = Some(vec![TraitBinding {
+        name: "manual-scaler".into(),
+        parameter_values: None,
+        properties: Some(serde_json::json!({ "replicaCount": 3 })),
+    }]);
+    let app = Application {
+        components: vec![instance("web-hello", "hello"), scaled],
+    };
+
+    let objects = app.render(&components).expect("render application");
+    let deployments: Vec<&serde_json::Value> = objects
+        .iter()
+        .filter(|o| o["kind"] == "Deployment")
+        .collect();
+    assert_eq!(2, deployments.len());
+    assert_eq!("web-hello", deployments[0]["metadata"]["name"]);
+    assert_eq!(
+        "hello",
+        deployments[0]["spec"]["template"]["spec"]["containers"][0]["env"][0]["value"]
+    );
+    assert_eq!("web-scaled", deployments[1]["metadata"]["name"]);
+    assert_eq!(
+        "hi",
+        deployments[1]["spec"]["template"]["spec"]["containers"][0]["env"][0]["value"]
+    );
+    assert_eq!(3, deployments[1]["spec"]["replicas"]);
+    assert_eq!(2, objects.iter().filter(|o| o["kind"] == "Service").count());
+
+    // Rendering runs the same checks as deploying.
+    let mut clashing = components["web"].clone();
+    clashing.containers[0]
+        .ports
+        .push(Port::basic("alt".into(), 80));
+    let mut clashing_components = components.clone();
+    clashing_components.insert("web".to_string(), clashing);
+    let err = app
+        .render(&clashing_components)
+        .expect_err("port declared twice");
+    assert!(err.to_string().contains("more than once"));
+
+    let unresolved = Application {
+        components: vec![ComponentConfiguration {
+            component_name: "missing".into(),
+            ..instance("web-missing", "hello")
+        }],
+    };
+    let err = unresolved
+        .render(&components)
+        .expect_err("unknown component");
+    assert!(err.to_string().contains("missing"));
+}
+
+#[test]
+fn test_env_name_collisions() {
+    let container = Container {
+        name: "web".into(),
+        image: "nginx:latest".into(),
+        env: vec![
+            Env {
+                name: "GREETING".into(),
+                value: Some("hello".into()),
+                from_param: None,
+                value_from: None,
+            },
+            Env {
+                name: "TARGET".into(),
+                value: None,
+                from_param: Some("target".into()),
+                value_from: None,
+            },
+        ],
+        ..Default::default()
+    };
+    assert_eq!(vec!["GREETING", "TARGET"], container.env_names());
+    assert!(container.validate_env_names().is_ok());
+
+    let mut collision = container.clone();
+    collision.env.push(Env {
+        name: "GREETING".into(),
+        value: None,
+        from_param: Some("greeting".into()),
+        value_from: None,
+    });
+    let err = collision
+        .validate_env_names()
+        .expect_err("plain and param env collide");
+    assert!(err.to_string().contains("GREETING"));
+    let component = Component {
+        containers: vec![collision],
+        ..Default::default()
+    };
+    assert!(component.validate().is_err());
+}
+
+#[test]
+fn test_default_resources_for() {
+    let task = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Task",
+            "containers": [{"name": "job", "image": "busybox:latest"}]
+        }"#,
+    )
+    .expect("component must parse");
+    let singleton = Component {
+        workload_type: "core.oam.dev/v1alpha1.SingletonServer".into(),
+        ..task.clone()
+    };
+
+    let requests = |component: &Component| {
+        component
+            .to_containers(BTreeMap::new())
+            .expect("containers")[0]
+            .resources
+            .clone()
+            .and_then(|r| r.requests)
+            .expect("requests must be set")
+    };
+    let task_requests = requests(&task);
+    assert_eq!(Some(&Quantity("0.5".into())), task_requests.get("cpu"));
+    assert_eq!(Some(&Quantity("256Mi".into())), task_requests.get("memory"));
+
+    let singleton_requests = requests(&singleton);
+    assert_eq!(Some(&Quantity("0.1".into())), singleton_requests.get("cpu"));
+    assert_eq!(
+        Some(&Quantity("128Mi".into())),
+        singleton_requests.get("memory")
+    );
+    assert_eq!(
+        Resources::default(),
+        default_resources_for("core.oam.dev/v1alpha1.SingletonServer")
+    );
+
+    // A container that declares its own resources keeps them, even the generic defaults.
+    let mut declared = task.clone();
+    declared.containers[0].resources = Some(Resources::default());
+    let declared_requests = requests(&declared);
+    assert_eq!(Some(&Quantity("0.1".into())), declared_requests.get("cpu"));
+    assert_eq!(
+        Some(&Quantity("128Mi".into())),
+        declared_requests.get("memory")
+    );
+    let restored: Component =
+        serde_json::from_value(declared.strip_defaults()).expect("stripped round trip");
+    assert_eq!(declared_requests, requests(&restored));
+
+    // A component overrides the defaults of its workload type.
+    let overridden = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Task",
+            "defaultResources": {"cpu": {"required": 2}, "memory": {"required": "1024"}},
+            "containers": [{"name": "job", "image": "busybox:latest"}]
+        }"#,
+    )
+    .expect("component must parse");
+    let overridden_requests = requests(&overridden);
+    assert_eq!(Some(&Quantity("2".into())), overridden_requests.get("cpu"));
+    assert_eq!(
+        Some(&Quantity("1024Mi".into())),
+        overridden_requests.get("memory")
+    );
+}
+
+#[test]
+fn test_from_crd() {
+    let crd = serde_json::json!({
+        "apiVersion": "core.oam.dev/v1alpha1",
+        "kind": "ComponentSchematic",
+        "metadata": {"name": "web"},
+        "spec": {
+            "workloadType": "core.oam.dev/v1alpha1.Server",
+            "containers": [{"name": "nginx", "image": "nginx:latest"}]
+        }
+    });
+    let (name, component) = Component::from_crd(&crd).expect("component must be read");
+    assert_eq!("web", name);
+    assert_eq!("core.oam.dev/v1alpha1.Server", component.workload_type);
+    assert_eq!("nginx", component.containers[0].name);
+
+    let mut wrong_kind = crd.clone();
+    wrong_kind["kind"] = serde_json::json!("ApplicationConfiguration");
+    let err = Component::from_crd(&wrong_kind).expect_err("kind must match");
+    assert!(err.to_string().contains("ApplicationConfiguration"));
+
+    let mut unnamed = crd.clone();
+    unnamed["metadata"] = serde_json::json!({});
+    assert!(Component::from_crd(&unnamed).is_err());
+}
+
+#[test]
+fn test_to_crd() {
+    let component = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Worker",
+            "containers": [{"name": "queue", "image": "busybox:latest"}]
+        }"#,
+    )
+    .expect("component must parse");
+
+    let crd = component.to_crd("queue-worker", &None);
+    assert_eq!("core.oam.dev/v1alpha1", crd["apiVersion"]);
+    assert_eq!("ComponentSchematic", crd["kind"]);
+    assert_eq!(serde_json::Value::Null, crd["status"]);
+
+    let (name, round_trip) = Component::from_crd(&crd).expect("component must be read");
+    assert_eq!("queue-worker", name);
+    assert_eq!(
+        serde_json::to_value(&component).expect("component json"),
+        serde_json::to_value(&round_trip).expect("component json")
+    );
+
+    let status = Some(OAMStatus::new(Some("Ready".into()), None));
+    let crd = component.to_crd("queue-worker", &status);
+    assert_eq!("Ready", crd["status"]["phase"]);
+}
+
+#[test]
+fn test_to_pod_spec_with_report() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "env": [
+                        {"name": "GREETING", "fromParam": "greeting"},
+                        {"name": "TARGET", "fromParam": "target", "value": "world"},
+                        {"name": "PLAIN", "value": "plain"}
+                    ],
+                    "resources": {
+                        "gpu": {"required": 1.5}
+                    }
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+
+    let mut params = BTreeMap::new();
+    params.insert("target".to_string(), serde_json::json!("everyone"));
+    let (pod_spec, report) = component
+        .to_pod_spec_with_report(params.clone())
+        .expect("pod spec");
+    assert_eq!(
+        serde_json::to_value(component.to_pod_spec(params).expect("pod spec"))
+            .expect("pod spec json"),
+        serde_json::to_value(pod_spec).expect("pod spec json")
+    );
+    assert_eq!(2, report.warnings.len());
+    assert!(report.warnings[0].contains("rounded up to 2"));
+    assert!(report.warnings[1].contains("GREETING"));
+    assert!(report.warnings[1].contains("greeting"));
+
+    let (_, report) = component
+        .to_pod_spec_with_report(BTreeMap::new())
+        .expect("pod spec");
+    assert_eq!(3, report.warnings.len());
+    assert!(report.warnings[2].contains("TARGET"));
+
+    let plain =
+        Component::from_str(r#"{"containers": [{"name": "web", "image": "nginx:latest"}]}"#)
+            .expect("component must parse");
+    assert!(plain
+        .to_pod_spec_with_report(BTreeMap::new())
+        .expect("pod spec")
+        .1
+        .is_empty());
+}
+
+#[test]
+fn test_probe_summary() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "livenessProbe": {
+                        "httpGet": {"path": "/healthz", "port": 8080, "httpHeaders": []}
+                    },
+                    "readinessProbe": {
+                        "tcpSocket": {"port": 8080}
+                    }
+                },
+                {
+                    "name": "worker",
+                    "image": "busybox:latest",
+                    "livenessProbe": {
+                        "exec": {"command": ["true"]}
+                    }
+                },
+                {
+                    "name": "plain",
+                    "image": "busybox:latest"
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+
+    assert_eq!(
+        vec![
+            ContainerProbes {
+                container: "web".into(),
+                liveness: Some(vec![ProbeHandler::HttpGet]),
+                readiness: Some(vec![ProbeHandler::TcpSocket]),
+            },
+            ContainerProbes {
+                container: "worker".into(),
+                liveness: Some(vec![ProbeHandler::Exec]),
+                readiness: None,
+            },
+            ContainerProbes {
+                container: "plain".into(),
+                liveness: None,
+                readiness: None,
+            },
+        ],
+        component.probe_summary()
+    );
+}
+
+#[test]
+fn test_volume_access_modes() {
+    let access_mode = |mode: &str, sharing: &str| {
+        let volume: Volume = serde_json::from_value(serde_json::json!({
+            "name": "data",
+            "mountPath": "/data",
+            "accessMode": mode,
+            "sharingPolicy": sharing
+        }))
+        .expect("volume must parse");
+        volume
+            .to_volume_claim_template()
+            .spec
+            .and_then(|s| s.access_modes)
+            .expect("access modes")
+    };
+    assert_eq!(vec!["ReadWriteOnce"], access_mode("RW", "Exclusive"));
+    assert_eq!(vec!["ReadWriteMany"], access_mode("RW", "Shared"));
+    assert_eq!(vec!["ReadOnlyMany"], access_mode("RO", "Exclusive"));
+    assert_eq!(vec!["ReadWriteMany"], access_mode("RWX", "Exclusive"));
+    assert_eq!(vec!["ReadWriteMany"], access_mode("RWX", "Shared"));
+}
+
+#[test]
+fn test_resources_with_overcommit() {
+    let resources = Resources {
+        cpu: CPU {
+            required: 0.25,
+            limit: None,
+        },
+        memory: Memory {
+            required: "256".into(),
+            limit: None,
+        },
+        ..Default::default()
+    };
+    let overcommitted = resources.with_overcommit(2.0).expect("2x overcommit");
+    assert_eq!(0.25, overcommitted.cpu.required);
+    assert_eq!(Some(0.5), overcommitted.cpu.limit);
+    assert_eq!(Some("512".to_string()), overcommitted.memory.limit);
+
+    let component = Component {
+        containers: vec![Container {
+            name: "web".into(),
+            image: "nginx:latest".into(),
+            resources: Some(overcommitted),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let limits = component
+        .to_containers(BTreeMap::new())
+        .expect("containers")[0]
+        .resources
+        .clone()
+        .and_then(|r| r.limits)
+        .expect("limits must be set");
+    assert_eq!(Some(&Quantity("0.5".into())), limits.get("cpu"));
+    assert_eq!(Some(&Quantity("512Mi".into())), limits.get("memory"));
+    assert!(component.validate().is_ok());
+
+    // A limit below the request is rejected.
+    let mut starved = component.clone();
+    starved.containers[0].resources = Some(Resources {
+        cpu: CPU {
+            required: 0.25,
+            limit: Some(0.1),
+        },
+        ..resources.clone()
+    });
+    let err = starved.validate().expect_err("CPU limit below request");
+    assert!(err.to_string().contains("CPU limit 0.1"));
+    starved.containers[0].resources = Some(Resources {
+        memory: Memory {
+            required: "256".into(),
+            limit: Some("128".into()),
+        },
+        ..resources.clone()
+    });
+    let err = starved.validate().expect_err("memory limit below request");
+    assert!(err.to_string().contains("memory limit 128"));
+
+    assert!(resources.with_overcommit(0.5).is_err());
+    let mut unparsable = resources.clone();
+    unparsable.memory.required = "plenty".into();
+    assert!(unparsable.with_overcommit(2.0).is_err());
+}
+
+#[test]
+fn test_effective_os_arch() {
+    let component = Component {
+        os_type: Some("Win".into()),
+        arch: Some("x86_64".into()),
+        ..Default::default()
+    };
+    assert_eq!(
+        (Some("windows".to_string()), Some("amd64".to_string())),
+        component.effective_os_arch().expect("aliases must map")
+    );
+    let mut selector = BTreeMap::new();
+    selector.insert("kubernetes.io/os".to_string(), "windows".to_string());
+    selector.insert("kubernetes.io/arch".to_string(), "amd64".to_string());
+    assert_eq!(Some(selector), component.to_node_selector());
+
+    let arm = Component {
+        arch: Some("aarch64".into()),
+        ..Default::default()
+    };
+    assert_eq!(
+        (None, Some("arm64".to_string())),
+        arm.effective_os_arch().expect("aliases must map")
+    );
+
+    // Names Kubernetes uses that have no alias are passed through, in lower case.
+    for arch in &["amd64", "arm", "ppc64le", "s390x", "386"] {
+        let other = Component {
+            arch: Some(arch.to_uppercase()),
+            ..Default::default()
+        };
+        assert_eq!(
+            (None, Some(arch.to_string())),
+            other.effective_os_arch().expect("arch passes through")
+        );
+    }
+    let linux = Component {
+        os_type: Some("Linux".into()),
+        ..Default::default()
+    };
+    assert_eq!(
+        (Some("linux".to_string()), None),
+        linux.effective_os_arch().expect("os passes through")
+    );
+
+    let unknown = Component {
+        arch: Some("sparc".into()),
+        ..Default::default()
+    };
+    let err = unknown.effective_os_arch().expect_err("unknown arch");
+    assert!(err.to_string().contains("sparc"));
+    assert!(unknown.validate().is_err());
+
+    let invalid = Component {
+        arch: Some("x86 64".into()),
+        ..Default::default()
+    };
+    let err = invalid.effective_os_arch().expect_err("invalid arch");
+    assert!(err.to_string().contains("x86 64"));
+    assert!(invalid.validate().is_err());
+}
+
+#[test]
+fn test_to_pod_disruption_budget() {
+    let with_budget = |workload_type: &str, value: serde_json::Value| Component {
+        workload_type: workload_type.into(),
+        workload_settings: vec![workload_setting("pdb", value)],
+        ..Default::default()
+    };
+
+    let pdb = with_budget(
+        "core.oam.dev/v1alpha1.Server",
+        serde_json::json!({ "minAvailable": 2 }),
+    )
+    .to_pod_disruption_budget("squid", BTreeMap::new())
+    .expect("pdb")
+    .expect("pdb is set");
+    assert_eq!(Some("squid".to_string()), pdb.metadata.unwrap().name);
+    let spec = pdb.spec.expect("spec");
+    assert_eq!(Some(IntOrString::Int(2)), spec.min_available);
+    assert_eq!(None, spec.max_unavailable);
+    assert_eq!(
+        Some(&"squid".to_string()),
+        spec.selector
+            .and_then(|s| s.match_labels)
+            .unwrap()
+            .get("oam.dev/instance-name")
+    );
+
+    let err = with_budget(
+        "core.oam.dev/v1alpha1.Server",
+        serde_json::json!({ "minAvailable": 2, "maxUnavailable": "50%" }),
+    )
+    .to_pod_disruption_budget("squid", BTreeMap::new())
+    .expect_err("both limits are set");
+    assert!(err.to_string().contains("exactly one"));
+
+    assert!(
+        with_budget("core.oam.dev/v1alpha1.Worker", serde_json::json!({}))
+            .to_pod_disruption_budget("squid", BTreeMap::new())
+            .is_err()
+    );
+    assert!(with_budget(
+        "core.oam.dev/v1alpha1.SingletonServer",
+        serde_json::json!({ "maxUnavailable": 1 })
+    )
+    .to_pod_disruption_budget("squid", BTreeMap::new())
+    .is_err());
+    assert!(Component::default()
+        .to_pod_disruption_budget("squid", BTreeMap::new())
+        .expect("no pdb")
+        .is_none());
+
+    // An extended workload type interprets its settings itself.
+    let extended = with_budget(
+        "openfaas.com/v1alpha2.Function",
+        serde_json::json!({ "maxUnavailable": 1 }),
+    );
+    assert!(extended.validate_workload_settings(BTreeMap::new()).is_ok());
+    let mut strategy = extended.clone();
+    strategy.workload_settings[0].name = "deploymentStrategy".into();
+    strategy.workload_settings[0].value = Some(serde_json::json!({ "type": "BlueGreen" }));
+    assert!(strategy.validate_workload_settings(BTreeMap::new()).is_ok());
+    strategy.workload_type = "core.oam.dev/v1alpha1.Server".into();
+    assert!(strategy
+        .validate_workload_settings(BTreeMap::new())
+        .is_err());
+}
+
+#[test]
+fn test_redacted() {
+    let env = |name: &str, value: &str| Env {
+        name: name.into(),
+        value: Some(value.into()),
+        from_param: None,
+        value_from: None,
+    };
+    let component = Component {
+        containers: vec![Container {
+            name: "web".into(),
+            env: vec![
+                env("DB_PASSWORD", "hunter2"),
+                env("LOG_LEVEL", "debug"),
+                env("api_token", "abc123"),
+            ],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    let redacted = component.redacted();
+    let values: Vec<Option<String>> = redacted.containers[0]
+        .env
+        .iter()
+        .map(|e| e.value.clone())
+        .collect();
+    assert_eq!(
+        vec![
+            Some("***".to_string()),
+            Some("debug".to_string()),
+            Some("***".to_string())
+        ],
+        values
+    );
+    // The original is left unchanged.
+    assert_eq!(
+        Some("hunter2".to_string()),
+        component.containers[0].env[0].value
+    );
+
+    let custom = component.redacted_with(&["LOG_*"]);
+    assert_eq!(
+        Some("hunter2".to_string()),
+        custom.containers[0].env[0].value
+    );
+    assert_eq!(Some("***".to_string()), custom.containers[0].env[1].value);
+}
+
+#[test]
+fn test_http_get_probe_without_headers() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "livenessProbe": {
+                        "httpGet": {"path": "/healthz", "port": 8080}
+                    }
+                }
+            ]
+        }"#,
+    )
+    .expect("a probe without headers must parse");
+    let http_get = component.containers[0]
+        .liveness_probe
+        .as_ref()
+        .and_then(|p| p.http_get.as_ref())
+        .expect("http get probe");
+    assert!(http_get.http_headers.is_empty());
+
+    let action = component
+        .to_containers(BTreeMap::new())
+        .expect("containers")[0]
+        .liveness_probe
+        .clone()
+        .and_then(|p| p.http_get)
+        .expect("rendered http get probe");
+    assert_eq!(None, action.http_headers);
+    assert_eq!(Some("/healthz".to_string()), action.path);
+}
+
+#[test]
+fn test_effective_replica_count() {
+    let component = |workload_type: &str| Component {
+        workload_type: workload_type.into(),
+        ..Default::default()
+    };
+    let count = |c: &Component| c.effective_replica_count(BTreeMap::new()).expect("count");
+
+    let mut server = component("core.oam.dev/v1alpha1.Server");
+    assert_eq!(Some(1), count(&server));
+    server.scale(3).expect("scale");
+    assert_eq!(Some(3), count(&server));
+
+    let mut worker = component("core.oam.dev/v1alpha1.Worker");
+    worker.scale(0).expect("scale");
+    assert_eq!(Some(0), count(&worker));
+
+    assert_eq!(
+        Some(1),
+        count(&component("core.oam.dev/v1alpha1.SingletonServer"))
+    );
+    assert_eq!(
+        Some(1),
+        count(&component("core.oam.dev/v1alpha1.SingletonWorker"))
+    );
+    let mut stateful = component("core.oam.dev/v1alpha1.StatefulService");
+    assert_eq!(Some(1), count(&stateful));
+    stateful.scale(2).expect("scale");
+    assert_eq!(Some(2), count(&stateful));
+    assert_eq!(None, count(&component("core.oam.dev/v1alpha1.Task")));
+    assert_eq!(
+        None,
+        count(&component("core.oam.dev/v1alpha1.ScheduledTask"))
+    );
+    assert_eq!(None, count(&component("openfaas.com/v1alpha2.Function")));
+}
+
+#[test]
+fn test_to_containers_omits_empty_lists() {
+    let mut component =
+        Component::from_str(r#"{"containers": [{"name": "web", "image": "nginx:latest"}]}"#)
+            .expect("component must parse");
+    let rendered = serde_json::to_value(
+        &component
+            .to_containers(BTreeMap::new())
+            .expect("containers")[0],
+    )
+    .expect("container must serialize");
+    assert!(rendered.get("env").is_none());
+    assert!(rendered.get("ports").is_none());
+
+    for c in component.containers_mut() {
+        c.env.push(Env {
+            name: "MODE".into(),
+            value: Some("debug".into()),
+            from_param: None,
+            value_from: None,
+        });
+    }
+    let container = &component
+        .to_containers(BTreeMap::new())
+        .expect("containers")[0];
+    assert_eq!(1, container.env.as_ref().expect("env").len());
+    assert_eq!(None, container.ports);
+}
+
+#[test]
+fn test_validate_unique_parameter_and_setting_names() {
+    let component = Component::from_str(
+        r#"{
+            "parameters": [
+                {"name": "replicas", "type": "number"},
+                {"name": "mode", "type": "string"}
+            ],
+            "workloadSettings": [
+                {"name": "replicaCount", "type": "number", "fromParam": "replicas"},
+                {"name": "serviceType", "type": "string", "value": "ClusterIP"}
+            ],
+            "containers": [{"name": "web", "image": "nginx:latest"}]
+        }"#,
+    )
+    .expect("component must parse");
+    assert!(component.validate().is_ok());
+
+    let mut duplicate_parameter = component.clone();
+    duplicate_parameter
+        .parameters
+        .push(duplicate_parameter.parameters[0].clone());
+    let err = duplicate_parameter
+        .validate()
+        .expect_err("duplicate parameter");
+    assert!(err.to_string().contains("parameter replicas"));
+
+    let mut duplicate_setting = component.clone();
+    duplicate_setting
+        .workload_settings
+        .push(duplicate_setting.workload_settings[1].clone());
+    let err = duplicate_setting
+        .validate()
+        .expect_err("duplicate workload setting");
+    assert!(err.to_string().contains("workload setting serviceType"));
+}
+
+#[test]
+fn test_env_value_from() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "env": [
+                        {
+                            "name": "POD_NAMESPACE",
+                            "valueFrom": {"fieldRef": {"fieldPath": "metadata.namespace"}}
+                        },
+                        {
+                            "name": "MEMORY_LIMIT",
+                            "valueFrom": {
+                                "resourceFieldRef": {"resource": "limits.memory", "divisor": "1Mi"}
+                            }
+                        }
+                    ]
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    assert!(component.validate().is_ok());
+
+    let env = component
+        .to_containers(BTreeMap::new())
+        .expect("containers")[0]
+        .env
+        .clone()
+        .expect("env");
+    assert_eq!(None, env[0].value);
+    assert_eq!(
+        "metadata.namespace",
+        env[0]
+            .value_from
+            .as_ref()
+            .and_then(|s| s.field_ref.as_ref())
+            .expect("field ref")
+            .field_path
+    );
+    let resource = env[1]
+        .value_from
+        .as_ref()
+        .and_then(|s| s.resource_field_ref.as_ref())
+        .expect("resource field ref");
+    assert_eq!("limits.memory", resource.resource);
+    assert_eq!(Some(Quantity("1Mi".into())), resource.divisor);
+    assert_eq!(None, resource.container_name);
+
+    let mut invalid = component.clone();
+    invalid.containers[0].env[0].value_from = Some(EnvSource {
+        field_ref: Some(FieldRef {
+            field_path: "spec.containers".into(),
+        }),
+        resource_field_ref: None,
+    });
+    assert!(invalid.validate().is_err());
+    assert!(invalid.to_containers(BTreeMap::new()).is_err());
+
+    // valueFrom takes precedence over value, which is reported.
+    let mut both = component.clone();
+    both.containers[0].env[0].value = Some("default".into());
+    assert!(both.validate().is_ok());
+    let (_, report) = both
+        .to_pod_spec_with_report(BTreeMap::new())
+        .expect("pod spec");
+    assert!(report.warnings[0].contains("overrides its value"));
+}
+
+#[test]
+fn test_automount_service_account_token() {
+    let component = Component::from_str(
+        r#"{
+            "automountServiceAccountToken": false,
+            "containers": [{"name": "web", "image": "nginx:latest"}]
+        }"#,
+    )
+    .expect("component must parse");
+    assert_eq!(
+        Some(false),
+        component
+            .to_pod_spec(BTreeMap::new())
+            .expect("pod spec")
+            .automount_service_account_token
+    );
+
+    assert_eq!(
+        None,
+        Component::default()
+            .to_pod_spec(BTreeMap::new())
+            .expect("pod spec")
+            .automount_service_account_token
+    );
+}
+
+#[test]
+fn test_parameter_bindings() {
+    let component = Component::from_str(
+        r#"{
+            "parameters": [
+                {"name": "dbHost", "type": "string"},
+                {"name": "replicas", "type": "number"},
+                {"name": "unused", "type": "string"}
+            ],
+            "workloadSettings": [
+                {"name": "replicaCount", "type": "number", "fromParam": "replicas"}
+            ],
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "env": [
+                        {"name": "DB_HOST", "fromParam": "dbHost"},
+                        {"name": "LOG_LEVEL", "value": "info"}
+                    ]
+                }
+            ],
+            "initContainers": [
+                {
+                    "name": "migrate",
+                    "image": "migrate:latest",
+                    "env": [{"name": "DATABASE_HOST", "fromParam": "dbHost"}]
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+
+    let bindings = component.parameter_bindings();
+    assert_eq!(3, bindings.len());
+    assert_eq!(
+        vec![
+            "containers[0].env[DB_HOST]".to_string(),
+            "initContainers[0].env[DATABASE_HOST]".to_string()
+        ],
+        bindings["dbHost"]
+    );
+    assert_eq!(
+        vec!["workloadSettings[replicaCount]".to_string()],
+        bindings["replicas"]
+    );
+    assert!(bindings["unused"].is_empty());
+}
+
+#[test]
+fn test_validate_parameter_references() {
+    let component = Component::from_str(
+        r#"{
+            "parameters": [{"name": "dbHost", "type": "string"}],
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "env": [{"name": "DB_HOST", "fromParam": "dbHost"}]
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    assert!(component.validate().is_ok());
+
+    let mut dangling = component.clone();
+    dangling.workload_settings.push(WorkloadSetting {
+        name: "replicaCount".into(),
+        description: None,
+        parameter_type: ParameterType::Number,
+        required: false,
+        value: None,
+        from_param: Some("replicas".into()),
+    });
+    let err = dangling.validate().expect_err("undeclared parameter");
+    assert!(err
+        .to_string()
+        .contains("workloadSettings[replicaCount] refers to parameter replicas"));
+
+    let mut dangling_env = component.clone();
+    dangling_env.containers[0].env[0].from_param = Some("host".into());
+    let err = dangling_env.validate().expect_err("undeclared parameter");
+    assert!(err
+        .to_string()
+        .contains("containers[0].env[DB_HOST] refers to parameter host"));
+}
+
+#[test]
+fn test_port_app_protocol() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "api",
+                    "image": "api:latest",
+                    "ports": [
+                        {"name": "grpc", "containerPort": 9090, "appProtocol": "grpc"},
+                        {"name": "metrics", "containerPort": 9100}
+                    ]
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    assert_eq!(
+        Some("grpc".to_string()),
+        component.containers[0].ports[0].app_protocol
+    );
+    assert_eq!(None, component.containers[0].ports[1].app_protocol);
+    assert!(component.validate().is_ok());
+
+    let (_, report) = component
+        .to_pod_spec_with_report(BTreeMap::new())
+        .expect("pod spec");
+    assert!(report.warnings.is_empty());
+
+    let mut unknown = component.clone();
+    unknown.containers[0].ports[0].app_protocol = Some("smtp".into());
+    let err = unknown.validate().expect_err("unknown app protocol");
+    assert!(err.to_string().contains("appProtocol smtp"));
+}
+
+#[test]
+fn test_container_count_and_total_gpus() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "trainer",
+                    "image": "trainer:latest",
+                    "resources": {"gpu": {"required": 2}}
+                },
+                {
+                    "name": "evaluator",
+                    "image": "evaluator:latest",
+                    "resources": {"gpu": {"required": 1}}
+                },
+                {"name": "web", "image": "nginx:latest"}
+            ],
+            "initContainers": [
+                {
+                    "name": "warmup",
+                    "image": "warmup:latest",
+                    "resources": {"gpu": {"required": 1}}
+                }
+            ],
+            "sidecars": [{"name": "proxy", "image": "envoy:latest"}]
+        }"#,
+    )
+    .expect("component must parse");
+
+    assert_eq!(5, component.container_count());
+    assert_eq!(4.0, component.total_gpus());
+
+    assert_eq!(0, Component::default().container_count());
+    assert_eq!(0.0, Component::default().total_gpus());
+}
+
+#[test]
+fn test_from_yaml_resolves_anchors() {
+    let component = Component::from_yaml(
+        r#"
+apiVersion: core.oam.dev/v1alpha1
+kind: ComponentSchematic
+metadata:
+  name: web
+spec:
+  containers:
+    - &web
+      name: web
+      image: nginx:latest
+      env:
+        - name: LOG_LEVEL
+          value: info
+    - *web
+  sidecars:
+    - <<: *web
+      name: proxy
+"#,
+    )
+    .expect("component must parse");
+
+    assert_eq!(2, component.containers.len());
+    assert_eq!(component.containers[0], component.containers[1]);
+    assert_eq!("nginx:latest", component.containers[1].image);
+
+    let proxy = &component.sidecars[0];
+    assert_eq!("proxy", proxy.name);
+    assert_eq!("nginx:latest", proxy.image);
+    assert_eq!("LOG_LEVEL", proxy.env[0].name);
+}
+
+#[test]
+fn test_canonical_json() {
+    let one = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Server",
+            "containers": [{"name": "web", "image": "nginx:latest"}]
+        }"#,
+    )
+    .expect("component must parse");
+    let two = Component::from_yaml(
+        r#"
+containers:
+  - image: nginx:latest
+    name: web
+workloadType: core.oam.dev/v1alpha1.Server
+"#,
+    )
+    .expect("component must parse");
+
+    let canonical = one.canonical_json();
+    assert_eq!(canonical, two.canonical_json());
+    assert_eq!(canonical, one.clone().canonical_json());
+    assert!(!canonical.contains(' '));
+    assert!(!canonical.contains('\n'));
+    // Keys are sorted, so "anti" comes first and "workloadType" last.
+    assert!(canonical.starts_with(r#"{"antiAffinity":null,"#));
+    assert!(canonical.ends_with(r#""workloadType":"core.oam.dev/v1alpha1.Server"}"#));
+
+    let mut changed = one.clone();
+    changed.containers[0].image = "nginx:1.17".into();
+    assert_ne!(canonical, changed.canonical_json());
+}
+
+#[test]
+fn test_gpu_resources() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "trainer",
+                    "image": "trainer:latest",
+                    "resources": {"gpu": {"required": 2}}
+                },
+                {
+                    "name": "scheduler-friendly",
+                    "image": "trainer:latest",
+                    "resources": {"gpu": {"required": 1, "mirrorRequests": true}}
+                },
+                {
+                    "name": "cpu-only",
+                    "image": "trainer:latest"
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    let containers = component
+        .to_containers(BTreeMap::new())
+        .expect("containers");
+    let resources = |i: usize| containers[i].resources.clone().expect("resources");
+
+    // By default, GPUs are only a limit.
+    let limits_only = resources(0);
+    assert_eq!(
+        Some(&Quantity("2".into())),
+        limits_only.limits.expect("limits").get(GPU_RESOURCE_NAME)
+    );
+    assert_eq!(
+        None,
+        limits_only
+            .requests
+            .expect("requests")
+            .get(GPU_RESOURCE_NAME)
+    );
+
+    let mirrored = resources(1);
+    assert_eq!(
+        Some(&Quantity("1".into())),
+        mirrored.limits.expect("limits").get(GPU_RESOURCE_NAME)
+    );
+    assert_eq!(
+        Some(&Quantity("1".into())),
+        mirrored.requests.expect("requests").get(GPU_RESOURCE_NAME)
+    );
+
+    assert_eq!(None, resources(2).limits);
+}
+
+#[test]
+fn test_validate_unique_container_names() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {"name": "web", "image": "nginx:latest"},
+                {"name": "cache", "image": "redis:5"}
+            ],
+            "initContainers": [
+                {"name": "migrate", "image": "migrate:latest"}
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    assert!(component.validate_unique_container_names().is_ok());
+
+    let mut duplicate = component.clone();
+    duplicate.init_containers[0].name = "cache".into();
+    let err = duplicate
+        .validate_unique_container_names()
+        .expect_err("duplicate container name");
+    assert!(err.to_string().contains("container name cache"));
+    assert!(duplicate.validate().is_err());
+}
+
+#[test]
+fn test_port_range() {
+    let component = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Server",
+            "containers": [
+                {
+                    "name": "cluster",
+                    "image": "redis:5",
+                    "ports": [
+                        {"name": "client", "containerPort": 6379},
+                        {"name": "bus", "containerPortRange": {"start": 7000, "end": 7002}}
+                    ]
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    assert!(component.validate().is_ok());
+
+    let ports = component
+        .to_containers(BTreeMap::new())
+        .expect("containers")[0]
+        .ports
+        .clone()
+        .expect("ports");
+    let rendered: Vec<(Option<String>, i32)> = ports
+        .into_iter()
+        .map(|p| (p.name, p.container_port))
+        .collect();
+    assert_eq!(
+        vec![
+            (Some("client".to_string()), 6379),
+            (Some("bus-7000".to_string()), 7000),
+            (Some("bus-7001".to_string()), 7001),
+            (Some("bus-7002".to_string()), 7002),
+        ],
+        rendered
+    );
+    assert_eq!(4, component.listening_ports().len());
+
+    let mut inverted = component.clone();
+    inverted.containers[0].ports[1].container_port_range = Some(PortRange {
+        start: 7002,
+        end: 7000,
+    });
+    let err = inverted.validate().expect_err("inverted range");
+    assert!(err.to_string().contains("start is after its end"));
+
+    let mut too_large = component.clone();
+    too_large.containers[0].ports[1].container_port_range = Some(PortRange {
+        start: 7000,
+        end: 8000,
+    });
+    assert!(too_large.validate().is_err());
+
+    // Each expanded name must fit Kubernetes' 15 character limit on port names.
+    let mut long_names = component.clone();
+    long_names.containers[0].ports[1].name = "grpc-backend".into();
+    let err = long_names.validate().expect_err("long port names");
+    assert!(err.to_string().contains("grpc-backend-7002"));
+
+    let mut no_port = component.clone();
+    no_port.containers[0].ports[0].container_port = None;
+    let err = no_port.validate().expect_err("no container port");
+    assert!(err
+        .to_string()
+        .contains("must set containerPort or containerPortRange"));
+}
+
+#[test]
+fn test_workload_settings_map() {
+    let component = Component {
+        workload_settings: vec![
+            workload_setting("replicaCount", serde_json::json!(3)),
+            workload_setting("minReadySeconds", serde_json::json!(10)),
+        ],
+        ..Default::default()
+    };
+    let settings = component.workload_settings_map().expect("settings");
+    assert_eq!(2, settings.len());
+    assert_eq!(
+        Some(serde_json::json!(3)),
+        settings["replicaCount"].value.clone()
+    );
+    assert!(settings.get("headless").is_none());
+
+    let mut duplicate = component.clone();
+    duplicate
+        .workload_settings
+        .push(workload_setting("replicaCount", serde_json::json!(5)));
+    let err = duplicate
+        .workload_settings_map()
+        .expect_err("duplicate setting");
+    assert!(err
+        .to_string()
+        .contains("workload setting replicaCount is defined more than once"));
+}
+
+#[test]
+fn test_pod_spec_order() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "resources": {
+                        "volumes": [
+                            {"name": "zeta", "mountPath": "/zeta", "disk": {"required": "1G", "ephemeral": true}},
+                            {"name": "alpha", "mountPath": "/alpha", "disk": {"required": "1G", "ephemeral": true}}
+                        ]
+                    }
+                },
+                {
+                    "name": "api",
+                    "image": "api:latest",
+                    "resources": {
+                        "volumes": [
+                            {"name": "mid", "mountPath": "/mid", "disk": {"required": "1G", "ephemeral": true}}
+                        ]
+                    }
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    let pod_spec = component.to_pod_spec(BTreeMap::new()).expect("pod spec");
+    let containers: Vec<String> = pod_spec.containers.iter().map(|c| c.name.clone()).collect();
+    assert_eq!(vec!["web", "api"], containers);
+    let volumes: Vec<String> = pod_spec
+        .volumes
+        .expect("volumes")
+        .into_iter()
+        .map(|v| v.name)
+        .collect();
+    assert_eq!(vec!["alpha", "mid", "zeta"], volumes);
+
+    // Reordering the volumes does not change the pod spec.
+    let mut reordered = component.clone();
+    if let Some(volumes) = reordered.containers[0]
+        .resources
+        .as_mut()
+        .and_then(|r| r.volumes.as_mut())
+    {
+        volumes.reverse();
+    }
+    assert_eq!(
+        component
+            .to_pod_spec(BTreeMap::new())
+            .expect("pod spec")
+            .volumes,
+        reordered
+            .to_pod_spec(BTreeMap::new())
+            .expect("pod spec")
+            .volumes
+    );
+}
+
+#[test]
+fn test_required_capabilities() {
+    let component = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Server",
+            "containers": [
+                {
+                    "name": "trainer",
+                    "image": "trainer:latest",
+                    "resources": {
+                        "gpu": {"required": 1},
+                        "volumes": [
+                            {"name": "models", "mountPath": "/models", "sharingPolicy": "Shared", "disk": {"required": "10G", "ephemeral": false}},
+                            {"name": "scratch", "mountPath": "/scratch", "disk": {"required": "1G", "ephemeral": true}}
+                        ]
+                    }
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    assert_eq!(
+        vec![
+            Capability::ExtendedResource(GPU_RESOURCE_NAME.to_string()),
+            Capability::AccessMode("ReadWriteMany".to_string()),
+        ],
+        component.required_capabilities()
+    );
+
+    // Capabilities of the attached traits are listed alongside the component's.
+    let autoscaler = TraitBinding {
+        name: "auto-scaler".into(),
+        parameter_values: None,
+        properties: Some(serde_json::json!({ "maximum": 5 })),
+    };
+    let mut capabilities = component.required_capabilities();
+    capabilities.extend(autoscaler.required_capabilities());
+    assert!(capabilities.contains(&Capability::ExtendedResource("nvidia.com/gpu".to_string())));
+    assert!(capabilities.contains(&Capability::ApiVersion("autoscaling/v2beta1".to_string())));
+
+    let plain =
+        Component::from_str(r#"{"containers": [{"name": "web", "image": "nginx:latest"}]}"#)
+            .expect("component must parse");
+    assert!(plain.required_capabilities().is_empty());
+}
+
+#[test]
+fn test_validate_volume_access_modes() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "writer",
+                    "image": "writer:latest",
+                    "resources": {
+                        "volumes": [
+                            {"name": "data", "mountPath": "/var/data", "sharingPolicy": "Shared"},
+                            {"name": "cache", "mountPath": "/var/cache"}
+                        ]
+                    }
+                },
+                {
+                    "name": "reader",
+                    "image": "reader:latest",
+                    "resources": {
+                        "volumes": [
+                            {"name": "data", "mountPath": "/srv/data", "accessMode": "RO", "sharingPolicy": "Shared"}
+                        ]
+                    }
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    // A Shared volume may be written by one container and read by another.
+    assert!(component.validate().is_ok());
+    // The pod volume is writable whichever container is declared first.
+    let data_read_only = |component: &Component| {
+        component
+            .to_pod_spec(BTreeMap::new())
+            .expect("pod spec")
+            .volumes
+            .expect("volumes")
+            .into_iter()
+            .find(|v| v.name == "data")
+            .and_then(|v| v.persistent_volume_claim)
+            .and_then(|pvc| pvc.read_only)
+    };
+    assert_eq!(Some(false), data_read_only(&component));
+    let mut reader_first = component.clone();
+    reader_first.containers.reverse();
+    assert_eq!(Some(false), data_read_only(&reader_first));
+    let mut readers = component.clone();
+    if let Some(volumes) = readers.containers[0]
+        .resources
+        .as_mut()
+        .and_then(|r| r.volumes.as_mut())
+    {
+        volumes[0].access_mode = AccessMode::RO;
+    }
+    assert_eq!(Some(true), data_read_only(&readers));
+
+    // An Exclusive volume mounted twice by one container must keep its access mode.
+    let mut conflict = component.clone();
+    if let Some(volumes) = conflict.containers[0]
+        .resources
+        .as_mut()
+        .and_then(|r| r.volumes.as_mut())
+    {
+        volumes.push(Volume {
+            mount_path: "/srv/cache".into(),
+            access_mode: AccessMode::RO,
+            ..volumes[1].clone()
+        });
+    }
+    let mut same_mode = conflict.clone();
+    if let Some(volumes) = same_mode.containers[0]
+        .resources
+        .as_mut()
+        .and_then(|r| r.volumes.as_mut())
+    {
+        volumes[2].access_mode = AccessMode::RW;
+    }
+    assert!(same_mode.validate().is_ok());
+    let err = conflict.validate().expect_err("conflicting access modes");
+    assert!(err
+        .to_string()
+        .contains("volume cache is Exclusive, but container writer mounts it as both RW and RO"));
+}
+
+/// Assert that a component survives serialization unchanged, as JSON, as YAML and with
+/// its defaults stripped, and that its pod spec does too.
+fn assert_round_trip(component: &Component) {
+    let json = serde_json::to_value(component).expect("component json");
+    let from_json: Component = serde_json::from_value(json.clone()).expect("json round trip");
+    assert_eq!(component, &from_json);
+    assert_eq!(
+        json,
+        serde_json::to_value(&from_json).expect("component json")
+    );
+
+    let yaml = serde_yaml::to_string(component).expect("component yaml");
+    assert_eq!(
+        component,
+        &Component::from_yaml(yaml.as_str()).expect("yaml round trip")
+    );
+
+    let mut restored: Component =
+        serde_json::from_value(component.strip_defaults()).expect("stripped round trip");
+    restored.apply_defaults();
+    let mut expected = component.clone();
+    expected.apply_defaults();
+    assert_eq!(expected, restored);
+
+    // The API server drops empty lists, so a rendered one would differ from the live
+    // object on every reconcile.
+    let pod_spec = serde_json::to_value(component.to_pod_spec(BTreeMap::new()).expect("pod spec"))
+        .expect("pod spec json");
+    let mut empty = vec![];
+    find_empty_arrays(&pod_spec, "", &mut empty);
+    assert!(empty.is_empty(), "pod spec has empty lists at {:?}", empty);
+    let reread: k8s_openapi::api::core::v1::PodSpec =
+        serde_json::from_value(pod_spec.clone()).expect("pod spec round trip");
+    assert_eq!(
+        pod_spec,
+        serde_json::to_value(reread).expect("pod spec json")
+    );
+    assert_eq!(
+        component.to_pod_spec(BTreeMap::new()).expect("pod spec"),
+        from_json.to_pod_spec(BTreeMap::new()).expect("pod spec")
+    );
+}
+
+fn find_empty_arrays(value: &serde_json::Value, path: &str, found: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Array(items) if items.is_empty() => found.push(path.to_string()),
+        serde_json::Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                find_empty_arrays(item, format!("{}/{}", path, i).as_str(), found);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for (key, item) in map.iter() {
+                find_empty_arrays(item, format!("{}/{}", path, key).as_str(), found);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[test]
+fn test_round_trip_minimal() {
+    assert_round_trip(
+        &Component::from_str(r#"{"containers": [{"name": "web", "image": "nginx:latest"}]}"#)
+            .expect("component must parse"),
+    );
+}
+
+#[test]
+fn test_round_trip_full() {
+    assert_round_trip(
+        &Component::from_str(
+            r#"{
+                "workloadType": "core.oam.dev/v1alpha1.Server",
+                "osType": "linux",
+                "parameters": [
+                    {"name": "greeting", "type": "string", "default": "hello"},
+                    {"name": "replicas", "type": "number", "default": 2}
+                ],
+                "workloadSettings": [
+                    {"name": "replicaCount", "type": "number", "fromParam": "replicas"}
+                ],
+                "initContainers": [
+                    {"name": "migrate", "image": "migrate:1.0", "cmd": ["migrate"], "args": ["up"]}
+                ],
+                "sidecars": [
+                    {"name": "log-shipper", "image": "fluent/fluent-bit:2.1"}
+                ],
+                "containers": [
+                    {
+                        "name": "web",
+                        "image": "example.com/web:1.2",
+                        "imagePullSecret": "registry",
+                        "env": [
+                            {"name": "GREETING", "fromParam": "greeting"},
+                            {"name": "PLAIN", "value": "plain"}
+                        ],
+                        "ports": [
+                            {"name": "http", "containerPort": 8080, "appProtocol": "http"},
+                            {"name": "dns", "containerPort": 53, "protocol": "UDP"},
+                            {"name": "bus", "containerPortRange": {"start": 7000, "end": 7001}}
+                        ],
+                        "config": [
+                            {"path": "/etc/web/web.conf", "value": "debug = false"}
+                        ],
+                        "resources": {
+                            "cpu": {"required": 0.5, "limit": 1.0},
+                            "memory": {"required": "256", "limit": "512"},
+                            "gpu": {"required": 1, "mirrorRequests": true},
+                            "volumes": [
+                                {"name": "cache", "mountPath": "/cache", "disk": {"required": "1G", "ephemeral": true}},
+                                {"name": "data", "mountPath": "/data", "accessMode": "RO", "sharingPolicy": "Shared", "disk": {"required": "10G", "ephemeral": false}}
+                            ]
+                        }
+                    }
+                ]
+            }"#,
+        )
+        .expect("component must parse"),
+    );
+}
+
+#[test]
+fn test_round_trip_probes() {
+    assert_round_trip(
+        &Component::from_str(
+            r#"{
+                "parameters": [
+                    {"name": "period", "type": "number", "default": 15}
+                ],
+                "containers": [
+                    {
+                        "name": "web",
+                        "image": "nginx:latest",
+                        "ports": [{"name": "http", "containerPort": 80}],
+                        "livenessProbe": {
+                            "httpGet": {"path": "/healthz", "port": 80},
+                            "failureThreshold": 5,
+                            "fromParam": {"periodSeconds": "period"}
+                        },
+                        "readinessProbe": {
+                            "httpGet": {
+                                "path": "/ready",
+                                "port": 80,
+                                "httpHeaders": [{"name": "X-Probe", "value": "ready"}]
+                            },
+                            "initialDelaySeconds": 5
+                        }
+                    },
+                    {
+                        "name": "worker",
+                        "image": "worker:latest",
+                        "livenessProbe": {"exec": {"command": ["cat", "/tmp/healthy"]}},
+                        "readinessProbe": {"tcpSocket": {"port": 9090}}
+                    }
+                ]
+            }"#,
+        )
+        .expect("component must parse"),
+    );
+}
+
+#[test]
+fn test_image_pull_policy() {
+    let container = |image: &str| Container {
+        name: "web".into(),
+        image: image.into(),
+        ..Default::default()
+    };
+    assert_eq!(
+        "Always",
+        container("nginx:latest").effective_image_pull_policy()
+    );
+    assert_eq!("Always", container("nginx").effective_image_pull_policy());
+    assert_eq!(
+        "IfNotPresent",
+        container("nginx:1.17").effective_image_pull_policy()
+    );
+    assert_eq!(
+        "IfNotPresent",
+        container("example.com:5000/web@sha256:0123456789abcdef0123456789abcdef")
+            .effective_image_pull_policy()
+    );
+
+    let component = Component {
+        containers: vec![
+            container("nginx:latest"),
+            Container {
+                name: "api".into(),
+                image_pull_policy: Some("Never".into()),
+                ..container("api:1.0")
+            },
+            Container {
+                name: "worker".into(),
+                ..container("worker:2.3")
+            },
+        ],
+        ..Default::default()
+    };
+    assert!(component.validate().is_ok());
+    let policies: Vec<Option<String>> = component
+        .to_containers(BTreeMap::new())
+        .expect("containers")
+        .into_iter()
+        .map(|c| c.image_pull_policy)
+        .collect();
+    assert_eq!(
+        vec![
+            Some("Always".to_string()),
+            Some("Never".to_string()),
+            Some("IfNotPresent".to_string()),
+        ],
+        policies
+    );
+
+    let mut invalid = component.clone();
+    invalid.containers[0].image_pull_policy = Some("Sometimes".into());
+    let err = invalid.validate().expect_err("unknown pull policy");
+    assert!(err.to_string().contains("image pull policy Sometimes"));
+}
+
+#[test]
+fn test_validate_gpu_with_limits() {
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "trainer",
+                    "image": "trainer:1.0",
+                    "resources": {"gpu": {"required": 2, "mirrorRequests": true}}
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+    assert!(component.validate_gpu_with_limits().is_ok());
+    assert!(component.validate().is_ok());
+
+    let with_gpu = |required: f64| {
+        let mut c = component.clone();
+        c.containers[0].resources.as_mut().expect("resources").gpu = Some(GPU {
+            required,
+            mirror_requests: false,
+        });
+        c
+    };
+    assert!(with_gpu(0.0).validate_gpu_with_limits().is_ok());
+
+    // A GPU count that cannot be rendered leaves the container without a GPU limit.
+    for required in &[std::f64::INFINITY, std::f64::NAN, -1.0] {
+        let err = with_gpu(*required)
+            .validate_gpu_with_limits()
+            .expect_err("GPU without a limit");
+        assert!(err.to_string().contains("no nvidia.com/gpu limit"));
+        assert!(with_gpu(*required).validate().is_err());
+    }
+}
+
+#[test]
+fn test_from_value() {
+    let value = serde_json::json!({
+        "workloadType": "core.oam.dev/v1alpha1.Server",
+        "containers": [
+            {
+                "name": "web",
+                "image": "nginx:latest",
+                "ports": [{"name": "http", "containerPort": 80}]
+            }
+        ]
+    });
+    let component = Component::from_value(value.clone()).expect("component must parse");
+    assert_eq!(
+        Component::from_str(value.to_string().as_str()).expect("component must parse"),
+        component
+    );
+    assert_eq!("core.oam.dev/v1alpha1.Server", component.workload_type);
+    assert_eq!(
+        80,
+        component.containers[0].ports[0]
+            .resolve_port(&BTreeMap::new())
+            .expect("port")
+    );
+
+    assert!(Component::from_value(serde_json::json!({ "containers": "web" })).is_err());
+}
+
+#[test]
+fn test_env_resolve_precedence() {
+    let field_ref = EnvSource {
+        field_ref: Some(FieldRef {
+            field_path: "status.podIP".into(),
+        }),
+        resource_field_ref: None,
+    };
+    let mut params = BTreeMap::new();
+    params.insert("host".to_string(), serde_json::json!("db.example.com"));
+    params.insert("port".to_string(), serde_json::json!(5432));
+
+    // Each case sets some of valueFrom, fromParam and value, and expects the rendered
+    // value, whether valueFrom is rendered, and how many warnings are reported.
+    let cases = vec![
+        (None, None, None, None, false, 0),
+        (None, None, Some("fallback"), Some("fallback"), false, 0),
+        (None, Some("host"), None, Some("db.example.com"), false, 0),
+        (
+            None,
+            Some("host"),
+            Some("fallback"),
+            Some("db.example.com"),
+            false,
+            0,
+        ),
+        (None, Some("missing"), None, None, false, 1),
+        (
+            None,
+            Some("missing"),
+            Some("fallback"),
+            Some("fallback"),
+            false,
+            1,
+        ),
+        (Some(&field_ref), None, None, None, true, 0),
+        (Some(&field_ref), None, Some("fallback"), None, true, 1),
+        (Some(&field_ref), Some("host"), None, None, true, 1),
+        (
+            Some(&field_ref),
+            Some("host"),
+            Some("fallback"),
+            None,
+            true,
+            2,
+        ),
+    ];
+    for (value_from, from_param, value, expected, rendered_from, warnings) in cases {
+        let env = Env {
+            name: "DB_HOST".into(),
+            value: value.map(String::from),
+            from_param: from_param.map(String::from),
+            value_from: value_from.cloned(),
+        };
+        let mut report = ConversionReport::default();
+        let var = env.resolve(&params, &mut report).expect("resolve");
+        let case = format!("{:?} {:?} {:?}", value_from, from_param, value);
+        assert_eq!(expected.map(String::from), var.value, "{}", case);
+        assert_eq!(rendered_from, var.value_from.is_some(), "{}", case);
+        assert_eq!(warnings, report.warnings.len(), "{}", case);
+    }
+
+    // Parameters that are not strings are rendered as JSON.
+    let port = Env {
+        name: "DB_PORT".into(),
+        value: None,
+        from_param: Some("port".into()),
+        value_from: None,
+    };
+    let var = port
+        .resolve(&params, &mut ConversionReport::default())
+        .expect("resolve");
+    assert_eq!(Some("5432".to_string()), var.value);
+
+    let mut report = ConversionReport::default();
+    let overridden = Env {
+        name: "POD_IP".into(),
+        value: Some("127.0.0.1".into()),
+        from_param: None,
+        value_from: Some(field_ref.clone()),
+    };
+    overridden.resolve(&params, &mut report).expect("resolve");
+    assert!(report.warnings[0].contains("overrides its value"));
+
+    let invalid = Env {
+        name: "POD_IP".into(),
+        value: None,
+        from_param: None,
+        value_from: Some(EnvSource {
+            field_ref: None,
+            resource_field_ref: None,
+        }),
+    };
+    assert!(invalid
+        .resolve(&params, &mut ConversionReport::default())
+        .is_err());
+}
+
+#[test]
+fn test_scale_to_zero() {
+    let mut worker = Component {
+        workload_type: "core.oam.dev/v1alpha1.Worker".into(),
+        containers: vec![Container {
+            name: "queue".into(),
+            image: "worker:latest".into(),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    worker.scale(0).expect("scale a worker to zero");
+    assert!(worker.validate().is_ok());
+    let objects = render_all(&worker, "queue", BTreeMap::new()).expect("render");
+    assert_eq!("Deployment", objects[0]["kind"]);
+    assert_eq!(0, objects[0]["spec"]["replicas"]);
+
+    let mut singleton = Component {
+        workload_type: "core.oam.dev/v1alpha1.SingletonWorker".into(),
+        ..worker.clone()
+    };
+    singleton.workload_settings.clear();
+    let err = singleton
+        .scale(0)
+        .expect_err("a singleton cannot be scaled to zero");
+    assert!(err.to_string().contains("SingletonWorker"));
+    assert!(singleton.workload_settings.is_empty());
+}
+
+#[test]
+fn test_apply_traits_order() {
+    let component = Component {
+        workload_type: "core.oam.dev/v1alpha1.Server".into(),
+        containers: vec![Container {
+            name: "web".into(),
+            image: "nginx:latest".into(),
+            ports: vec![Port::basic("http".into(), 80)],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let binding = |name: &str, properties: serde_json::Value| TraitBinding {
+        name: name.into(),
+        parameter_values: None,
+        properties: Some(properties),
+    };
+    let ingress = binding(
+        "ingress",
+        serde_json::json!({ "hostname": "web.example.com" }),
+    );
+    let scaler = binding("manual-scaler", serde_json::json!({ "replicaCount": 3 }));
+    let monitor = binding("service-monitor", serde_json::json!({ "port": "http" }));
+
+    let render = |traits: Vec<TraitBinding>| {
+        let mut objects = render_all(&component, "web", BTreeMap::new()).expect("render");
+        component
+            .apply_traits(&mut objects, &traits)
+            .expect("apply traits");
+        objects
+    };
+    let ordered = render(vec![scaler.clone(), ingress.clone(), monitor.clone()]);
+    let shuffled = render(vec![monitor.clone(), ingress.clone(), scaler.clone()]);
+    assert_eq!(ordered, shuffled);
+    assert_eq!(render(vec![ingress, monitor, scaler]), ordered);
+
+    assert_eq!(3, ordered[0]["spec"]["replicas"]);
+    let kinds: Vec<&str> = ordered
+        .iter()
+        .skip(ordered.len() - 2)
+        .map(|o| o["kind"].as_str().expect("kind"))
+        .collect();
+    assert_eq!(vec!["Ingress", "ServiceMonitor"], kinds);
+}
+
+#[test]
+fn test_env_references() {
+    assert_eq!(
+        vec!["HOST", "PORT"],
+        env_references("http://$(HOST):$(PORT)/")
+    );
+    assert!(env_references("price: $$(PRICE) and $$5").is_empty());
+    assert_eq!(vec!["PRICE"], env_references("$$$(PRICE)"));
+    assert!(env_references("$(UNCLOSED").is_empty());
+    assert!(env_references("$5 and $").is_empty());
+
+    let component = Component::from_str(
+        r#"{
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "env": [
+                        {"name": "HOST", "value": "db.example.com"},
+                        {"name": "URL", "value": "postgres://$(HOST):$(PORT)/"},
+                        {"name": "PORT", "value": "5432"},
+                        {"name": "PRICE", "value": "$$(HOST) costs $$5"}
+                    ]
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+
+    // Values are rendered as they are written; Kubernetes expands and unescapes them.
+    let (pod_spec, report) = component
+        .to_pod_spec_with_report(BTreeMap::new())
+        .expect("pod spec");
+    let env = pod_spec.containers[0].env.clone().expect("env");
+    assert_eq!(
+        Some("postgres://$(HOST):$(PORT)/".to_string()),
+        env[1].value
+    );
+    assert_eq!(Some("$$(HOST) costs $$5".to_string()), env[3].value);
+
+    // PORT is declared after URL, so Kubernetes does not expand it there.
+    assert_eq!(1, report.warnings.len());
+    assert!(report.warnings[0].contains("URL refers to $(PORT)"));
+}
+
+#[test]
+fn test_validate_ports_duplicates() {
+    let component = |ports: Vec<Port>| Component {
+        containers: vec![Container {
+            name: "dns".into(),
+            image: "coredns/coredns:1.6.7".into(),
+            ports,
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let udp = |name: &str, port: i32| Port {
+        protocol: PortProtocol::UDP,
+        ..Port::basic(name.into(), port)
+    };
+
+    // One port number may be declared for each protocol.
+    let dns = component(vec![Port::basic("dns-tcp".into(), 53), udp("dns", 53)]);
+    assert!(dns.validate_ports(BTreeMap::new()).is_ok());
+
+    let duplicate = component(vec![
+        Port::basic("dns".into(), 53),
+        Port::basic("alt".into(), 53),
+    ]);
+    let err = duplicate
+        .validate_ports(BTreeMap::new())
+        .expect_err("duplicate port");
+    assert!(err
+        .to_string()
+        .contains("declares port 53/TCP more than once"));
+
+    let mut overlapping = Port::basic("range".into(), 0);
+    overlapping.container_port_range = Some(PortRange { start: 50, end: 60 });
+    let err = component(vec![Port::basic("dns".into(), 53), overlapping])
+        .validate_ports(BTreeMap::new())
+        .expect_err("range overlaps a port");
+    assert!(err.to_string().contains("53/TCP"));
+
+    // Sidecar ports are checked too.
+    let sidecar = Component {
+        sidecars: vec![Container {
+            name: "proxy".into(),
+            image: "envoyproxy/envoy:v1.12.0".into(),
+            ports: vec![
+                Port::basic("http".into(), 80),
+                Port::basic("alt".into(), 80),
+            ],
+            ..Default::default()
+        }],
+        ..component(vec![])
+    };
+    let err = sidecar
+        .validate_ports(BTreeMap::new())
+        .expect_err("duplicate sidecar port");
+    assert!(err.to_string().contains("container proxy"));
+}
+
+#[test]
+fn test_validate_port_count() {
+    let component = |count: i32| Component {
+        containers: vec![Container {
+            name: "web".into(),
+            image: "nginx:latest".into(),
+            ports: (0..count)
+                .map(|i| Port::basic(format!("port-{}", i), 8000 + i))
+                .collect(),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    assert!(component(50).validate_port_count().is_empty());
+
+    let warnings = component(51).validate_port_count();
+    assert_eq!(1, warnings.len());
+    assert!(warnings[0].contains("declares 51 ports, more than 50"));
+}
+
+#[test]
+fn test_render_all_in_namespace() {
+    let component = Component::from_str(
+        r#"{
+            "workloadType": "core.oam.dev/v1alpha1.Server",
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "ports": [{"name": "http", "containerPort": 80}],
+                    "config": [{"path": "/etc/web/config.yaml", "value": "debug: true"}]
+                }
+            ]
+        }"#,
+    )
+    .expect("component must parse");
+
+    let objects =
+        render_all_in_namespace(&component, "web", BTreeMap::new(), "shop").expect("render");
+    let unscoped = render_all(&component, "web", BTreeMap::new()).expect("render");
+    assert_eq!(unscoped.len(), objects.len());
+    assert!(objects.len() >= 3);
+    for object in objects.iter() {
+        assert_eq!(
+            "shop", object["metadata"]["namespace"],
+            "{}",
+            object["kind"]
+        );
+    }
+
+    // Cluster-scoped objects, such as those a trait may create, take no namespace.
+    let mut objects = vec![
+        serde_json::json!({"kind": "Namespace", "metadata": {"name": "shop"}}),
+        serde_json::json!({"kind": "Ingress", "metadata": {"name": "web-trait-ingress"}}),
+    ];
+    set_namespace(&mut objects, "shop").expect("set namespace");
+    assert!(objects[0]["metadata"].get("namespace").is_none());
+    assert_eq!("shop", objects[1]["metadata"]["namespace"]);
+
+    let err = render_all_in_namespace(&component, "web", BTreeMap::new(), "Shop_1")
+        .expect_err("invalid namespace");
+    assert!(err.to_string().contains("invalid namespace"));
+    assert!(render_all_in_namespace(&component, "web", BTreeMap::new(), "").is_err());
+}
+
+#[test]
+fn test_pod_spec_diff() {
+    let component = Component {
+        containers: vec![Container {
+            name: "web".into(),
+            image: "nginx:1.17".into(),
+            ports: vec![Port::basic("http".into(), 80)],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let desired = component.to_pod_spec(BTreeMap::new()).expect("pod spec");
+    assert!(component
+        .pod_spec_matches(&desired, BTreeMap::new())
+        .expect("pod spec"));
+
+    // Fields Kubernetes defaults on the live spec do not count as differences.
+    let mut live = desired.clone();
+    live.dns_policy = Some("ClusterFirst".into());
+    live.scheduler_name = Some("default-scheduler".into());
+    live.containers[0].termination_message_path = Some("/dev/termination-log".into());
+    assert!(component
+        .pod_spec_matches(&live, BTreeMap::new())
+        .expect("pod spec"));
+    assert!(component
+        .pod_spec_diff(&live, BTreeMap::new())
+        .expect("pod spec diff")
+        .is_empty());
+
+    live.containers[0].image = Some("nginx:1.16".into());
+    assert!(!component
+        .pod_spec_matches(&live, BTreeMap::new())
+        .expect("pod spec"));
+    assert_eq!(
+        vec!["/containers/0/image".to_string()],
+        component
+            .pod_spec_diff(&live, BTreeMap::new())
+            .expect("pod spec diff")
+    );
+
+    // Quantities are compared by value, since the API server returns them canonicalized.
+    let mut canonical = desired.clone();
+    {
+        let requests = canonical.containers[0]
+            .resources
+            .as_mut()
+            .and_then(|r| r.requests.as_mut())
+            .expect("requests");
+        requests.insert("cpu".into(), Quantity("100m".into()));
+        requests.insert("memory".into(), Quantity("134217728".into()));
+    }
+    assert!(component
+        .pod_spec_diff(&canonical, BTreeMap::new())
+        .expect("pod spec diff")
+        .is_empty());
+    canonical.containers[0]
+        .resources
+        .as_mut()
+        .and_then(|r| r.requests.as_mut())
+        .expect("requests")
+        .insert("cpu".into(), Quantity("200m".into()));
+    assert_eq!(
+        vec!["/containers/0/resources/requests/cpu".to_string()],
+        component
+            .pod_spec_diff(&canonical, BTreeMap::new())
+            .expect("pod spec diff")
+    );
+
+    let mut extra = desired.clone();
+    extra.containers.push(desired.containers[0].clone());
+    assert_eq!(
+        vec!["/containers".to_string()],
+        component
+            .pod_spec_diff(&extra, BTreeMap::new())
+            .expect("pod spec diff")
+    );
+}
+
+#[test]
+fn test_validate_service_ports() {
+    let component = |ports: Vec<Vec<Port>>| Component {
+        containers: ports
+            .into_iter()
+            .enumerate()
+            .map(|(i, ports)| Container {
+                name: format!("c{}", i),
+                image: "nginx:1.17".into(),
+                ports,
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    let ok = component(vec![
+        vec![Port::basic("http".into(), 80)],
+        vec![Port::basic("metrics".into(), 9090)],
+    ]);
+    assert!(ok.validate_service_ports(BTreeMap::new()).is_ok());
+    // A single port does not need a name.
+    let single = component(vec![vec![Port::basic("".into(), 80)]]);
+    assert!(single.validate_service_ports(BTreeMap::new()).is_ok());
+
+    let duplicate_port = component(vec![
+        vec![Port::basic("http".into(), 80)],
+        vec![Port::basic("web".into(), 80)],
+    ]);
+    let err = duplicate_port
+        .validate_service_ports(BTreeMap::new())
+        .expect_err("duplicate port");
+    assert!(err.to_string().contains("80/TCP"));
+    // The same number is fine with another protocol.
+    let mut udp = Port::basic("dns-udp".into(), 53);
+    udp.protocol = PortProtocol::UDP;
+    let dns = component(vec![vec![Port::basic("dns-tcp".into(), 53)], vec![udp]]);
+    assert!(dns.validate_service_ports(BTreeMap::new()).is_ok());
+
+    let duplicate_name = component(vec![
+        vec![Port::basic("http".into(), 80)],
+        vec![Port::basic("http".into(), 8080)],
+    ]);
+    let err = duplicate_name
+        .validate_service_ports(BTreeMap::new())
+        .expect_err("duplicate name");
+    assert!(err.to_string().contains("port name http"));
+
+    let unnamed = component(vec![
+        vec![Port::basic("http".into(), 80)],
+        vec![Port::basic("".into(), 8080)],
+    ]);
+    let err = unnamed
+        .validate_service_ports(BTreeMap::new())
+        .expect_err("unnamed port");
+    assert!(err.to_string().contains("must be named"));
+    assert!(render_all(&unnamed, "web", BTreeMap::new()).is_err());
+}
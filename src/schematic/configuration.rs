@@ -18,6 +18,9 @@ pub struct ComponentConfiguration {
     pub traits: Option<Vec<TraitBinding>>,
     /// Application Scopes which the component was involved
     pub application_scopes: Option<Vec<String>>,
+    /// The names of the instances that must be created before this one
+    #[serde(default)]
+    pub depends_on: Vec<String>,
 }
 
 /// ApplicationConfiguration is the top-level configuration object in OAM.
@@ -1,4 +1,5 @@
 use crate::schematic::configuration::*;
+use crate::schematic::Application;
 
 #[test]
 fn test_component_configuration() {
@@ -41,4 +42,52 @@ fn test_application_configuration() {
     .expect("JSON must parse");
 
     assert!(conf.variables.is_some());
-}
\ No newline at end of file
+}
+#[test]
+fn test_render_order() {
+    let instance = |name: &str, depends_on: &[&str]| ComponentConfiguration {
+        component_name: "web".into(),
+        instance_name: name.into(),
+        parameter_values: None,
+        traits: None,
+        application_scopes: None,
+        depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+    };
+
+    // Listed in reverse, a chain is ordered from its first dependency.
+    let app = Application {
+        components: vec![
+            instance("frontend", &["api"]),
+            instance("api", &["db"]),
+            instance("db", &[]),
+            instance("cache", &[]),
+        ],
+    };
+    assert_eq!(
+        vec!["db", "api", "frontend", "cache"],
+        app.render_order().expect("render order")
+    );
+
+    let cyclic = Application {
+        components: vec![
+            instance("db", &[]),
+            instance("frontend", &["api"]),
+            instance("api", &["worker", "db"]),
+            instance("worker", &["api"]),
+        ],
+    };
+    let err = cyclic.render_order().expect_err("dependency cycle");
+    assert!(err.to_string().contains("api -> worker -> api"));
+    assert!(!err.to_string().contains("frontend ->"));
+
+    let unknown = Application {
+        components: vec![instance("api", &["db"])],
+    };
+    assert!(unknown.render_order().is_err());
+
+    let conf: ComponentConfiguration = serde_json::from_str(
+        r#"{"componentName": "web", "instanceName": "api", "dependsOn": ["db"]}"#,
+    )
+    .expect("JSON must parse");
+    assert_eq!(vec!["db"], conf.depends_on);
+}
@@ -0,0 +1,141 @@
+use failure::Error;
+use serde_json::{Map, Value};
+
+use crate::schematic::component::DEFAULT_WORKLOAD_TYPE;
+use crate::workload_type::{
+    OAM_API_VERSION, REPLICABLE_TASK_NAME, SCHEDULED_TASK_NAME, SERVER_NAME, SINGLETON_SERVER_NAME,
+    SINGLETON_TASK_NAME, SINGLETON_WORKER, STATEFUL_SERVICE_NAME, TASK_NAME, WORKER_NAME,
+};
+
+/// The API version used by components written against the Hydra spec.
+const HYDRA_API_VERSION: &str = "core.hydra.io/v1alpha1";
+
+/// Workload type kinds that were renamed, and their current names.
+const WORKLOAD_TYPE_RENAMES: &[(&str, &str)] = &[
+    ("Service", "Server"),
+    ("ReplicatedService", "Server"),
+    ("SingletonService", "SingletonServer"),
+    ("ReplicatedTask", "Task"),
+    ("ReplicatedWorker", "Worker"),
+];
+
+/// Component fields that were renamed, and their current names.
+const COMPONENT_FIELD_RENAMES: &[(&str, &str)] =
+    &[("os", "osType"), ("settings", "workloadSettings")];
+
+/// Port fields that were renamed, and their current names.
+const PORT_FIELD_RENAMES: &[(&str, &str)] = &[("port", "containerPort")];
+
+/// Migrate a v1alpha1 component to the current schema.
+///
+/// The value may be a bare component spec or a whole ComponentSchematic manifest.
+/// Deprecated field names and workload type names are rewritten so that the result
+/// deserializes into a `Component`. Values that are already current pass through
+/// unchanged.
+pub fn migrate_v1alpha1(value: Value) -> Result<Value, Error> {
+    let mut value = value;
+    let object = value.as_object_mut().ok_or_else(|| {
+        format_err!("cannot migrate component: expected an object holding a component spec")
+    })?;
+    if object.contains_key("kind") || object.contains_key("apiVersion") {
+        if let Some(api_version) = object.get_mut("apiVersion") {
+            if *api_version == HYDRA_API_VERSION {
+                *api_version = Value::String(OAM_API_VERSION.to_string());
+            }
+        }
+        let spec = object.get_mut("spec").ok_or_else(|| {
+            format_err!("cannot migrate component: the manifest has no spec to migrate")
+        })?;
+        migrate_spec(spec)?;
+    } else {
+        migrate_spec(&mut value)?;
+    }
+    Ok(value)
+}
+
+fn migrate_spec(spec: &mut Value) -> Result<(), Error> {
+    let spec = spec
+        .as_object_mut()
+        .ok_or_else(|| format_err!("cannot migrate component: spec must be an object"))?;
+    rename_fields(spec, COMPONENT_FIELD_RENAMES)?;
+    if let Some(workload_type) = spec.get_mut("workloadType") {
+        if let Some(current) = workload_type.as_str().map(migrate_workload_type) {
+            *workload_type = Value::String(current?);
+        }
+    }
+    if let Some(containers) = spec.get_mut("containers").and_then(|c| c.as_array_mut()) {
+        for container in containers.iter_mut() {
+            let ports = container
+                .get_mut("ports")
+                .and_then(|p| p.as_array_mut())
+                .into_iter()
+                .flatten();
+            for port in ports {
+                if let Some(port) = port.as_object_mut() {
+                    rename_fields(port, PORT_FIELD_RENAMES)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Move each deprecated field to its current name.
+///
+/// It is an error for an object to carry both names, since it is unclear which wins.
+fn rename_fields(object: &mut Map<String, Value>, renames: &[(&str, &str)]) -> Result<(), Error> {
+    for (old, new) in renames.iter() {
+        if let Some(value) = object.remove(*old) {
+            if object.contains_key(*new) {
+                return Err(format_err!(
+                    "cannot migrate component: both {} and {} are set; remove the deprecated {}",
+                    old,
+                    new,
+                    old
+                ));
+            }
+            object.insert(new.to_string(), value);
+        }
+    }
+    Ok(())
+}
+
+fn migrate_workload_type(workload_type: &str) -> Result<String, Error> {
+    let (group, kind) = match workload_type
+        .rsplitn(2, '.')
+        .collect::<Vec<&str>>()
+        .as_slice()
+    {
+        [kind, group] => (group.to_string(), kind.to_string()),
+        _ => return Ok(workload_type.to_string()),
+    };
+    // Extended workload types belong to other groups and are left alone.
+    if group != HYDRA_API_VERSION && group != OAM_API_VERSION {
+        return Ok(workload_type.to_string());
+    }
+    let kind = WORKLOAD_TYPE_RENAMES
+        .iter()
+        .find(|(old, _)| *old == kind)
+        .map_or(kind.as_str(), |(_, new)| *new);
+    let current = format!("{}.{}", OAM_API_VERSION, kind);
+    let known = [
+        SERVER_NAME,
+        SINGLETON_SERVER_NAME,
+        STATEFUL_SERVICE_NAME,
+        TASK_NAME,
+        SINGLETON_TASK_NAME,
+        REPLICABLE_TASK_NAME,
+        SCHEDULED_TASK_NAME,
+        WORKER_NAME,
+        SINGLETON_WORKER,
+        DEFAULT_WORKLOAD_TYPE,
+    ];
+    if group == HYDRA_API_VERSION && !known.contains(&current.as_str()) {
+        return Err(format_err!(
+            "cannot migrate workload type {}: use one of {}",
+            workload_type,
+            known.join(", ")
+        ));
+    }
+    Ok(current)
+}
@@ -0,0 +1,96 @@
+use crate::schematic::{component::Component, migration::migrate_v1alpha1};
+
+#[test]
+fn test_migrate_v1alpha1_manifest() {
+    let old = serde_json::json!({
+        "apiVersion": "core.hydra.io/v1alpha1",
+        "kind": "ComponentSchematic",
+        "metadata": {
+            "name": "web"
+        },
+        "spec": {
+            "workloadType": "core.hydra.io/v1alpha1.ReplicatedService",
+            "os": "linux",
+            "settings": [
+                {
+                    "name": "headless",
+                    "type": "boolean",
+                    "value": false
+                }
+            ],
+            "containers": [
+                {
+                    "name": "web",
+                    "image": "nginx:latest",
+                    "ports": [
+                        {
+                            "name": "http",
+                            "port": 80
+                        }
+                    ]
+                }
+            ]
+        }
+    });
+
+    let migrated = migrate_v1alpha1(old).expect("manifest must migrate");
+    assert_eq!("core.oam.dev/v1alpha1", migrated["apiVersion"]);
+    let component: Component =
+        serde_json::from_value(migrated["spec"].clone()).expect("component must deserialize");
+    assert_eq!("core.oam.dev/v1alpha1.Server", component.workload_type);
+    assert_eq!(Some("linux".to_string()), component.os_type);
+    assert_eq!("headless", component.workload_settings[0].name);
+    assert_eq!(
+        80,
+        component.containers[0].ports[0]
+            .resolve_port(&Default::default())
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_migrate_v1alpha1_current_spec_unchanged() {
+    let current = serde_json::json!({
+        "workloadType": "core.oam.dev/v1alpha1.SingletonServer",
+        "osType": "linux",
+        "containers": []
+    });
+    assert_eq!(
+        current,
+        migrate_v1alpha1(current.clone()).expect("current spec must migrate")
+    );
+
+    let extended = serde_json::json!({
+        "workloadType": "openfaas.com/v1alpha2.Function",
+        "containers": []
+    });
+    assert_eq!(
+        extended,
+        migrate_v1alpha1(extended.clone()).expect("extended workload must migrate")
+    );
+}
+
+#[test]
+fn test_migrate_v1alpha1_errors() {
+    assert!(migrate_v1alpha1(serde_json::json!(["not", "a", "component"])).is_err());
+
+    let unknown = serde_json::json!({
+        "workloadType": "core.hydra.io/v1alpha1.Daemon",
+        "containers": []
+    });
+    let err = migrate_v1alpha1(unknown).expect_err("unknown workload types must fail");
+    assert!(err.to_string().contains("use one of"));
+
+    let conflicting = serde_json::json!({
+        "os": "linux",
+        "osType": "windows",
+        "containers": []
+    });
+    assert!(migrate_v1alpha1(conflicting).is_err());
+
+    let no_spec = serde_json::json!({
+        "kind": "ComponentSchematic",
+        "metadata": {"name": "web"}
+    });
+    assert!(migrate_v1alpha1(no_spec).is_err());
+}
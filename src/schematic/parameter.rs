@@ -8,7 +8,7 @@ pub type ParameterList = Vec<Parameter>;
 ///
 /// Parameters have primitive types, and may be marked as required. Default values
 /// may be provided as well.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Parameter {
     pub name: String,
@@ -25,39 +25,46 @@ pub struct Parameter {
 
 impl Parameter {
     fn validate(&self, val: &serde_json::Value) -> Result<(), Error> {
-        match self.parameter_type {
-            ParameterType::Boolean => val
-                .as_bool()
-                .ok_or_else(|| format_err!("expected boolean value for {}", self.name.as_str()))
-                .and(Ok(())),
-            ParameterType::String => val
-                .as_str()
-                .ok_or_else(|| format_err!("expected string value for {}", self.name.as_str()))
-                .and(Ok(())),
-            ParameterType::Number => {
-                // AFAIK, there is no numeric value in JSON that cannot be represented as an f64.
-                val.as_f64()
-                    .ok_or_else(|| format_err!("expected numeric value for {}", self.name.as_str()))
-                    .and(Ok(()))
-            }
-            ParameterType::Object => {
-                // support object here
-                val.as_object()
-                    .ok_or_else(|| format_err!("expected object value for {}", self.name.as_str()))
-                    .and(Ok(()))
+        self.parameter_type.validate(self.name.as_str(), val)
+    }
+
+    /// coerce parses a raw string, such as one from the command line or the environment,
+    /// into a value of this parameter's type.
+    ///
+    /// Strings are taken as they are. Objects, arrays and null are parsed as JSON.
+    pub fn coerce(&self, raw: &str) -> Result<serde_json::Value, Error> {
+        let invalid = || {
+            format_err!(
+                "cannot use {:?} for parameter {}: expected a {} value",
+                raw,
+                self.name,
+                self.parameter_type.json_schema_type()
+            )
+        };
+        let value = match self.parameter_type {
+            ParameterType::String => serde_json::Value::String(raw.to_string()),
+            ParameterType::Boolean => {
+                serde_json::Value::Bool(raw.trim().parse().map_err(|_| invalid())?)
             }
-            ParameterType::Array => {
-                // support array here
-                val.as_array()
-                    .ok_or_else(|| format_err!("expected array value for {}", self.name.as_str()))
-                    .and(Ok(()))
+            ParameterType::Number => {
+                let raw = raw.trim();
+                // Integers are kept as integers, so that they can be used as counts.
+                match raw.parse::<i64>() {
+                    Ok(i) => serde_json::Value::from(i),
+                    Err(_) => raw
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(serde_json::Number::from_f64)
+                        .map(serde_json::Value::Number)
+                        .ok_or_else(invalid)?,
+                }
             }
-            ParameterType::Null => {
-                // Not entirely clear what we want to do here.
-                val.as_null()
-                    .ok_or_else(|| format_err!("expected null value for {}", self.name.as_str()))
+            ParameterType::Null | ParameterType::Object | ParameterType::Array => {
+                serde_json::from_str(raw).map_err(|_| invalid())?
             }
-        }
+        };
+        self.validate(&value).map_err(|_| invalid())?;
+        Ok(value)
     }
 }
 
@@ -103,12 +110,48 @@ pub struct ValidationErrors {
     errs: Vec<Error>,
 }
 
+/// The result of resolving supplied values against parameter definitions.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResolvedParameters {
+    /// Values for every defined parameter.
+    pub values: ParamMap,
+    /// Supplied values that do not match any parameter definition.
+    ///
+    /// This is only populated when unknown parameters are preserved.
+    pub unknown: ParamMap,
+}
+
 pub fn resolve_parameters(
     definition: Vec<Parameter>,
     values: ParamMap,
 ) -> Result<ParamMap, ValidationErrors> {
+    resolve_parameters_with_unknown(definition, values, true).map(|r| r.values)
+}
+
+/// Resolve parameters, deciding what to do with supplied values that have no definition.
+///
+/// When `preserve_unknown` is true, unknown values are passed through in
+/// `ResolvedParameters::unknown` so that a component written for a newer version
+/// can still be deployed. When it is false, each unknown value is a validation error.
+pub fn resolve_parameters_with_unknown(
+    definition: Vec<Parameter>,
+    values: ParamMap,
+    preserve_unknown: bool,
+) -> Result<ResolvedParameters, ValidationErrors> {
     let mut errors: Vec<Error> = Vec::new();
     let mut resolved: ParamMap = BTreeMap::new();
+    let mut unknown: ParamMap = BTreeMap::new();
+
+    for (name, value) in values.iter() {
+        if definition.iter().any(|d| d.name == *name) {
+            continue;
+        }
+        if preserve_unknown {
+            unknown.insert(name.clone(), value.clone());
+        } else {
+            errors.push(format_err!("parameter {} is not defined", name));
+        }
+    }
 
     definition
         .iter()
@@ -142,7 +185,10 @@ pub fn resolve_parameters(
     if !errors.is_empty() {
         return Err(ValidationErrors { errs: errors });
     }
-    Ok(resolved)
+    Ok(ResolvedParameters {
+        values: resolved,
+        unknown,
+    })
 }
 
 /// Resolve current values with material from parent values and return a map of name/value pairs.
@@ -188,6 +234,61 @@ pub fn resolve_values(
     Ok(merged)
 }
 
+/// Check that every parameter definition is usable.
+///
+/// Names must be unique, and a default value must have the declared type, so an
+/// `object` default must be a JSON object and an `array` default a JSON array.
+pub fn validate_parameters(definition: &[Parameter]) -> Result<(), Error> {
+    for (i, p) in definition.iter().enumerate() {
+        if definition[..i].iter().any(|other| other.name == p.name) {
+            return Err(format_err!(
+                "parameter {} is defined more than once",
+                p.name
+            ));
+        }
+        if let Some(default) = p.default.as_ref() {
+            p.parameter_type
+                .validate(format!("default of parameter {}", p.name).as_str(), default)?;
+        }
+    }
+    Ok(())
+}
+
+/// Describe parameter definitions as a JSON Schema for the object of parameter values.
+///
+/// Each parameter becomes a property with its type, description and default, and
+/// required parameters are listed as required properties.
+pub fn parameters_json_schema(definition: &[Parameter]) -> serde_json::Value {
+    let mut properties = serde_json::Map::new();
+    for p in definition.iter() {
+        let mut property = serde_json::Map::new();
+        property.insert(
+            "type".to_string(),
+            serde_json::Value::from(p.parameter_type.json_schema_type()),
+        );
+        if let Some(description) = p.description.as_ref() {
+            property.insert(
+                "description".to_string(),
+                serde_json::Value::from(description.as_str()),
+            );
+        }
+        if let Some(default) = p.default.as_ref() {
+            property.insert("default".to_string(), default.clone());
+        }
+        properties.insert(p.name.clone(), serde_json::Value::Object(property));
+    }
+    let required: Vec<&str> = definition
+        .iter()
+        .filter(|p| p.required)
+        .map(|p| p.name.as_str())
+        .collect();
+    serde_json::json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
 /// Supplies the default value for all required fields.
 fn default_required() -> bool {
     false
@@ -207,6 +308,51 @@ pub enum ParameterType {
     Array,
 }
 
+impl ParameterType {
+    /// The JSON Schema type name of this parameter type.
+    pub fn json_schema_type(&self) -> &'static str {
+        match self {
+            ParameterType::Boolean => "boolean",
+            ParameterType::String => "string",
+            ParameterType::Number => "number",
+            ParameterType::Null => "null",
+            ParameterType::Object => "object",
+            ParameterType::Array => "array",
+        }
+    }
+
+    /// Check that a value named `name` has this type.
+    pub fn validate(&self, name: &str, val: &serde_json::Value) -> Result<(), Error> {
+        match self {
+            ParameterType::Boolean => val
+                .as_bool()
+                .ok_or_else(|| format_err!("expected boolean value for {}", name))
+                .and(Ok(())),
+            ParameterType::String => val
+                .as_str()
+                .ok_or_else(|| format_err!("expected string value for {}", name))
+                .and(Ok(())),
+            ParameterType::Number => {
+                // AFAIK, there is no numeric value in JSON that cannot be represented as an f64.
+                val.as_f64()
+                    .ok_or_else(|| format_err!("expected numeric value for {}", name))
+                    .and(Ok(()))
+            }
+            ParameterType::Object => val
+                .as_object()
+                .ok_or_else(|| format_err!("expected object value for {}", name))
+                .and(Ok(())),
+            ParameterType::Array => val
+                .as_array()
+                .ok_or_else(|| format_err!("expected array value for {}", name))
+                .and(Ok(())),
+            ParameterType::Null => val
+                .as_null()
+                .ok_or_else(|| format_err!("expected null value for {}", name)),
+        }
+    }
+}
+
 /// A value that is substituted into a parameter.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
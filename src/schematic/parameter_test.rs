@@ -64,6 +64,39 @@ fn test_resolve_parameters() {
     );
 }
 
+#[test]
+fn test_resolve_parameters_with_unknown() {
+    let params = vec![Parameter {
+        name: "email".into(),
+        description: None,
+        parameter_type: ParameterType::String,
+        required: true,
+        default: None,
+    }];
+    let mut vals: BTreeMap<String, serde_json::Value> = BTreeMap::new();
+    vals.insert("email".into(), json!("eliot@example.com"));
+    vals.insert("shoeSize".into(), json!(9));
+
+    // Lenient mode passes the unknown value through.
+    let res = resolve_parameters_with_unknown(params.clone(), vals.clone(), true)
+        .expect("unknown values are preserved");
+    assert_eq!(1, res.values.len());
+    assert_eq!(
+        json!("eliot@example.com"),
+        *res.values.get("email").expect("email should be set")
+    );
+    assert_eq!(1, res.unknown.len());
+    assert_eq!(
+        json!(9),
+        *res.unknown.get("shoeSize").expect("shoeSize kept")
+    );
+
+    // Strict mode rejects it.
+    let res = resolve_parameters_with_unknown(params.clone(), vals, false);
+    assert!(res.is_err());
+    assert!(res.unwrap_err().to_string().contains("shoeSize"));
+}
+
 #[test]
 fn test_resolve_values() {
     let parent = vec![
@@ -226,3 +259,147 @@ fn test_extract_params() {
     );
     assert_eq!(None, extract_string_params("d", params.clone()));
 }
+
+#[test]
+fn test_object_parameter() {
+    let params = vec![Parameter {
+        name: "database".into(),
+        description: Some("connection settings".into()),
+        parameter_type: ParameterType::Object,
+        required: false,
+        default: Some(json!({"host": "localhost", "port": 5432})),
+    }];
+    assert!(validate_parameters(&params).is_ok());
+    assert_eq!(
+        json!({
+            "type": "object",
+            "properties": {
+                "database": {
+                    "type": "object",
+                    "description": "connection settings",
+                    "default": {"host": "localhost", "port": 5432}
+                }
+            },
+            "required": []
+        }),
+        parameters_json_schema(&params)
+    );
+
+    let mut vals = BTreeMap::new();
+    vals.insert("database".to_string(), json!({"host": "db.example.com"}));
+    let res = resolve_parameters(params.clone(), vals).expect("object value");
+    assert_eq!(json!({"host": "db.example.com"}), res["database"]);
+
+    let mut bad_default = params.clone();
+    bad_default[0].default = Some(json!(["localhost"]));
+    assert!(validate_parameters(&bad_default).is_err());
+}
+
+#[test]
+fn test_array_parameter() {
+    let params = vec![
+        Parameter {
+            name: "hosts".into(),
+            description: None,
+            parameter_type: ParameterType::Array,
+            required: true,
+            default: Some(json!(["a.example.com", "b.example.com"])),
+        },
+        Parameter {
+            name: "replicas".into(),
+            description: None,
+            parameter_type: ParameterType::Number,
+            required: false,
+            default: None,
+        },
+    ];
+    assert!(validate_parameters(&params).is_ok());
+    assert_eq!(
+        json!({
+            "type": "object",
+            "properties": {
+                "hosts": {
+                    "type": "array",
+                    "default": ["a.example.com", "b.example.com"]
+                },
+                "replicas": {
+                    "type": "number"
+                }
+            },
+            "required": ["hosts"]
+        }),
+        parameters_json_schema(&params)
+    );
+
+    let mut vals = BTreeMap::new();
+    vals.insert("replicas".to_string(), json!(2));
+    vals.insert("hosts".to_string(), json!(["c.example.com"]));
+    let res = resolve_parameters(params.clone(), vals.clone()).expect("array value");
+    assert_eq!(json!(["c.example.com"]), res["hosts"]);
+    vals.insert("hosts".to_string(), json!("a.example.com"));
+    assert!(resolve_parameters(params.clone(), vals).is_err());
+
+    let mut bad_default = params.clone();
+    bad_default[0].default = Some(json!("a.example.com"));
+    assert!(validate_parameters(&bad_default).is_err());
+
+    let mut duplicated = params.clone();
+    duplicated[1].name = "hosts".into();
+    assert!(validate_parameters(&duplicated).is_err());
+}
+
+#[test]
+fn test_parameter_coerce() {
+    let param = |parameter_type: ParameterType| Parameter {
+        name: "p".into(),
+        description: None,
+        parameter_type,
+        required: false,
+        default: None,
+    };
+
+    assert_eq!(
+        json!("42"),
+        param(ParameterType::String).coerce("42").unwrap()
+    );
+    assert_eq!(
+        json!(true),
+        param(ParameterType::Boolean).coerce("true").unwrap()
+    );
+    assert_eq!(
+        json!(false),
+        param(ParameterType::Boolean).coerce("false").unwrap()
+    );
+    assert_eq!(
+        json!(42),
+        param(ParameterType::Number).coerce("42").unwrap()
+    );
+    assert_eq!(
+        Some(42),
+        param(ParameterType::Number).coerce("42").unwrap().as_i64()
+    );
+    assert_eq!(
+        json!(0.5),
+        param(ParameterType::Number).coerce("0.5").unwrap()
+    );
+    assert_eq!(
+        json!({ "a": 1 }),
+        param(ParameterType::Object).coerce(r#"{"a": 1}"#).unwrap()
+    );
+    assert_eq!(
+        json!(["a", "b"]),
+        param(ParameterType::Array).coerce(r#"["a", "b"]"#).unwrap()
+    );
+    assert_eq!(
+        json!(null),
+        param(ParameterType::Null).coerce("null").unwrap()
+    );
+
+    let err = param(ParameterType::Number)
+        .coerce("forty-two")
+        .expect_err("not a number");
+    assert!(err.to_string().contains("expected a number value"));
+    assert!(param(ParameterType::Number).coerce("NaN").is_err());
+    assert!(param(ParameterType::Boolean).coerce("yes").is_err());
+    assert!(param(ParameterType::Array).coerce(r#"{"a": 1}"#).is_err());
+}
@@ -0,0 +1,90 @@
+use failure::Error;
+use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+
+/// Quantity suffixes and their multipliers.
+///
+/// Binary suffixes are listed first so that "Mi" is matched before "M"; "m" and
+/// "M" are distinct (milli and mega).
+const SUFFIXES: &[(&str, f64)] = &[
+    ("Ki", 1024.0),
+    ("Mi", 1_048_576.0),
+    ("Gi", 1_073_741_824.0),
+    ("Ti", 1_099_511_627_776.0),
+    ("Pi", 1_125_899_906_842_624.0),
+    ("Ei", 1_152_921_504_606_846_976.0),
+    ("n", 1e-9),
+    ("u", 1e-6),
+    ("m", 1e-3),
+    ("k", 1e3),
+    ("M", 1e6),
+    ("G", 1e9),
+    ("T", 1e12),
+    ("P", 1e15),
+    ("E", 1e18),
+];
+
+/// The number of bytes in a mebibyte, the unit of a bare memory requirement.
+pub const MEBIBYTE: f64 = 1_048_576.0;
+
+/// Parse a Kubernetes quantity string (e.g. "500m", "1.5", "2Gi") into its base value.
+pub fn parse_quantity(quantity: &str) -> Result<f64, Error> {
+    let trimmed = quantity.trim();
+    let (number, multiplier) = SUFFIXES
+        .iter()
+        .find(|(suffix, _)| trimmed.ends_with(suffix))
+        .map(|(suffix, mult)| (&trimmed[..trimmed.len() - suffix.len()], *mult))
+        .unwrap_or((trimmed, 1.0));
+    number
+        .parse::<f64>()
+        .map(|n| n * multiplier)
+        .map_err(|_| format_err!("invalid quantity {:?}", quantity))
+}
+
+/// Parse a memory requirement into bytes.
+///
+/// Following the component schematic, a bare number is a count of mebibytes.
+pub fn parse_memory(memory: &str) -> Result<f64, Error> {
+    match memory.trim().parse::<f64>() {
+        Ok(mib) => Ok(mib * MEBIBYTE),
+        Err(_) => parse_quantity(memory),
+    }
+}
+
+/// Format a number of CPU cores as a millicore quantity.
+pub fn cpu_quantity(cores: f64) -> Quantity {
+    Quantity(format!("{}m", (cores * 1000.0).round() as i64))
+}
+
+/// Format a number of bytes as a memory quantity, using Mi when it divides evenly.
+pub fn memory_quantity(bytes: f64) -> Quantity {
+    let bytes = bytes.round() as i64;
+    if bytes % (MEBIBYTE as i64) == 0 {
+        return Quantity(format!("{}Mi", bytes / MEBIBYTE as i64));
+    }
+    Quantity(bytes.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schematic::quantity::*;
+
+    #[test]
+    fn test_parse_quantity() {
+        assert_eq!(0.5, parse_quantity("500m").expect("millicpu"));
+        assert_eq!(2.0, parse_quantity("2").expect("plain"));
+        assert_eq!(1_073_741_824.0, parse_quantity("1Gi").expect("Gi"));
+        assert_eq!(2_000_000.0, parse_quantity("2M").expect("M"));
+        assert_eq!(1000.0, parse_quantity("1e3").expect("exponent"));
+        assert!(parse_quantity("lots").is_err());
+    }
+
+    #[test]
+    fn test_format_quantity() {
+        assert_eq!(Quantity("1500m".into()), cpu_quantity(1.5));
+        assert_eq!(
+            Quantity("1536Mi".into()),
+            memory_quantity(1536.0 * MEBIBYTE)
+        );
+        assert_eq!(Quantity("1000".into()), memory_quantity(1000.0));
+    }
+}
@@ -2,8 +2,10 @@
 pub mod health;
 pub use crate::schematic::scopes::health::Health;
 pub mod network;
+pub mod selector;
 use crate::schematic::configuration::ComponentConfiguration;
 pub use crate::schematic::scopes::network::Network;
+pub use crate::schematic::scopes::selector::LabelSelector;
 use failure::Error;
 use k8s_openapi::apimachinery::pkg::apis::meta::v1 as meta;
 
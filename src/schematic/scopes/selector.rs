@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+/// LabelSelector selects the components that belong to a scope by their labels.
+///
+/// It follows the Kubernetes label selector semantics: every entry in `matchLabels`
+/// and every requirement in `matchExpressions` must match. An empty selector
+/// matches everything.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelSelector {
+    pub match_labels: Option<BTreeMap<String, String>>,
+    pub match_expressions: Option<Vec<LabelSelectorRequirement>>,
+}
+
+impl LabelSelector {
+    /// Return true if the given labels satisfy this selector.
+    pub fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        let labels_match = self
+            .match_labels
+            .as_ref()
+            .map_or(true, |ml| ml.iter().all(|(k, v)| labels.get(k) == Some(v)));
+        let expressions_match = self
+            .match_expressions
+            .as_ref()
+            .map_or(true, |exprs| exprs.iter().all(|e| e.matches(labels)));
+        labels_match && expressions_match
+    }
+}
+
+/// A LabelSelectorRequirement is a single expression in a LabelSelector.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelSelectorRequirement {
+    pub key: String,
+    pub operator: SelectorOperator,
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+impl LabelSelectorRequirement {
+    fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        let value = labels.get(&self.key);
+        match self.operator {
+            SelectorOperator::In => value.map_or(false, |v| self.values.contains(v)),
+            SelectorOperator::NotIn => value.map_or(true, |v| !self.values.contains(v)),
+            SelectorOperator::Exists => value.is_some(),
+            SelectorOperator::DoesNotExist => value.is_none(),
+        }
+    }
+}
+
+/// SelectorOperator is the relationship between a label's key and its values.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum SelectorOperator {
+    In,
+    NotIn,
+    Exists,
+    DoesNotExist,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::schematic::scopes::selector::*;
+
+    fn labels() -> BTreeMap<String, String> {
+        let mut labels = BTreeMap::new();
+        labels.insert("app.kubernetes.io/name".to_string(), "myapp".to_string());
+        labels.insert("oam.dev/workload-type".to_string(), "Service".to_string());
+        labels.insert("oam.dev/instance-name".to_string(), "web".to_string());
+        labels
+    }
+
+    #[test]
+    fn test_match_labels() {
+        let mut match_labels = BTreeMap::new();
+        match_labels.insert("app.kubernetes.io/name".to_string(), "myapp".to_string());
+        let selector = LabelSelector {
+            match_labels: Some(match_labels.clone()),
+            match_expressions: None,
+        };
+        assert!(selector.matches(&labels()));
+
+        match_labels.insert("oam.dev/instance-name".to_string(), "db".to_string());
+        let selector = LabelSelector {
+            match_labels: Some(match_labels),
+            match_expressions: None,
+        };
+        assert!(!selector.matches(&labels()));
+
+        assert!(LabelSelector::default().matches(&labels()));
+    }
+
+    #[test]
+    fn test_match_expressions() {
+        let selector: LabelSelector = serde_json::from_str(
+            r#"{
+                "matchExpressions": [
+                    {
+                        "key": "oam.dev/workload-type",
+                        "operator": "In",
+                        "values": ["Service", "SingletonServer"]
+                    },
+                    {
+                        "key": "oam.dev/role",
+                        "operator": "DoesNotExist"
+                    }
+                ]
+            }"#,
+        )
+        .expect("selector must parse");
+        assert!(selector.matches(&labels()));
+
+        let mut worker = labels();
+        worker.insert("oam.dev/workload-type".to_string(), "Worker".to_string());
+        assert!(!selector.matches(&worker));
+
+        let not_in = LabelSelector {
+            match_labels: None,
+            match_expressions: Some(vec![
+                LabelSelectorRequirement {
+                    key: "oam.dev/instance-name".into(),
+                    operator: SelectorOperator::NotIn,
+                    values: vec!["web".into()],
+                },
+                LabelSelectorRequirement {
+                    key: "app.kubernetes.io/name".into(),
+                    operator: SelectorOperator::Exists,
+                    values: vec![],
+                },
+            ]),
+        };
+        assert!(!not_in.matches(&labels()));
+    }
+}
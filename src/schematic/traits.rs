@@ -1,4 +1,5 @@
 use crate::lifecycle::Phase;
+use crate::schematic::component::Capability;
 use crate::schematic::parameter::ParameterValue;
 use kube::client::APIClient;
 use log::info;
@@ -12,6 +13,8 @@ mod empty;
 pub use crate::schematic::traits::empty::Empty;
 mod manual_scaler;
 pub use crate::schematic::traits::manual_scaler::ManualScaler;
+mod service_monitor;
+pub use crate::schematic::traits::service_monitor::ServiceMonitor;
 mod volume_mounter;
 pub use crate::schematic::traits::volume_mounter::VolumeMounter;
 mod util;
@@ -24,13 +27,54 @@ mod autoscaler_test;
 mod manual_scaler_test;
 #[cfg(test)]
 mod ingress_test;
+#[cfg(test)]
+mod service_monitor_test;
 
 pub const INGRESS_V1ALPHA1: &str = "ingress";
 pub const AUTOSCALER_V1ALPHA1: &str = "auto-scaler";
 pub const MANUAL_SCALER_V1ALPHA1: &str = "manual-scaler";
 pub const VOLUME_MOUNTER_V1ALPHA1: &str = "volume-mounter";
+pub const SERVICE_MONITOR_V1ALPHA1: &str = "service-monitor";
 pub const EMPTY: &str = "empty";
 
+/// The names of the traits Rudr knows how to apply.
+pub const TRAIT_NAMES: &[&str] = &[
+    MANUAL_SCALER_V1ALPHA1,
+    AUTOSCALER_V1ALPHA1,
+    INGRESS_V1ALPHA1,
+    VOLUME_MOUNTER_V1ALPHA1,
+    SERVICE_MONITOR_V1ALPHA1,
+    EMPTY,
+];
+
+/// The order traits are applied in, whatever order they are bound in: scalers, then
+/// networking, then observability, then storage.
+///
+/// Scalers change the workload the others may read, and a fixed order keeps the objects
+/// traits create in the same order from one render to the next. Unknown traits go last.
+pub const TRAIT_APPLICATION_ORDER: &[&str] = &[
+    MANUAL_SCALER_V1ALPHA1,
+    AUTOSCALER_V1ALPHA1,
+    INGRESS_V1ALPHA1,
+    SERVICE_MONITOR_V1ALPHA1,
+    VOLUME_MOUNTER_V1ALPHA1,
+    EMPTY,
+];
+
+/// in_application_order returns the bindings in the order their traits are applied.
+///
+/// Bindings of the same trait keep the order they are bound in.
+pub fn in_application_order(bindings: &[TraitBinding]) -> Vec<&TraitBinding> {
+    let mut ordered: Vec<&TraitBinding> = bindings.iter().collect();
+    ordered.sort_by_key(|b| {
+        TRAIT_APPLICATION_ORDER
+            .iter()
+            .position(|name| *name == b.name.as_str())
+            .unwrap_or_else(|| TRAIT_APPLICATION_ORDER.len())
+    });
+    ordered
+}
+
 /// Trait describes OAM traits.
 ///
 /// OAM traits are ops-oriented "add-ons" that can be attached to Components of the appropriate workloadType.
@@ -50,6 +94,162 @@ pub struct TraitBinding {
     pub parameter_values: Option<Vec<ParameterValue>>,
     pub properties: Option<serde_json::Value>,
 }
+impl TraitBinding {
+    /// validate_properties checks the properties against the schema of the bound trait.
+    ///
+    /// Required properties must be present, every property must have the type the trait
+    /// reads it as, and unknown properties are an error, so that a misspelled property
+    /// is not silently ignored. Traits without a schema are not checked here.
+    pub fn validate_properties(&self) -> Result<(), failure::Error> {
+        let schema = match property_schema(self.name.as_str()) {
+            Some(schema) => schema,
+            None => return Ok(()),
+        };
+        let empty = serde_json::Map::new();
+        let properties = match self.properties.as_ref() {
+            None | Some(serde_json::Value::Null) => &empty,
+            Some(serde_json::Value::Object(map)) => map,
+            Some(other) => {
+                return Err(format_err!(
+                    "properties of trait {} must be an object, got {}",
+                    self.name,
+                    other
+                ))
+            }
+        };
+        for (key, value) in properties.iter() {
+            let property = schema
+                .iter()
+                .find(|p| p.name == key.as_str())
+                .ok_or_else(|| {
+                    format_err!(
+                        "trait {} has no property {}; known properties are {}",
+                        self.name,
+                        key,
+                        schema
+                            .iter()
+                            .map(|p| p.name)
+                            .collect::<Vec<&str>>()
+                            .join(", ")
+                    )
+                })?;
+            if !property.property_type.matches(value) {
+                return Err(format_err!(
+                    "property {} of trait {} must be {}, got {}",
+                    key,
+                    self.name,
+                    property.property_type.describe(),
+                    value
+                ));
+            }
+        }
+        for property in schema.iter().filter(|p| p.required) {
+            if !properties.contains_key(property.name) {
+                return Err(format_err!(
+                    "trait {} requires property {}",
+                    self.name,
+                    property.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// required_capabilities lists the API versions of the objects the bound trait creates
+    /// that a cluster may not serve.
+    pub fn required_capabilities(&self) -> Vec<Capability> {
+        let api_version = match self.name.as_str() {
+            AUTOSCALER_V1ALPHA1 => "autoscaling/v2beta1",
+            INGRESS_V1ALPHA1 => "extensions/v1beta1",
+            SERVICE_MONITOR_V1ALPHA1 => service_monitor::SERVICE_MONITOR_API_VERSION,
+            _ => return vec![],
+        };
+        vec![Capability::ApiVersion(api_version.to_string())]
+    }
+}
+
+/// The JSON type of a trait property.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PropertyType {
+    String,
+    Integer,
+    Object,
+}
+impl PropertyType {
+    fn matches(self, value: &serde_json::Value) -> bool {
+        match self {
+            PropertyType::String => value.is_string(),
+            PropertyType::Integer => value.is_i64() || value.is_u64(),
+            PropertyType::Object => value.is_object(),
+        }
+    }
+    fn describe(self) -> &'static str {
+        match self {
+            PropertyType::String => "a string",
+            PropertyType::Integer => "an integer",
+            PropertyType::Object => "an object",
+        }
+    }
+}
+
+/// A property a trait reads from its binding.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PropertySchema {
+    pub name: &'static str,
+    pub property_type: PropertyType,
+    pub required: bool,
+}
+
+const fn property(
+    name: &'static str,
+    property_type: PropertyType,
+    required: bool,
+) -> PropertySchema {
+    PropertySchema {
+        name,
+        property_type,
+        required,
+    }
+}
+
+const MANUAL_SCALER_PROPERTIES: &[PropertySchema] =
+    &[property("replicaCount", PropertyType::Integer, true)];
+const AUTOSCALER_PROPERTIES: &[PropertySchema] = &[
+    property("minimum", PropertyType::Integer, false),
+    property("maximum", PropertyType::Integer, false),
+    property("cpu", PropertyType::Integer, false),
+    property("memory", PropertyType::Integer, false),
+];
+const INGRESS_PROPERTIES: &[PropertySchema] = &[
+    property("hostname", PropertyType::String, false),
+    property("path", PropertyType::String, false),
+    property("servicePort", PropertyType::Integer, false),
+    property("tlsHosts", PropertyType::String, false),
+    property("tlsSecretName", PropertyType::String, false),
+    property("annotations", PropertyType::Object, false),
+];
+const VOLUME_MOUNTER_PROPERTIES: &[PropertySchema] = &[
+    property("volumeName", PropertyType::String, true),
+    property("storageClass", PropertyType::String, false),
+];
+const SERVICE_MONITOR_PROPERTIES: &[PropertySchema] = &[
+    property("port", PropertyType::String, true),
+    property("interval", PropertyType::String, false),
+    property("path", PropertyType::String, false),
+];
+
+/// property_schema returns the properties a known trait accepts.
+pub fn property_schema(trait_name: &str) -> Option<&'static [PropertySchema]> {
+    match trait_name {
+        MANUAL_SCALER_V1ALPHA1 => Some(MANUAL_SCALER_PROPERTIES),
+        AUTOSCALER_V1ALPHA1 => Some(AUTOSCALER_PROPERTIES),
+        INGRESS_V1ALPHA1 => Some(INGRESS_PROPERTIES),
+        VOLUME_MOUNTER_V1ALPHA1 => Some(VOLUME_MOUNTER_PROPERTIES),
+        SERVICE_MONITOR_V1ALPHA1 => Some(SERVICE_MONITOR_PROPERTIES),
+        EMPTY => Some(&[]),
+        _ => None,
+    }
+}
 
 /// OAMTrait is an enumeration of the known traits.
 ///
@@ -61,6 +261,7 @@ pub enum OAMTrait {
     ManualScaler(ManualScaler),
     Ingress(Ingress),
     VolumeMounter(Box<VolumeMounter>),
+    ServiceMonitor(ServiceMonitor),
     Empty(Empty),
 }
 impl OAMTrait {
@@ -70,9 +271,37 @@ impl OAMTrait {
             OAMTrait::Ingress(i) => i.exec(ns, client, phase),
             OAMTrait::ManualScaler(m) => m.exec(ns, client, phase),
             OAMTrait::VolumeMounter(v) => v.exec(ns, client, phase),
+            OAMTrait::ServiceMonitor(s) => s.exec(ns, client, phase),
             OAMTrait::Empty(e) => e.exec(ns, client, phase),
         }
     }
+    /// Render the Kubernetes objects this trait creates, without a cluster.
+    ///
+    /// Traits that change the workload rather than create objects, such as the manual
+    /// scaler, update the rendered workload objects in place.
+    pub fn render(
+        &self,
+        workload: &mut [serde_json::Value],
+    ) -> Result<Vec<serde_json::Value>, failure::Error> {
+        match self {
+            OAMTrait::Autoscaler(a) => Ok(vec![serde_json::to_value(
+                a.to_horizontal_pod_autoscaler(),
+            )?]),
+            OAMTrait::Ingress(i) => Ok(vec![serde_json::to_value(i.to_ext_ingress())?]),
+            OAMTrait::VolumeMounter(v) => Ok(v
+                .to_pvc()
+                .map(serde_json::to_value)
+                .transpose()?
+                .into_iter()
+                .collect()),
+            OAMTrait::ManualScaler(m) => {
+                m.scale_objects(workload);
+                Ok(vec![])
+            }
+            OAMTrait::ServiceMonitor(s) => Ok(vec![s.to_service_monitor()]),
+            OAMTrait::Empty(_) => Ok(vec![]),
+        }
+    }
     pub fn status(&self, ns: &str, client: APIClient) -> Option<BTreeMap<String, String>> {
         match self {
             OAMTrait::Autoscaler(a) => a.status(ns, client),
@@ -80,6 +309,7 @@ impl OAMTrait {
             OAMTrait::ManualScaler(m) => m.status(ns, client),
             OAMTrait::Empty(e) => e.status(ns, client),
             OAMTrait::VolumeMounter(v) => v.status(ns, client),
+            OAMTrait::ServiceMonitor(s) => s.status(ns, client),
         }
     }
 }
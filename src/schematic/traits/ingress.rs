@@ -1,4 +1,5 @@
 use crate::schematic::traits::{util::*, TraitImplementation};
+use failure::Error;
 use k8s_openapi::api::extensions::v1beta1 as ext;
 use k8s_openapi::apimachinery::pkg::{apis::meta::v1 as meta, util::intstr::IntOrString};
 use kube::client::APIClient;
@@ -6,6 +7,9 @@ use log::warn;
 use serde_json::map::Map;
 use std::collections::BTreeMap;
 
+/// The annotation prefixes Rudr manages, which an Ingress trait may not set.
+pub const MANAGED_ANNOTATION_PREFIXES: &[&str] = &["oam.dev/", "rudr.io/"];
+
 /// An Ingress trait creates an ingress point to the workload type to which it is attached.
 ///
 /// In Kubernetes, this will create an Ingress and attach it to the Service of a particular
@@ -21,18 +25,27 @@ pub struct Ingress {
     pub owner_ref: OwnerRefs,
     pub tls_hosts: Option<Vec<String>>,
     pub tls_secret_name: Option<String>,
+    pub annotations: BTreeMap<String, String>,
 }
 impl Ingress {
+    /// Read the trait properties.
+    ///
+    /// The `annotations` property configures the ingress controller, such as an nginx
+    /// rewrite target. Its keys may not use a prefix Rudr manages.
+    ///
+    /// `tlsSecretName` and the comma-separated `tlsHosts` add a TLS block. The TLS hosts
+    /// must be among the hosts the Ingress routes.
     pub fn from_properties(
         name: String,
         instance_name: String,
         component_name: String,
         properties_map: Option<&Map<String, serde_json::value::Value>>,
         owner_ref: OwnerRefs,
-    ) -> Self {
+    ) -> Result<Self, Error> {
+        let annotations = read_annotations(instance_name.as_str(), properties_map)?;
         // Right now, we're relying on the higher level validation logic to validate types.
         let instancename = instance_name.clone();
-        Ingress {
+        let ingress = Ingress {
             name,
             instance_name,
             component_name,
@@ -45,10 +58,41 @@ impl Ingress {
             path: properties_map
                         .and_then(|map| map.get("path").map(|p| p.as_str().unwrap_or("").to_string())),
             tls_hosts: properties_map
-                        .and_then(|map| map.get("tlsHosts").map(|p| p.as_str().unwrap_or("").split(",").map(|p| p.to_string()).collect())),
+                        .and_then(|map| map.get("tlsHosts").map(|p| p.as_str().unwrap_or("").split(',').map(str::trim).filter(|h| !h.is_empty()).map(|h| h.to_string()).collect())),
             tls_secret_name: properties_map
                         .and_then(|map| map.get("tlsSecretName").map(|p| p.as_str().unwrap_or("").to_string())),
+            annotations,
+        };
+        ingress.validate_tls()?;
+        Ok(ingress)
+    }
+    /// The host the Ingress routes.
+    fn host(&self) -> String {
+        self.hostname
+            .clone()
+            .unwrap_or_else(|| "example.com".to_string())
+    }
+    fn validate_tls(&self) -> Result<(), Error> {
+        let tls_hosts = match self.tls_hosts.as_ref() {
+            Some(tls_hosts) => tls_hosts,
+            None => return Ok(()),
+        };
+        if self.tls_secret_name.is_none() {
+            return Err(format_err!(
+                "ingress trait for {}: tlsHosts requires a tlsSecretName",
+                self.instance_name
+            ));
+        }
+        let host = self.host();
+        if let Some(tls_host) = tls_hosts.iter().find(|h| **h != host) {
+            return Err(format_err!(
+                "ingress trait for {}: TLS host {} is not a routing host; the Ingress routes {}",
+                self.instance_name,
+                tls_host,
+                host
+            ));
         }
+        Ok(())
     }
     pub fn to_ext_ingress(&self) -> ext::Ingress {
         let labels = trait_labels(self.name.clone(), self.instance_name.clone());
@@ -57,15 +101,17 @@ impl Ingress {
                 //name: Some(format!("{}-trait-ingress", self.name.clone())),
                 name: Some(self.kube_name()),
                 labels: Some(labels),
+                annotations: if self.annotations.is_empty() {
+                    None
+                } else {
+                    Some(self.annotations.clone())
+                },
                 owner_references: self.owner_ref.clone(),
                 ..Default::default()
             }),
             spec: Some(ext::IngressSpec {
                 rules: Some(vec![ext::IngressRule {
-                    host: self
-                        .hostname
-                        .clone()
-                        .or_else(|| Some("example.com".to_string())),
+                    host: Some(self.host()),
                     http: Some(ext::HTTPIngressRuleValue {
                         paths: vec![ext::HTTPIngressPath {
                             backend: ext::IngressBackend {
@@ -92,6 +138,46 @@ impl Ingress {
         format!("{}-trait-ingress", self.instance_name)
     }
 }
+/// Read the `annotations` property, whose values must be strings.
+fn read_annotations(
+    instance_name: &str,
+    properties_map: Option<&Map<String, serde_json::value::Value>>,
+) -> Result<BTreeMap<String, String>, Error> {
+    let mut annotations = BTreeMap::new();
+    let properties = match properties_map.and_then(|map| map.get("annotations")) {
+        Some(properties) => properties,
+        None => return Ok(annotations),
+    };
+    let properties = properties.as_object().ok_or_else(|| {
+        format_err!(
+            "ingress trait for {}: annotations must be an object",
+            instance_name
+        )
+    })?;
+    for (key, value) in properties.iter() {
+        if let Some(prefix) = MANAGED_ANNOTATION_PREFIXES
+            .iter()
+            .find(|prefix| key.starts_with(*prefix))
+        {
+            return Err(format_err!(
+                "ingress trait for {}: annotation {} uses the prefix {}, which Rudr manages",
+                instance_name,
+                key,
+                prefix
+            ));
+        }
+        let value = value.as_str().ok_or_else(|| {
+            format_err!(
+                "ingress trait for {}: annotation {} must be a string",
+                instance_name,
+                key
+            )
+        })?;
+        annotations.insert(key.clone(), value.to_string());
+    }
+    Ok(annotations)
+}
+
 impl TraitImplementation for Ingress {
     fn add(&self, ns: &str, client: APIClient) -> TraitResult {
         let ingress = self.to_ext_ingress();
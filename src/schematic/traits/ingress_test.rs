@@ -1,7 +1,9 @@
 use crate::schematic::traits::*;
+use k8s_openapi::api::extensions::v1beta1 as ext;
 use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
 use serde_json::json;
 use serde_json::map::Map;
+use std::collections::BTreeMap;
 
 #[test]
 fn test_ingress_defaults() {
@@ -15,6 +17,7 @@ fn test_ingress_defaults() {
         owner_ref: None,
         tls_hosts: None,
         tls_secret_name: None,
+        annotations: BTreeMap::new(),
     };
 
     let king = ig.to_ext_ingress();
@@ -57,7 +60,7 @@ fn test_ingress_v1alpha1() {
             "hostname": "in.example.com",
             "path": "/path",
             "servicePort": 9999,
-            "tlsHosts": "x.example.com,y.example.com,z.example.com",
+            "tlsHosts": "in.example.com",
             "tlsSecretName": "my_secret",
         })),
     };
@@ -73,7 +76,8 @@ fn test_ingress_v1alpha1() {
         "patsy".into(),
         prop_map,
         None,
-    );
+    )
+    .expect("ingress");
 
     let king = ig.to_ext_ingress();
     assert_eq!(
@@ -120,10 +124,11 @@ fn test_ingress_v1alpha1() {
         .expect("one tls expected");
     assert_eq!("my_secret", tls.secret_name.as_ref().expect("secret expected"));
     let tls_hosts = tls.hosts.as_ref().expect("hosts are expected");
-    assert_eq!(3, tls_hosts.len());
-    assert_eq!("x.example.com", tls_hosts.get(0).expect("host is expected"));
-    assert_eq!("y.example.com", tls_hosts.get(1).expect("host is expected"));
-    assert_eq!("z.example.com", tls_hosts.get(2).expect("host is expected"));
+    assert_eq!(1, tls_hosts.len());
+    assert_eq!(
+        "in.example.com",
+        tls_hosts.get(0).expect("host is expected")
+    );
 }
 
 #[test]
@@ -148,7 +153,8 @@ fn test_ingress_v1alpha1_invalid_service_port() {
         "patsy".into(),
         prop_map,
         None,
-    );
+    )
+    .expect("ingress");
 
     let king = ig.to_ext_ingress();
     let spec = king.spec.expect("spec is required");
@@ -191,7 +197,8 @@ fn test_ingress_v1alpha_defaults() {
         "patsy".into(),
         prop_map,
         None,
-    );
+    )
+    .expect("ingress");
 
     let king = ig.to_ext_ingress();
     let rule = king
@@ -215,3 +222,126 @@ fn test_ingress_v1alpha_defaults() {
             .path
     );
 }
+
+fn ingress(properties: serde_json::Value) -> Result<Ingress, failure::Error> {
+    Ingress::from_properties(
+        "my-ingress".into(),
+        "squid".into(),
+        "patsy".into(),
+        properties.as_object(),
+        None,
+    )
+}
+
+#[test]
+fn test_ingress_annotations() {
+    let ig = ingress(json!({
+        "hostname": "in.example.com",
+        "annotations": {
+            "nginx.ingress.kubernetes.io/rewrite-target": "/"
+        }
+    }))
+    .expect("ingress");
+
+    let annotations = ig
+        .to_ext_ingress()
+        .metadata
+        .expect("metadata required")
+        .annotations
+        .expect("annotations required");
+    assert_eq!(1, annotations.len());
+    assert_eq!(
+        Some(&"/".to_string()),
+        annotations.get("nginx.ingress.kubernetes.io/rewrite-target")
+    );
+
+    // No annotations are rendered when none are given.
+    let plain = ingress(json!({ "hostname": "in.example.com" })).expect("ingress");
+    assert!(plain
+        .to_ext_ingress()
+        .metadata
+        .expect("metadata required")
+        .annotations
+        .is_none());
+}
+
+#[test]
+fn test_ingress_annotations_invalid() {
+    let err = ingress(json!({ "annotations": { "rudr.io/spec-hash": "abc" } }))
+        .expect_err("managed annotation");
+    assert!(err.to_string().contains("which Rudr manages"));
+    assert!(ingress(json!({ "annotations": { "oam.dev/instance-name": "squid" } })).is_err());
+
+    assert!(ingress(json!({ "annotations": { "replicas": 3 } })).is_err());
+    assert!(ingress(json!({ "annotations": "rewrite-target=/" })).is_err());
+
+    // The property type is checked before the trait is loaded, too.
+    let binding = TraitBinding {
+        name: INGRESS_V1ALPHA1.into(),
+        parameter_values: None,
+        properties: Some(json!({ "annotations": ["rewrite-target"] })),
+    };
+    assert!(binding.validate_properties().is_err());
+}
+
+#[test]
+fn test_ingress_tls() {
+    let ig = ingress(json!({
+        "hostname": "in.example.com",
+        "tlsHosts": "in.example.com",
+        "tlsSecretName": "in-example-com-tls"
+    }))
+    .expect("ingress");
+    let tls = ig.to_ext_ingress().spec.expect("spec required").tls;
+    assert_eq!(
+        Some(vec![ext::IngressTLS {
+            hosts: Some(vec!["in.example.com".into()]),
+            secret_name: Some("in-example-com-tls".into()),
+        }]),
+        tls
+    );
+
+    // The TLS hosts are optional.
+    let ig = ingress(json!({
+        "hostname": "in.example.com",
+        "tlsSecretName": "in-example-com-tls"
+    }))
+    .expect("ingress");
+    assert!(ig.tls_hosts.is_none());
+
+    // Spaces around the TLS hosts are ignored, and so are empty entries.
+    let ig = ingress(json!({
+        "hostname": "in.example.com",
+        "tlsHosts": "in.example.com, in.example.com,",
+        "tlsSecretName": "in-example-com-tls"
+    }))
+    .expect("ingress");
+    assert_eq!(
+        Some(vec!["in.example.com".to_string(), "in.example.com".to_string()]),
+        ig.tls_hosts
+    );
+}
+
+#[test]
+fn test_ingress_tls_host_mismatch() {
+    let err = ingress(json!({
+        "hostname": "in.example.com",
+        "tlsHosts": "in.example.com,out.example.com",
+        "tlsSecretName": "in-example-com-tls"
+    }))
+    .expect_err("TLS host is not routed");
+    assert!(err.to_string().contains("TLS host out.example.com"));
+
+    // The routing host defaults to example.com.
+    assert!(ingress(json!({
+        "tlsHosts": "in.example.com",
+        "tlsSecretName": "in-example-com-tls"
+    }))
+    .is_err());
+
+    assert!(ingress(json!({
+        "hostname": "in.example.com",
+        "tlsHosts": "in.example.com"
+    }))
+    .is_err());
+}
@@ -148,6 +148,23 @@ impl ManualScaler {
             ..Default::default()
         }
     }
+
+    /// Scale rendered workload objects, as `scale` does for the objects in the cluster.
+    ///
+    /// The Deployment of a Server or Worker gets the replica count and the Job of a Task
+    /// the parallelism; other workload types and objects are left unchanged.
+    pub fn scale_objects(&self, objects: &mut [serde_json::Value]) {
+        let (kind, field) = match self.workload_type.as_str() {
+            SERVER_NAME | WORKER_NAME => ("Deployment", "replicas"),
+            TASK_NAME => ("Job", "parallelism"),
+            _ => return,
+        };
+        for object in objects.iter_mut().filter(|o| o["kind"] == kind) {
+            if let Some(spec) = object.get_mut("spec").and_then(|s| s.as_object_mut()) {
+                spec.insert(field.to_string(), serde_json::json!(self.replica_count));
+            }
+        }
+    }
 }
 
 impl TraitImplementation for ManualScaler {
@@ -60,6 +60,39 @@ fn test_scale_job() {
     assert_eq!(Some(9), second.spec.expect("spec is required").parallelism);
 }
 
+#[test]
+fn test_scale_objects() {
+    let scaler = |workload_type: &str| ManualScaler {
+        name: "name".into(),
+        instance_name: "inst_name".into(),
+        component_name: "comp_name".into(),
+        owner_ref: None,
+        replica_count: 9,
+        workload_type: workload_type.into(),
+    };
+    let rendered = || {
+        vec![
+            json!({"kind": "Deployment", "spec": {"replicas": 1}}),
+            json!({"kind": "Job", "spec": {"parallelism": 2}}),
+        ]
+    };
+
+    let mut objects = rendered();
+    scaler(SERVER_NAME).scale_objects(&mut objects);
+    assert_eq!(json!(9), objects[0]["spec"]["replicas"]);
+    assert_eq!(json!(2), objects[1]["spec"]["parallelism"]);
+
+    let mut objects = rendered();
+    scaler(TASK_NAME).scale_objects(&mut objects);
+    assert_eq!(json!(1), objects[0]["spec"]["replicas"]);
+    assert_eq!(json!(9), objects[1]["spec"]["parallelism"]);
+
+    // Workload types the cluster path does not scale are left as rendered.
+    let mut objects = rendered();
+    scaler(SINGLETON_TASK_NAME).scale_objects(&mut objects);
+    assert_eq!(rendered(), objects);
+}
+
 #[test]
 fn test_manual_scaler_v1alpha1_properties() {
     let first = batch::Job {
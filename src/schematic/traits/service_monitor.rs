@@ -0,0 +1,165 @@
+use crate::schematic::component::Component;
+use crate::schematic::traits::{util::*, TraitImplementation};
+use crate::workload_type::workload_info;
+use failure::Error;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as meta;
+use kube::api::{DeleteParams, PatchParams, PostParams, RawApi};
+use kube::client::APIClient;
+use serde_json::map::Map;
+use std::collections::BTreeMap;
+
+/// The API version of the Prometheus Operator's ServiceMonitor.
+pub const SERVICE_MONITOR_API_VERSION: &str = "monitoring.coreos.com/v1";
+
+/// A ServiceMonitor trait has the Prometheus Operator scrape the component instance to
+/// which it is attached.
+///
+/// In Kubernetes, this will create a ServiceMonitor that selects the Service of a
+/// particular component instance and scrapes one of its named ports.
+#[derive(Clone, Debug)]
+pub struct ServiceMonitor {
+    pub name: String,
+    pub instance_name: String,
+    pub component_name: String,
+    pub port: String,
+    pub interval: Option<String>,
+    pub path: Option<String>,
+    pub owner_ref: OwnerRefs,
+}
+impl ServiceMonitor {
+    /// Read the trait properties: the `port` to scrape, by name, and the optional scrape
+    /// `interval` and metrics `path`.
+    ///
+    /// The port must be one the component's Service exposes, so a component whose
+    /// workload type has no Service, or with no port of that name, is an error.
+    pub fn from_properties(
+        name: String,
+        instance_name: String,
+        component_name: String,
+        properties_map: Option<&Map<String, serde_json::value::Value>>,
+        owner_ref: OwnerRefs,
+        component: &Component,
+    ) -> Result<Self, Error> {
+        let property = |key: &str| {
+            properties_map
+                .and_then(|map| map.get(key))
+                .and_then(|p| p.as_str())
+                .map(|p| p.to_string())
+        };
+        let port = property("port").ok_or_else(|| {
+            format_err!(
+                "service-monitor trait for {} requires a port name",
+                instance_name
+            )
+        })?;
+        let has_service = workload_info(component.workload_type.as_str())
+            .map(|info| info.has_service)
+            .unwrap_or(false);
+        if !has_service {
+            return Err(format_err!(
+                "service-monitor trait for {}: workload type {} has no Service",
+                instance_name,
+                component.workload_type
+            ));
+        }
+        if !component.listening_ports().iter().any(|p| p.name == port) {
+            return Err(format_err!(
+                "service-monitor trait for {}: the Service has no port named {}",
+                instance_name,
+                port
+            ));
+        }
+        Ok(ServiceMonitor {
+            name,
+            instance_name,
+            component_name,
+            port,
+            interval: property("interval"),
+            path: property("path"),
+            owner_ref,
+        })
+    }
+
+    /// Render the ServiceMonitor. The Prometheus Operator's types are not part of the
+    /// Kubernetes API, so it is rendered as JSON.
+    pub fn to_service_monitor(&self) -> serde_json::Value {
+        let mut endpoint = serde_json::json!({ "port": self.port });
+        if let Some(interval) = self.interval.as_ref() {
+            endpoint["interval"] = serde_json::json!(interval);
+        }
+        if let Some(path) = self.path.as_ref() {
+            endpoint["path"] = serde_json::json!(path);
+        }
+        let metadata = meta::ObjectMeta {
+            name: Some(self.kube_name()),
+            labels: Some(trait_labels(self.name.clone(), self.instance_name.clone())),
+            owner_references: self.owner_ref.clone(),
+            ..Default::default()
+        };
+        // The instance's Service is the only one carrying its instance name.
+        serde_json::json!({
+            "apiVersion": SERVICE_MONITOR_API_VERSION,
+            "kind": "ServiceMonitor",
+            "metadata": metadata,
+            "spec": {
+                "selector": {
+                    "matchLabels": {
+                        "oam.dev/instance-name": self.instance_name
+                    }
+                },
+                "endpoints": [endpoint]
+            }
+        })
+    }
+
+    fn kube_name(&self) -> String {
+        format!("{}-trait-service-monitor", self.instance_name)
+    }
+
+    fn resource(&self, ns: &str) -> RawApi {
+        RawApi::customResource("servicemonitors")
+            .group("monitoring.coreos.com")
+            .version("v1")
+            .within(ns)
+    }
+}
+impl TraitImplementation for ServiceMonitor {
+    fn add(&self, ns: &str, client: APIClient) -> TraitResult {
+        let req = self.resource(ns).create(
+            &PostParams::default(),
+            serde_json::to_vec(&self.to_service_monitor())?,
+        )?;
+        client.request::<serde_json::Value>(req)?;
+        Ok(())
+    }
+    fn modify(&self, ns: &str, client: APIClient) -> TraitResult {
+        let req = self.resource(ns).patch(
+            self.kube_name().as_str(),
+            &PatchParams::default(),
+            serde_json::to_vec(&self.to_service_monitor())?,
+        )?;
+        client.request::<serde_json::Value>(req)?;
+        Ok(())
+    }
+    fn delete(&self, ns: &str, client: APIClient) -> TraitResult {
+        let req = self
+            .resource(ns)
+            .delete(self.kube_name().as_str(), &DeleteParams::default())?;
+        client.request::<serde_json::Value>(req)?;
+        Ok(())
+    }
+    fn status(&self, ns: &str, client: APIClient) -> Option<BTreeMap<String, String>> {
+        let key = "servicemonitor/".to_string() + self.kube_name().as_str();
+        let state = match self
+            .resource(ns)
+            .get(self.kube_name().as_str())
+            .and_then(|req| client.request::<serde_json::Value>(req))
+        {
+            Ok(_) => "created".to_string(),
+            Err(e) => e.to_string(),
+        };
+        let mut resource = BTreeMap::new();
+        resource.insert(key, state);
+        Some(resource)
+    }
+}
@@ -0,0 +1,80 @@
+use crate::schematic::component::{Component, Container, Port};
+use crate::schematic::traits::*;
+use crate::workload_type::{SERVER_NAME, WORKER_NAME};
+use serde_json::json;
+
+fn web_component(workload_type: &str) -> Component {
+    Component {
+        workload_type: workload_type.into(),
+        containers: vec![Container {
+            name: "web".into(),
+            image: "nginx:latest".into(),
+            ports: vec![Port::basic("metrics".into(), 9090)],
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
+
+fn service_monitor(
+    properties: serde_json::Value,
+    component: &Component,
+) -> Result<ServiceMonitor, failure::Error> {
+    ServiceMonitor::from_properties(
+        "my-monitor".into(),
+        "squid".into(),
+        "patsy".into(),
+        properties.as_object(),
+        None,
+        component,
+    )
+}
+
+#[test]
+fn test_service_monitor() {
+    let monitor = service_monitor(
+        json!({
+            "port": "metrics",
+            "interval": "30s",
+            "path": "/prometheus"
+        }),
+        &web_component(SERVER_NAME),
+    )
+    .expect("service monitor");
+
+    let rendered = monitor.to_service_monitor();
+    assert_eq!("monitoring.coreos.com/v1", rendered["apiVersion"]);
+    assert_eq!("ServiceMonitor", rendered["kind"]);
+    assert_eq!("squid-trait-service-monitor", rendered["metadata"]["name"]);
+    assert_eq!(
+        json!({ "oam.dev/instance-name": "squid" }),
+        rendered["spec"]["selector"]["matchLabels"]
+    );
+    assert_eq!(
+        json!([{
+            "port": "metrics",
+            "interval": "30s",
+            "path": "/prometheus"
+        }]),
+        rendered["spec"]["endpoints"]
+    );
+
+    let minimal = service_monitor(json!({ "port": "metrics" }), &web_component(SERVER_NAME))
+        .expect("service monitor");
+    assert_eq!(
+        json!([{ "port": "metrics" }]),
+        minimal.to_service_monitor()["spec"]["endpoints"]
+    );
+}
+
+#[test]
+fn test_service_monitor_missing_port() {
+    let err = service_monitor(json!({ "port": "http" }), &web_component(SERVER_NAME))
+        .expect_err("port must be exposed");
+    assert!(err.to_string().contains("no port named http"));
+
+    // A Worker has no Service to scrape.
+    assert!(service_monitor(json!({ "port": "metrics" }), &web_component(WORKER_NAME)).is_err());
+
+    assert!(service_monitor(json!({}), &web_component(SERVER_NAME)).is_err());
+}
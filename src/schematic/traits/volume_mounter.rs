@@ -6,7 +6,7 @@ use log::warn;
 use serde_json::map::Map;
 
 use crate::schematic::{
-    component::{AccessMode, Component, SharingPolicy, Volume},
+    component::{Component, Volume},
     traits::util::{OwnerRefs, TraitResult},
     traits::TraitImplementation,
 };
@@ -89,7 +89,7 @@ impl VolumeMounter {
                 ..Default::default()
             }),
             spec: Some(core::PersistentVolumeClaimSpec {
-                access_modes: Some(vec![self.mount_policy(attach_to)]),
+                access_modes: Some(vec![attach_to.to_access_mode()]),
                 storage_class_name: Some(self.storage_class.clone()),
                 resources: Some(core::ResourceRequirements {
                     requests: Some(reqs),
@@ -100,21 +100,10 @@ impl VolumeMounter {
             ..Default::default()
         })
     }
-    fn mount_policy(&self, volume: &Volume) -> String {
-        match volume.access_mode {
-            AccessMode::RO => "ReadOnlyMany",
-            AccessMode::RW => match volume.sharing_policy {
-                SharingPolicy::Shared => "ReadWriteMany",
-                _ => "ReadWriteOnce",
-            },
-        }
-        .to_string()
-    }
-
     /// Locate the volume that this mounter is supposed to attach that is not ephemeral
     fn find_volume(&self) -> Option<&Volume> {
         self.component.containers.iter().find_map(|c| {
-            c.resources.volumes.as_ref().and_then(|vols| {
+            c.resources().volumes.as_ref().and_then(|vols| {
                 vols.iter()
                     .filter(|v| !v.disk.as_ref().unwrap().ephemeral)
                     .find(|v| self.volume_name.eq_ignore_ascii_case(v.name.as_str()))
@@ -257,7 +246,7 @@ mod test {
     fn mock_container(name: &str) -> Container {
         Container {
             name: name.to_string(),
-            resources: Resources {
+            resources: Some(Resources {
                 volumes: Some(vec![Volume {
                     name: "panda-bears".to_string(),
                     mount_path: "/var/foo".to_string(),
@@ -267,9 +256,12 @@ mod test {
                         required: "123M".to_string(),
                         ephemeral: false,
                     }),
+                    inline_content: None,
+                    sub_path: None,
+                    mount_propagation: None,
                 }]),
                 ..Default::default()
-            },
+            }),
             ..Default::default()
         }
     }
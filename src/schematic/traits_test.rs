@@ -30,3 +30,51 @@ fn mock_client() -> APIClient {
         client: reqwest::Client::new(),
     })
 }
+
+#[test]
+fn test_validate_properties() {
+    let binding = |name: &str, properties: serde_json::Value| TraitBinding {
+        name: name.into(),
+        parameter_values: None,
+        properties: Some(properties),
+    };
+
+    assert!(binding(
+        MANUAL_SCALER_V1ALPHA1,
+        serde_json::json!({ "replicaCount": 3 })
+    )
+    .validate_properties()
+    .is_ok());
+
+    let err = binding(
+        MANUAL_SCALER_V1ALPHA1,
+        serde_json::json!({ "replicaCont": 3 }),
+    )
+    .validate_properties()
+    .expect_err("misspelled property");
+    assert!(err
+        .to_string()
+        .contains("trait manual-scaler has no property replicaCont"));
+    assert!(err.to_string().contains("replicaCount"));
+
+    let err = binding(
+        MANUAL_SCALER_V1ALPHA1,
+        serde_json::json!({ "replicaCount": "three" }),
+    )
+    .validate_properties()
+    .expect_err("mistyped property");
+    assert!(err.to_string().contains("must be an integer"));
+
+    let err = binding(MANUAL_SCALER_V1ALPHA1, serde_json::json!({}))
+        .validate_properties()
+        .expect_err("missing property");
+    assert!(err.to_string().contains("requires property replicaCount"));
+
+    // Ingress properties are all optional.
+    let ingress = TraitBinding {
+        name: INGRESS_V1ALPHA1.into(),
+        parameter_values: None,
+        properties: None,
+    };
+    assert!(ingress.validate_properties().is_ok());
+}
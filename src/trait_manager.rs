@@ -13,7 +13,8 @@ use crate::{
         configuration::ComponentConfiguration,
         parameter::ParameterValue,
         traits::{
-            self, Autoscaler, Empty, Ingress, ManualScaler, OAMTrait, TraitBinding, VolumeMounter,
+            self, Autoscaler, Empty, Ingress, ManualScaler, OAMTrait, ServiceMonitor, TraitBinding,
+            VolumeMounter,
         },
     },
 };
@@ -38,16 +39,18 @@ pub struct TraitManager {
 impl TraitManager {
     pub fn load_traits(&mut self) -> Result<(), failure::Error> {
         let mut traits: Vec<OAMTrait> = vec![];
-        for t in self.component.traits.as_ref().unwrap_or(&vec![]).iter() {
-            // Load all of the traits into the manager.
+        let bindings = self.component.traits.clone().unwrap_or_default();
+        for t in traits::in_application_order(&bindings) {
+            // Load all of the traits into the manager, in the order they are applied.
             let imp = self.load_trait(&t)?;
             traits.push(imp);
         }
         self.traits = traits;
         Ok(())
     }
-    fn load_trait(&self, binding: &TraitBinding) -> Result<OAMTrait, failure::Error> {
+    pub(crate) fn load_trait(&self, binding: &TraitBinding) -> Result<OAMTrait, failure::Error> {
         debug!("Trait binding params: {:?}", &binding.parameter_values);
+        binding.validate_properties()?;
         let empty_value_ref: &serde_json::Value = &json!("");
         let prop_map: Option<&Map<String, serde_json::value::Value>> = binding
             .properties
@@ -62,7 +65,7 @@ impl TraitManager {
                     self.component.component_name.clone(),
                     prop_map,
                     self.owner_ref.clone(),
-                );
+                )?;
                 debug!("INGRESS_V1ALPHA1: {:?}", ing);
                 Ok(OAMTrait::Ingress(ing))
             }
@@ -101,13 +104,29 @@ impl TraitManager {
                 debug!("Manual_scaler: {:?}", scaler);
                 Ok(OAMTrait::ManualScaler(scaler))
             }
+            traits::SERVICE_MONITOR_V1ALPHA1 => {
+                let monitor = ServiceMonitor::from_properties(
+                    self.config_name.clone(),
+                    self.instance_name.clone(),
+                    self.component.component_name.clone(),
+                    prop_map,
+                    self.owner_ref.clone(),
+                    &self.component_schematic,
+                )?;
+                debug!("SERVICE_MONITOR: {:?}", monitor);
+                Ok(OAMTrait::ServiceMonitor(monitor))
+            }
             // Empty is a debugging tool for checking whether the traits system is functioning independently of
             // its environment.
             traits::EMPTY => {
                 let empty = Empty {};
                 Ok(OAMTrait::Empty(empty))
             }
-            _ => Err(format_err!("unknown trait {}", binding.name)),
+            _ => Err(format_err!(
+                "unknown trait {}; known traits are {}",
+                binding.name,
+                traits::TRAIT_NAMES.join(", ")
+            )),
         }
     }
     pub fn exec(&self, ns: &str, client: APIClient, phase: Phase) -> Result<(), Error> {
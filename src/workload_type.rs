@@ -3,19 +3,29 @@ use log::info;
 use std::collections::BTreeMap;
 
 mod server;
-pub use crate::workload_type::server::{ReplicatedServer, SingletonServer};
+pub use crate::workload_type::server::{ReplicatedServer, SingletonServer, StatefulService};
 
 mod task;
-pub use crate::workload_type::task::{ReplicatedTask, SingletonTask};
+pub use crate::workload_type::task::{
+    ReplicableTask, ReplicatedTask, ScheduledTask, SingletonTask,
+};
 
 mod worker;
 pub use crate::workload_type::worker::{ReplicatedWorker, SingletonWorker};
 
 mod workload_builder;
-pub use crate::workload_type::workload_builder::WorkloadMetadata;
+pub use crate::workload_type::workload_builder::{instance_labels, WorkloadMetadata};
 
 mod statefulset_builder;
 
+mod render;
+pub use crate::workload_type::render::{render_all, render_all_in_namespace, set_namespace};
+
+mod registry;
+pub use crate::workload_type::registry::{
+    is_core_workload_type, workload_info, workload_registry, WorkloadInfo,
+};
+
 pub mod extended_workload;
 
 pub const OAM_API_VERSION: &str = "core.oam.dev/v1alpha1";
@@ -24,11 +34,17 @@ pub const OAM_API_VERSION: &str = "core.oam.dev/v1alpha1";
 pub const SERVER_NAME: &str = "core.oam.dev/v1alpha1.Server";
 /// SingletonServer is a kind of Server that can't be replicated
 pub const SINGLETON_SERVER_NAME: &str = "core.oam.dev/v1alpha1.SingletonServer";
+/// StatefulService is a Server whose replicas each keep their own durable storage
+pub const STATEFUL_SERVICE_NAME: &str = "core.oam.dev/v1alpha1.StatefulService";
 
 /// SingletonTask is a task that cannot be replicated
 pub const SINGLETON_TASK_NAME: &str = "core.oam.dev/v1alpha1.SingletonTask";
 /// Task just means a replicated task which will replace ReplicatedTask
 pub const TASK_NAME: &str = "core.oam.dev/v1alpha1.Task";
+/// ReplicableTask is a task whose parallelism and completions come from parameters
+pub const REPLICABLE_TASK_NAME: &str = "core.oam.dev/v1alpha1.ReplicableTask";
+/// ScheduledTask is a task that runs on a cron schedule
+pub const SCHEDULED_TASK_NAME: &str = "core.oam.dev/v1alpha1.ScheduledTask";
 
 /// Singleton worker is a Worker that cannot be replicated
 pub const SINGLETON_WORKER: &str = "core.oam.dev/v1alpha1.SingletonWorker";
@@ -39,6 +55,7 @@ type InstigatorResult = Result<(), Error>;
 type StatusResult = Result<BTreeMap<String, String>, Error>;
 pub type ParamMap = BTreeMap<String, serde_json::Value>;
 pub type ValidationResult = Result<(), failure::Error>;
+pub type RenderResult = Result<Vec<serde_json::Value>, Error>;
 
 /// KubeName describes anything that can produce its own Kubernetes name.
 ///
@@ -79,13 +96,20 @@ pub trait WorkloadType {
     fn validate(&self) -> ValidationResult {
         Ok(())
     }
+    /// Render returns the Kubernetes objects that add would create, without a cluster.
+    fn render(&self) -> RenderResult {
+        Err(format_err!("Not implemented"))
+    }
 }
 
 pub enum CoreWorkloadType {
     SingletonServerType(SingletonServer),
     ReplicatedServerType(ReplicatedServer),
+    StatefulServiceType(StatefulService),
     SingletonTaskType(SingletonTask),
     ReplicatedTaskType(ReplicatedTask),
+    ReplicableTaskType(ReplicableTask),
+    ScheduledTaskType(ScheduledTask),
     ReplicatedWorkerType(ReplicatedWorker),
     SingletonWorkerType(SingletonWorker),
 }
@@ -95,8 +119,11 @@ impl WorkloadType for CoreWorkloadType {
         match self {
             CoreWorkloadType::SingletonServerType(sing) => sing.add(),
             CoreWorkloadType::ReplicatedServerType(repl) => repl.add(),
+            CoreWorkloadType::StatefulServiceType(stateful) => stateful.add(),
             CoreWorkloadType::SingletonTaskType(task) => task.add(),
             CoreWorkloadType::ReplicatedTaskType(task) => task.add(),
+            CoreWorkloadType::ReplicableTaskType(task) => task.add(),
+            CoreWorkloadType::ScheduledTaskType(task) => task.add(),
             CoreWorkloadType::ReplicatedWorkerType(task) => task.add(),
             CoreWorkloadType::SingletonWorkerType(task) => task.add(),
         }
@@ -105,8 +132,11 @@ impl WorkloadType for CoreWorkloadType {
         match self {
             CoreWorkloadType::SingletonServerType(sing) => sing.modify(),
             CoreWorkloadType::ReplicatedServerType(repl) => repl.modify(),
+            CoreWorkloadType::StatefulServiceType(stateful) => stateful.modify(),
             CoreWorkloadType::SingletonTaskType(task) => task.modify(),
             CoreWorkloadType::ReplicatedTaskType(task) => task.modify(),
+            CoreWorkloadType::ReplicableTaskType(task) => task.modify(),
+            CoreWorkloadType::ScheduledTaskType(task) => task.modify(),
             CoreWorkloadType::ReplicatedWorkerType(task) => task.modify(),
             CoreWorkloadType::SingletonWorkerType(task) => task.modify(),
         }
@@ -115,8 +145,11 @@ impl WorkloadType for CoreWorkloadType {
         match self {
             CoreWorkloadType::SingletonServerType(sing) => sing.delete(),
             CoreWorkloadType::ReplicatedServerType(repl) => repl.delete(),
+            CoreWorkloadType::StatefulServiceType(stateful) => stateful.delete(),
             CoreWorkloadType::SingletonTaskType(task) => task.delete(),
             CoreWorkloadType::ReplicatedTaskType(task) => task.delete(),
+            CoreWorkloadType::ReplicableTaskType(task) => task.delete(),
+            CoreWorkloadType::ScheduledTaskType(task) => task.delete(),
             CoreWorkloadType::ReplicatedWorkerType(task) => task.delete(),
             CoreWorkloadType::SingletonWorkerType(task) => task.delete(),
         }
@@ -125,8 +158,11 @@ impl WorkloadType for CoreWorkloadType {
         match self {
             CoreWorkloadType::SingletonServerType(sing) => sing.status(),
             CoreWorkloadType::ReplicatedServerType(repl) => repl.status(),
+            CoreWorkloadType::StatefulServiceType(stateful) => stateful.status(),
             CoreWorkloadType::SingletonTaskType(task) => task.status(),
             CoreWorkloadType::ReplicatedTaskType(task) => task.status(),
+            CoreWorkloadType::ReplicableTaskType(task) => task.status(),
+            CoreWorkloadType::ScheduledTaskType(task) => task.status(),
             CoreWorkloadType::ReplicatedWorkerType(task) => task.status(),
             CoreWorkloadType::SingletonWorkerType(task) => task.status(),
         }
@@ -135,12 +171,57 @@ impl WorkloadType for CoreWorkloadType {
         match self {
             CoreWorkloadType::SingletonServerType(sing) => sing.validate(),
             CoreWorkloadType::ReplicatedServerType(repl) => repl.validate(),
+            CoreWorkloadType::StatefulServiceType(stateful) => stateful.validate(),
             CoreWorkloadType::SingletonTaskType(task) => task.validate(),
             CoreWorkloadType::ReplicatedTaskType(task) => task.validate(),
+            CoreWorkloadType::ReplicableTaskType(task) => task.validate(),
+            CoreWorkloadType::ScheduledTaskType(task) => task.validate(),
             CoreWorkloadType::ReplicatedWorkerType(task) => task.validate(),
             CoreWorkloadType::SingletonWorkerType(task) => task.validate(),
         }
     }
+    fn render(&self) -> RenderResult {
+        match self {
+            CoreWorkloadType::SingletonServerType(sing) => sing.render(),
+            CoreWorkloadType::ReplicatedServerType(repl) => repl.render(),
+            CoreWorkloadType::StatefulServiceType(stateful) => stateful.render(),
+            CoreWorkloadType::SingletonTaskType(task) => task.render(),
+            CoreWorkloadType::ReplicatedTaskType(task) => task.render(),
+            CoreWorkloadType::ReplicableTaskType(task) => task.render(),
+            CoreWorkloadType::ScheduledTaskType(task) => task.render(),
+            CoreWorkloadType::ReplicatedWorkerType(task) => task.render(),
+            CoreWorkloadType::SingletonWorkerType(task) => task.render(),
+        }
+    }
+}
+
+/// Build the core workload type named by the component in the metadata.
+pub fn core_workload_type(meta: WorkloadMetadata) -> Result<CoreWorkloadType, Error> {
+    let workload = match meta.definition.workload_type.as_str() {
+        SERVER_NAME => CoreWorkloadType::ReplicatedServerType(ReplicatedServer { meta }),
+        SINGLETON_SERVER_NAME => CoreWorkloadType::SingletonServerType(SingletonServer { meta }),
+        STATEFUL_SERVICE_NAME => CoreWorkloadType::StatefulServiceType(StatefulService { meta }),
+        SINGLETON_TASK_NAME => CoreWorkloadType::SingletonTaskType(SingletonTask { meta }),
+        TASK_NAME => CoreWorkloadType::ReplicatedTaskType(ReplicatedTask {
+            meta,
+            replica_count: Some(1), // Every(1) needs Some(1) to love.
+        }),
+        REPLICABLE_TASK_NAME => CoreWorkloadType::ReplicableTaskType(ReplicableTask { meta }),
+        SCHEDULED_TASK_NAME => CoreWorkloadType::ScheduledTaskType(ScheduledTask { meta }),
+        SINGLETON_WORKER => CoreWorkloadType::SingletonWorkerType(SingletonWorker { meta }),
+        WORKER_NAME => CoreWorkloadType::ReplicatedWorkerType(ReplicatedWorker {
+            meta,
+            replica_count: Some(1), // Every(1) needs Some(1) to love.
+        }),
+        other => {
+            workload_info(other)?;
+            return Err(format_err!(
+                "workload type {} is not a core workload type",
+                other
+            ));
+        }
+    };
+    Ok(workload)
 }
 
 pub enum ExtendedWorkloadType {
@@ -88,14 +88,14 @@ pub struct OpenFaaS {
 }
 
 impl OpenFaaS {
-    pub fn extract_environment(&self) -> Option<BTreeMap<String, String>> {
+    pub fn extract_environment(&self) -> Result<Option<BTreeMap<String, String>>, Error> {
         let mut envs = BTreeMap::new();
         let array = match self
             .meta
-            .get_workload_setting("environment")
+            .get_workload_setting("environment")?
             .and_then(|im| im.as_array().cloned())
         {
-            None => return None,
+            None => return Ok(None),
             Some(ar) => ar,
         };
 
@@ -127,15 +127,15 @@ impl OpenFaaS {
         }
 
         if envs.is_empty() {
-            None
+            Ok(None)
         } else {
-            Some(envs)
+            Ok(Some(envs))
         }
     }
     pub fn get_kube_faas(&self) -> Result<KubeFaaS, Error> {
         let image = match self
             .meta
-            .get_workload_setting("image")
+            .get_workload_setting("image")?
             .and_then(|im| im.as_str().and_then(|s| Some(s.to_string())))
         {
             None => {
@@ -147,7 +147,7 @@ impl OpenFaaS {
         };
         let handler = self
             .meta
-            .get_workload_setting("handler")
+            .get_workload_setting("handler")?
             .and_then(|im| im.as_str().and_then(|s| Some(s.to_string())));
 
         let mut kube_faas = KubeFaaS {
@@ -163,7 +163,7 @@ impl OpenFaaS {
                 name: self.meta.instance_name.clone(),
                 image: image.to_string(),
                 handler,
-                environment: self.extract_environment(),
+                environment: self.extract_environment()?,
                 ..Default::default()
             },
             status: None,
@@ -299,6 +299,6 @@ mod test {
         let mut envs = BTreeMap::new();
         envs.insert("write_debug".to_string(), "false".to_string());
         envs.insert("key".to_string(), "hello".to_string());
-        assert_eq!(of.extract_environment(), Some(envs))
+        assert_eq!(of.extract_environment().expect("environment"), Some(envs))
     }
 }
@@ -13,8 +13,8 @@ pub struct Others {
 }
 
 impl Others {
-    pub fn new(meta: WorkloadMetadata, type_: &str) -> Result<Self, Error> {
-        let gvk: GroupVersionKind = std::str::FromStr::from_str(type_)?;
+    pub fn new(meta: WorkloadMetadata) -> Result<Self, Error> {
+        let gvk = meta.definition.workload_gvk()?;
         if meta
             .definition
             .workload_settings
@@ -125,36 +125,34 @@ mod test {
 
     #[test]
     fn test_get_object() {
-        let workload = Others::new(
-            WorkloadMetadata {
-                name: "test".to_string(),
-                component_name: "test".to_string(),
-                instance_name: "test".to_string(),
-                namespace: "default".to_string(),
-                definition: Component {
-                    workload_settings: vec![WorkloadSetting {
-                        name: "spec".to_string(),
-                        parameter_type: ParameterType::Object,
-                        value: Some(
-                            serde_json::to_value(json!({"image":"testrepo/test","name":"test"}))
-                                .unwrap(),
-                        ),
-                        from_param: None,
-                        required: true,
-                        description: None,
-                    }],
-                    ..Default::default()
-                },
-                client: APIClient::new(Configuration {
-                    base_path: ".".into(),
-                    client: reqwest::Client::new(),
-                }),
-                params: BTreeMap::new(),
-                owner_ref: None,
-                annotations: None,
+        let workload = Others::new(WorkloadMetadata {
+            name: "test".to_string(),
+            component_name: "test".to_string(),
+            instance_name: "test".to_string(),
+            namespace: "default".to_string(),
+            definition: Component {
+                workload_type: "extend.oam.dev/v1alpha1.Test".to_string(),
+                workload_settings: vec![WorkloadSetting {
+                    name: "spec".to_string(),
+                    parameter_type: ParameterType::Object,
+                    value: Some(
+                        serde_json::to_value(json!({"image":"testrepo/test","name":"test"}))
+                            .unwrap(),
+                    ),
+                    from_param: None,
+                    required: true,
+                    description: None,
+                }],
+                ..Default::default()
             },
-            "extend.oam.dev/v1alpha1.Test",
-        )
+            client: APIClient::new(Configuration {
+                base_path: ".".into(),
+                client: reqwest::Client::new(),
+            }),
+            params: BTreeMap::new(),
+            owner_ref: None,
+            annotations: None,
+        })
         .unwrap();
 
         assert_eq!(
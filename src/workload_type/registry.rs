@@ -0,0 +1,112 @@
+use failure::Error;
+use std::collections::BTreeMap;
+
+use crate::workload_type::{
+    OAM_API_VERSION, REPLICABLE_TASK_NAME, SCHEDULED_TASK_NAME, SERVER_NAME, SINGLETON_SERVER_NAME,
+    SINGLETON_TASK_NAME, SINGLETON_WORKER, STATEFUL_SERVICE_NAME, TASK_NAME, WORKER_NAME,
+};
+
+/// WorkloadInfo describes the capabilities of a core workload type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorkloadInfo {
+    /// Whether more than one replica of the workload may run.
+    pub replicable: bool,
+    /// Whether a Service is created for the workload's ports.
+    pub has_service: bool,
+    /// Whether the workload runs to completion, rather than until it is removed.
+    pub run_to_completion: bool,
+}
+
+impl WorkloadInfo {
+    fn new(replicable: bool, has_service: bool, run_to_completion: bool) -> Self {
+        WorkloadInfo {
+            replicable,
+            has_service,
+            run_to_completion,
+        }
+    }
+}
+
+/// workload_registry lists the core workload types Rudr supports, keyed by GVK string.
+pub fn workload_registry() -> BTreeMap<&'static str, WorkloadInfo> {
+    let mut registry = BTreeMap::new();
+    registry.insert(SERVER_NAME, WorkloadInfo::new(true, true, false));
+    registry.insert(SINGLETON_SERVER_NAME, WorkloadInfo::new(false, true, false));
+    registry.insert(STATEFUL_SERVICE_NAME, WorkloadInfo::new(true, true, false));
+    registry.insert(SINGLETON_TASK_NAME, WorkloadInfo::new(false, false, true));
+    registry.insert(TASK_NAME, WorkloadInfo::new(true, false, true));
+    registry.insert(REPLICABLE_TASK_NAME, WorkloadInfo::new(true, false, true));
+    registry.insert(SCHEDULED_TASK_NAME, WorkloadInfo::new(false, false, true));
+    registry.insert(SINGLETON_WORKER, WorkloadInfo::new(false, false, false));
+    registry.insert(WORKER_NAME, WorkloadInfo::new(true, false, false));
+    registry
+}
+
+/// workload_info looks up a core workload type in the registry.
+pub fn workload_info(workload_type: &str) -> Result<WorkloadInfo, Error> {
+    let registry = workload_registry();
+    registry.get(workload_type).cloned().ok_or_else(|| {
+        format_err!(
+            "unsupported workload type {}; supported types are {}",
+            workload_type,
+            registry.keys().cloned().collect::<Vec<&str>>().join(", ")
+        )
+    })
+}
+
+/// is_core_workload_type reports whether a workload type belongs to the core OAM group.
+///
+/// Core workload types are fulfilled by Rudr itself and must be in the registry. Other
+/// workload types are fulfilled by extended workloads.
+pub fn is_core_workload_type(workload_type: &str) -> bool {
+    workload_type.starts_with(format!("{}.", OAM_API_VERSION).as_str())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::workload_type::registry::*;
+
+    #[test]
+    fn test_workload_registry() {
+        let registry = workload_registry();
+
+        let singleton = registry
+            .get(SINGLETON_SERVER_NAME)
+            .expect("SingletonServer");
+        assert!(!singleton.replicable);
+        assert!(singleton.has_service);
+        assert!(!singleton.run_to_completion);
+
+        let server = registry.get(SERVER_NAME).expect("Server");
+        assert!(server.replicable);
+        assert!(server.has_service);
+        assert!(!server.run_to_completion);
+
+        let task = registry.get(TASK_NAME).expect("Task");
+        assert!(task.replicable);
+        assert!(!task.has_service);
+        assert!(task.run_to_completion);
+
+        let worker = registry.get(WORKER_NAME).expect("Worker");
+        assert!(worker.replicable);
+        assert!(!worker.has_service);
+        assert!(!worker.run_to_completion);
+    }
+
+    #[test]
+    fn test_workload_info() {
+        assert_eq!(
+            workload_registry().get(TASK_NAME),
+            workload_info(TASK_NAME).ok().as_ref()
+        );
+
+        let err = workload_info("core.oam.dev/v1alpha1.Daemon").expect_err("unknown type");
+        assert!(err
+            .to_string()
+            .contains("unsupported workload type core.oam.dev/v1alpha1.Daemon"));
+        assert!(err.to_string().contains(SERVER_NAME));
+
+        assert!(is_core_workload_type(WORKER_NAME));
+        assert!(!is_core_workload_type("openfaas.com/v1alpha2.Function"));
+    }
+}
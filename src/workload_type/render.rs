@@ -0,0 +1,81 @@
+use failure::Error;
+use kube::{client::APIClient, config::Configuration};
+
+use crate::schematic::component::Component;
+use crate::workload_type::{
+    core_workload_type, is_core_workload_type, ParamMap, WorkloadMetadata, WorkloadType,
+};
+
+/// The kinds of cluster-scoped objects, which have no namespace.
+const CLUSTER_SCOPED_KINDS: &[&str] = &[
+    "ClusterRole",
+    "ClusterRoleBinding",
+    "CustomResourceDefinition",
+    "Namespace",
+    "PersistentVolume",
+    "PriorityClass",
+    "StorageClass",
+];
+
+/// Render the Kubernetes objects Rudr would create for an instance of a component.
+///
+/// Objects are returned in a stable order: the workload object (Deployment, StatefulSet,
+/// Job or CronJob), then its Service, then any ConfigMaps, then its PodDisruptionBudget.
+/// Traits are attached by an application configuration rather than the component, so
+/// they are not rendered here.
+pub fn render_all(
+    component: &Component,
+    instance_name: &str,
+    params: ParamMap,
+) -> Result<Vec<serde_json::Value>, Error> {
+    if !is_core_workload_type(component.workload_type.as_str()) {
+        return Err(format_err!(
+            "workload type {} cannot be rendered outside of a cluster",
+            component.workload_type
+        ));
+    }
+    let meta = WorkloadMetadata {
+        name: instance_name.to_string(),
+        component_name: instance_name.to_string(),
+        instance_name: instance_name.to_string(),
+        namespace: String::new(),
+        definition: component.clone(),
+        // Rendering never sends a request, so the client is never used.
+        client: APIClient::new(Configuration {
+            base_path: String::new(),
+            client: reqwest::Client::new(),
+        }),
+        params,
+        owner_ref: None,
+        annotations: None,
+    };
+    core_workload_type(meta)?.render()
+}
+
+/// Render the Kubernetes objects for an instance of a component, as `render_all` does,
+/// in the given namespace.
+pub fn render_all_in_namespace(
+    component: &Component,
+    instance_name: &str,
+    params: ParamMap,
+    namespace: &str,
+) -> Result<Vec<serde_json::Value>, Error> {
+    let mut objects = render_all(component, instance_name, params)?;
+    set_namespace(&mut objects, namespace)?;
+    Ok(objects)
+}
+
+/// Set the namespace of rendered objects, such as those `render_all` or a trait renders.
+///
+/// The namespace must be a DNS-1123 label. Cluster-scoped objects are left without one.
+pub fn set_namespace(objects: &mut [serde_json::Value], namespace: &str) -> Result<(), Error> {
+    Component::validate_name(namespace).map_err(|e| format_err!("invalid namespace: {}", e))?;
+    for object in objects.iter_mut() {
+        let kind = object["kind"].as_str().unwrap_or_default();
+        if CLUSTER_SCOPED_KINDS.contains(&kind) {
+            continue;
+        }
+        object["metadata"]["namespace"] = serde_json::json!(namespace);
+    }
+    Ok(())
+}
@@ -3,7 +3,8 @@ use k8s_openapi::apimachinery::pkg::apis::meta::v1 as meta;
 
 use crate::workload_type::workload_builder::{DeploymentBuilder, ServiceBuilder};
 use crate::workload_type::{
-    InstigatorResult, KubeName, StatusResult, WorkloadMetadata, WorkloadType,
+    InstigatorResult, KubeName, RenderResult, StatusResult, ValidationResult, WorkloadMetadata,
+    WorkloadType,
 };
 
 use crate::workload_type::statefulset_builder::StatefulsetBuilder;
@@ -19,20 +20,38 @@ impl ReplicatedServer {
     fn labels(&self) -> BTreeMap<String, String> {
         self.meta.labels("Service")
     }
-    fn add_deployment_builder(&self) -> InstigatorResult {
+    fn deployment_builder(&self) -> DeploymentBuilder {
         DeploymentBuilder::new(self.kube_name(), self.meta.definition.clone())
             .parameter_map(self.meta.params.clone())
             .labels(self.labels())
             .annotations(self.meta.annotations.clone())
             .owner_ref(self.meta.owner_ref.clone())
-            .do_request(self.meta.client.clone(), self.meta.namespace.clone(), "add")
     }
-    fn add_service_builder(&self) -> InstigatorResult {
+    fn service_builder(&self) -> ServiceBuilder {
         ServiceBuilder::new(self.kube_name(), self.meta.definition.clone())
+            .parameter_map(self.meta.params.clone())
             .labels(self.labels())
             .select_labels(self.meta.select_labels())
             .owner_ref(self.meta.owner_ref.clone())
-            .do_request(self.meta.client.clone(), self.meta.namespace.clone(), "add")
+            .target_port_by_name(
+                self.meta
+                    .definition
+                    .targets_port_by_name(self.meta.params.clone()),
+            )
+    }
+    fn add_deployment_builder(&self) -> InstigatorResult {
+        self.deployment_builder().do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "add",
+        )
+    }
+    fn add_service_builder(&self) -> InstigatorResult {
+        self.service_builder().do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "add",
+        )
     }
 }
 
@@ -71,30 +90,23 @@ impl WorkloadType for ReplicatedServer {
         //pre create config_map
         self.meta.create_config_maps("Service")?;
         self.add_deployment_builder()?;
+        self.meta.create_pod_disruption_budget()?;
         self.add_service_builder()
     }
     fn modify(&self) -> InstigatorResult {
         //TODO update config_map
-        DeploymentBuilder::new(self.kube_name(), self.meta.definition.clone())
-            .parameter_map(self.meta.params.clone())
-            .labels(self.labels())
-            .annotations(self.meta.annotations.clone())
-            .owner_ref(self.meta.owner_ref.clone())
-            .do_request(
-                self.meta.client.clone(),
-                self.meta.namespace.clone(),
-                "modify",
-            )?;
+        self.deployment_builder().do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "modify",
+        )?;
+        self.meta.apply_pod_disruption_budget()?;
 
-        ServiceBuilder::new(self.kube_name(), self.meta.definition.clone())
-            .labels(self.labels())
-            .select_labels(self.meta.select_labels())
-            .owner_ref(self.meta.owner_ref.clone())
-            .do_request(
-                self.meta.client.clone(),
-                self.meta.namespace.clone(),
-                "modify",
-            )
+        self.service_builder().do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "modify",
+        )
     }
     fn delete(&self) -> InstigatorResult {
         DeploymentBuilder::new(self.kube_name(), self.meta.definition.clone()).do_request(
@@ -102,6 +114,7 @@ impl WorkloadType for ReplicatedServer {
             self.meta.namespace.clone(),
             "delete",
         )?;
+        self.meta.delete_pod_disruption_budget()?;
 
         ServiceBuilder::new(self.kube_name(), self.meta.definition.clone()).do_request(
             self.meta.client.clone(),
@@ -109,6 +122,13 @@ impl WorkloadType for ReplicatedServer {
             "delete",
         )
     }
+    fn render(&self) -> RenderResult {
+        let mut objects = vec![self.deployment_builder().to_deployment_json()?];
+        objects.extend(self.service_builder().to_service_json()?);
+        objects.extend(self.meta.config_maps_json("Service")?);
+        objects.extend(self.meta.pod_disruption_budget_json()?);
+        Ok(objects)
+    }
     fn status(&self) -> StatusResult {
         let mut resources = BTreeMap::new();
 
@@ -150,20 +170,38 @@ impl SingletonServer {
     fn labels(&self) -> BTreeMap<String, String> {
         self.meta.labels("SingletonServer")
     }
-    fn add_statefulset_deployment_builder(&self) -> InstigatorResult {
+    fn statefulset_builder(&self) -> StatefulsetBuilder {
         StatefulsetBuilder::new(self.kube_name(), self.meta.definition.clone())
             .parameter_map(self.meta.params.clone())
             .labels(self.labels())
             .annotations(self.meta.annotations.clone())
             .owner_ref(self.meta.owner_ref.clone())
-            .do_request(self.meta.client.clone(), self.meta.namespace.clone(), "add")
     }
-    fn add_service_builder(&self) -> InstigatorResult {
+    fn service_builder(&self) -> ServiceBuilder {
         ServiceBuilder::new(self.kube_name(), self.meta.definition.clone())
+            .parameter_map(self.meta.params.clone())
             .labels(self.labels())
             .select_labels(self.meta.select_labels())
             .owner_ref(self.meta.owner_ref.clone())
-            .do_request(self.meta.client.clone(), self.meta.namespace.clone(), "add")
+            .target_port_by_name(
+                self.meta
+                    .definition
+                    .targets_port_by_name(self.meta.params.clone()),
+            )
+    }
+    fn add_statefulset_deployment_builder(&self) -> InstigatorResult {
+        self.statefulset_builder().do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "add",
+        )
+    }
+    fn add_service_builder(&self) -> InstigatorResult {
+        self.service_builder().do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "add",
+        )
     }
 }
 
@@ -176,7 +214,7 @@ impl KubeName for SingletonServer {
 impl WorkloadType for SingletonServer {
     fn add(&self) -> InstigatorResult {
         //pre create config_map
-        self.meta.create_config_maps("singleton-service")?;
+        self.meta.create_config_maps("SingletonServer")?;
 
         // Create deployment
         self.add_statefulset_deployment_builder()?;
@@ -206,6 +244,12 @@ impl WorkloadType for SingletonServer {
             "delete",
         )
     }
+    fn render(&self) -> RenderResult {
+        let mut objects = vec![self.statefulset_builder().to_statefulset_json()?];
+        objects.extend(self.service_builder().to_service_json()?);
+        objects.extend(self.meta.config_maps_json("SingletonServer")?);
+        Ok(objects)
+    }
     fn status(&self) -> StatusResult {
         let mut resources = BTreeMap::new();
 
@@ -239,11 +283,138 @@ impl WorkloadType for SingletonServer {
     }
 }
 
+/// StatefulService is a replicated server where each replica keeps its own durable storage.
+///
+/// It is implemented as a Kubernetes StatefulSet governed by a headless Service. Durable
+/// volumes are rendered as volumeClaimTemplates, so every replica gets its own claim.
+pub struct StatefulService {
+    pub meta: WorkloadMetadata,
+}
+
+impl StatefulService {
+    fn labels(&self) -> BTreeMap<String, String> {
+        self.meta.labels("StatefulService")
+    }
+    fn statefulset_builder(&self) -> StatefulsetBuilder {
+        StatefulsetBuilder::new(self.kube_name(), self.meta.definition.clone())
+            .parameter_map(self.meta.params.clone())
+            .labels(self.labels())
+            .annotations(self.meta.annotations.clone())
+            .owner_ref(self.meta.owner_ref.clone())
+            .service_name(self.kube_name())
+            .volume_claim_templates(true)
+    }
+    fn service_builder(&self) -> ServiceBuilder {
+        ServiceBuilder::new(self.kube_name(), self.meta.definition.clone())
+            .parameter_map(self.meta.params.clone())
+            .labels(self.labels())
+            .select_labels(self.meta.select_labels())
+            .owner_ref(self.meta.owner_ref.clone())
+            .target_port_by_name(
+                self.meta
+                    .definition
+                    .targets_port_by_name(self.meta.params.clone()),
+            )
+    }
+}
+
+impl KubeName for StatefulService {
+    fn kube_name(&self) -> String {
+        self.meta.instance_name.to_string()
+    }
+}
+
+impl WorkloadType for StatefulService {
+    fn add(&self) -> InstigatorResult {
+        //pre create config_map
+        self.meta.create_config_maps("StatefulService")?;
+        self.statefulset_builder().do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "add",
+        )?;
+        self.meta.create_pod_disruption_budget()?;
+        self.service_builder().do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "add",
+        )
+    }
+    fn modify(&self) -> InstigatorResult {
+        self.statefulset_builder().do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "modify",
+        )?;
+        self.meta.apply_pod_disruption_budget()?;
+        self.service_builder().do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "modify",
+        )
+    }
+    fn delete(&self) -> InstigatorResult {
+        StatefulsetBuilder::new(self.kube_name(), self.meta.definition.clone()).do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "delete",
+        )?;
+        self.meta.delete_pod_disruption_budget()?;
+
+        ServiceBuilder::new(self.kube_name(), self.meta.definition.clone()).do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "delete",
+        )
+    }
+    fn render(&self) -> RenderResult {
+        let mut objects = vec![self.statefulset_builder().to_statefulset_json()?];
+        objects.extend(self.service_builder().to_service_json()?);
+        objects.extend(self.meta.config_maps_json("StatefulService")?);
+        objects.extend(self.meta.pod_disruption_budget_json()?);
+        Ok(objects)
+    }
+    fn status(&self) -> StatusResult {
+        let mut resources = BTreeMap::new();
+
+        let key = "statefulset/".to_string() + self.kube_name().as_str();
+        let state = StatefulsetBuilder::new(self.kube_name(), self.meta.definition.clone())
+            .status(self.meta.client.clone(), self.meta.namespace.clone())
+            .unwrap_or_else(|e| e.to_string());
+        resources.insert(key, state);
+
+        let svc_key = "service/".to_string() + self.kube_name().as_str();
+        let svc_state = ServiceBuilder::new(self.kube_name(), self.meta.definition.clone())
+            .get_status(self.meta.client.clone(), self.meta.namespace.clone())
+            .unwrap_or_else(|e| e.to_string());
+        resources.insert(svc_key, svc_state);
+
+        Ok(resources)
+    }
+    /// A StatefulSet requires a headless Service to give its replicas stable network identities.
+    fn validate(&self) -> ValidationResult {
+        if !self.meta.definition.is_headless(self.meta.params.clone()) {
+            return Err(format_err!(
+                "StatefulService {} requires a headless Service: set the workload setting headless to true",
+                self.kube_name()
+            ));
+        }
+        if self.meta.definition.listening_port().is_none() {
+            return Err(format_err!(
+                "StatefulService {} requires a port for its headless Service",
+                self.kube_name()
+            ));
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use kube::{client::APIClient, config::Configuration};
 
-    use crate::schematic::component::Component;
+    use crate::schematic::component::{Component, Container, Port, WorkloadSetting};
+    use crate::schematic::component_test::workload_setting;
     use crate::workload_type::{server::*, KubeName, WorkloadMetadata};
 
     use std::collections::BTreeMap;
@@ -299,6 +470,47 @@ mod test {
         assert_eq!("Service", rs.labels().get("oam.dev/workload-type").unwrap());
     }
 
+    fn stateful_service(workload_settings: Vec<WorkloadSetting>) -> StatefulService {
+        StatefulService {
+            meta: WorkloadMetadata {
+                name: "de".into(),
+                component_name: "db".into(),
+                instance_name: "db".into(),
+                namespace: "tests".into(),
+                definition: Component {
+                    containers: vec![Container {
+                        name: "db".into(),
+                        image: "postgres:11".into(),
+                        ports: vec![Port::basic("pg".into(), 5432)],
+                        ..Default::default()
+                    }],
+                    workload_settings,
+                    ..Default::default()
+                },
+                annotations: None,
+                params: BTreeMap::new(),
+                client: APIClient::new(mock_kube_config()),
+                owner_ref: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_stateful_service_requires_headless_service() {
+        let headless = workload_setting("headless", serde_json::json!(true));
+        let svc = stateful_service(vec![headless]);
+        assert!(svc.validate().is_ok());
+        assert_eq!(
+            "StatefulService",
+            svc.labels().get("oam.dev/workload-type").unwrap()
+        );
+
+        let err = stateful_service(vec![])
+            .validate()
+            .expect_err("a StatefulService without a headless Service must not validate");
+        assert!(err.to_string().contains("headless"));
+    }
+
     /// This mock builds a KubeConfig that will not be able to make any requests.
     fn mock_kube_config() -> Configuration {
         Configuration {
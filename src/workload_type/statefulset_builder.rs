@@ -20,6 +20,8 @@ pub(crate) struct StatefulsetBuilder {
     restart_policy: String,
     owner_ref: Option<Vec<meta::OwnerReference>>,
     param_vals: ParamMap,
+    service_name: Option<String>,
+    volume_claim_templates: bool,
 }
 
 impl StatefulsetBuilder {
@@ -33,6 +35,8 @@ impl StatefulsetBuilder {
             restart_policy: "Always".to_string(),
             owner_ref: None,
             param_vals: BTreeMap::new(),
+            service_name: None,
+            volume_claim_templates: false,
         }
     }
     /// Add labels
@@ -59,35 +63,76 @@ impl StatefulsetBuilder {
         self
     }
 
-    pub fn to_statefulset(&self) -> apps::StatefulSet {
-        apps::StatefulSet {
+    /// Set the name of the Service that governs the StatefulSet.
+    pub fn service_name(mut self, service_name: String) -> Self {
+        self.service_name = Some(service_name);
+        self
+    }
+
+    /// Render durable volumes as volumeClaimTemplates, giving each replica its own storage.
+    pub fn volume_claim_templates(mut self, enabled: bool) -> Self {
+        self.volume_claim_templates = enabled;
+        self
+    }
+
+    fn to_pod_spec(&self) -> Result<api::PodSpec, failure::Error> {
+        let mut pod_spec = self
+            .component
+            .to_pod_spec_with_policy(self.param_vals.clone(), self.restart_policy.clone())?;
+        pod_spec.affinity = self.component.to_affinity(&self.labels);
+        if self.volume_claim_templates {
+            // Volumes provided by a claim template must not also be declared on the pod.
+            let claimed: Vec<String> = self
+                .component
+                .to_volume_claim_templates()
+                .iter()
+                .filter_map(|pvc| pvc.metadata.as_ref().and_then(|m| m.name.clone()))
+                .collect();
+            pod_spec.volumes = pod_spec.volumes.map(|vols| {
+                vols.into_iter()
+                    .filter(|v| !claimed.contains(&v.name))
+                    .collect()
+            });
+        }
+        Ok(pod_spec)
+    }
+
+    pub fn to_statefulset(&self) -> Result<apps::StatefulSet, failure::Error> {
+        let volume_claim_templates = if self.volume_claim_templates {
+            Some(self.component.to_volume_claim_templates())
+        } else {
+            None
+        };
+        Ok(apps::StatefulSet {
             metadata: workload_builder::form_metadata(
                 self.name.clone(),
                 self.labels.clone(),
                 self.owner_ref.clone(),
             ),
             spec: Some(apps::StatefulSetSpec {
+                replicas: if self.component.is_scalable() {
+                    self.component.replica_count(self.param_vals.clone())?
+                } else {
+                    None
+                },
                 selector: meta::LabelSelector {
                     match_labels: Some(self.labels.clone()),
                     ..Default::default()
                 },
-                template: api::PodTemplateSpec {
-                    metadata: Some(meta::ObjectMeta {
-                        name: Some(self.name.clone()),
-                        labels: Some(self.labels.clone()),
-                        annotations: self.annotations.clone(),
-                        owner_references: self.owner_ref.clone(),
-                        ..Default::default()
-                    }),
-                    spec: Some(self.component.to_pod_spec_with_policy(
-                        self.param_vals.clone(),
-                        self.restart_policy.clone(),
-                    )),
-                },
+                template: workload_builder::pod_template_spec(
+                    &self.component,
+                    &self.name,
+                    &self.labels,
+                    &self.annotations,
+                    &self.owner_ref,
+                    self.to_pod_spec()?,
+                ),
+                service_name: self.service_name.clone().unwrap_or_default(),
+                volume_claim_templates,
                 ..Default::default()
             }),
             ..Default::default()
-        }
+        })
     }
 
     pub fn status(self, client: APIClient, namespace: String) -> Result<String, kube::Error> {
@@ -109,8 +154,13 @@ impl StatefulsetBuilder {
         Ok(state)
     }
 
+    /// Serialize the StatefulSet, with the fields `to_workload_json` adds.
+    pub fn to_statefulset_json(&self) -> Result<serde_json::Value, failure::Error> {
+        workload_builder::to_workload_json(&self.to_statefulset()?, &self.component, &self.labels)
+    }
+
     pub fn do_request(self, client: APIClient, namespace: String, phase: &str) -> InstigatorResult {
-        let statefulset = self.to_statefulset();
+        let statefulset = self.to_statefulset_json()?;
         match phase {
             "modify" => {
                 let pp = kube::api::PatchParams::default();
@@ -136,3 +186,116 @@ impl StatefulsetBuilder {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::schematic::component::{Component, Container, Disk, Resources, Volume};
+    use crate::workload_type::statefulset_builder::*;
+    use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+
+    fn stateful_component() -> Component {
+        Component {
+            containers: vec![Container {
+                name: "db".into(),
+                image: "postgres:11".into(),
+                resources: Some(Resources {
+                    volumes: Some(vec![
+                        Volume {
+                            name: "data".into(),
+                            mount_path: "/var/lib/postgresql/data".into(),
+                            access_mode: Default::default(),
+                            sharing_policy: Default::default(),
+                            disk: Some(Disk {
+                                required: "10G".into(),
+                                ephemeral: false,
+                            }),
+                            inline_content: None,
+                            sub_path: None,
+                            mount_propagation: None,
+                        },
+                        Volume {
+                            name: "scratch".into(),
+                            mount_path: "/tmp".into(),
+                            access_mode: Default::default(),
+                            sharing_policy: Default::default(),
+                            disk: Some(Disk {
+                                required: "1G".into(),
+                                ephemeral: true,
+                            }),
+                            inline_content: None,
+                            sub_path: None,
+                            mount_propagation: None,
+                        },
+                    ]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_statefulset_volume_claim_templates() {
+        let mut labels = BTreeMap::new();
+        labels.insert("oam.dev/instance-name".to_string(), "db".to_string());
+        let sts = StatefulsetBuilder::new("db".into(), stateful_component())
+            .labels(labels)
+            .service_name("db".into())
+            .volume_claim_templates(true)
+            .to_statefulset()
+            .expect("statefulset");
+        let spec = sts.spec.expect("spec");
+        assert_eq!("db", spec.service_name);
+
+        let templates = spec.volume_claim_templates.expect("volume claim templates");
+        assert_eq!(1, templates.len());
+        let pvc = templates.get(0).expect("data template");
+        assert_eq!(
+            Some("data".to_string()),
+            pvc.metadata.clone().expect("metadata").name
+        );
+        let pvc_spec = pvc.spec.clone().expect("pvc spec");
+        assert_eq!(
+            Some(vec!["ReadWriteOnce".to_string()]),
+            pvc_spec.access_modes
+        );
+        assert_eq!(
+            Some(&Quantity("10G".into())),
+            pvc_spec
+                .resources
+                .expect("resources")
+                .requests
+                .expect("requests")
+                .get("storage")
+        );
+
+        // Only the ephemeral volume remains on the pod itself.
+        let volumes = spec
+            .template
+            .spec
+            .expect("pod spec")
+            .volumes
+            .expect("volumes");
+        assert_eq!(1, volumes.len());
+        assert_eq!("scratch", volumes.get(0).expect("scratch").name);
+    }
+
+    #[test]
+    fn test_statefulset_without_claim_templates() {
+        let sts = StatefulsetBuilder::new("db".into(), stateful_component())
+            .to_statefulset()
+            .expect("statefulset");
+        let spec = sts.spec.expect("spec");
+        assert!(spec.volume_claim_templates.is_none());
+        assert_eq!(
+            2,
+            spec.template
+                .spec
+                .expect("pod spec")
+                .volumes
+                .expect("volumes")
+                .len()
+        );
+    }
+}
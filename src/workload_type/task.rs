@@ -1,6 +1,8 @@
+use crate::schematic::component::Component;
 use crate::workload_type::{
-    workload_builder::{JobBuilder, WorkloadMetadata},
-    InstigatorResult, KubeName, StatusResult, WorkloadType,
+    workload_builder::{CronJobBuilder, JobBuilder, WorkloadMetadata},
+    InstigatorResult, KubeName, ParamMap, RenderResult, StatusResult, ValidationResult,
+    WorkloadType, REPLICABLE_TASK_NAME,
 };
 
 use std::collections::BTreeMap;
@@ -22,32 +24,40 @@ impl ReplicatedTask {
     fn labels(&self) -> BTreeMap<String, String> {
         self.meta.labels("Task")
     }
+    fn completion_mode(&self) -> Result<Option<String>, failure::Error> {
+        task_completion_mode(
+            self.kube_name().as_str(),
+            &self.meta.definition,
+            &self.meta.params,
+        )
+    }
+    fn job_builder(&self) -> Result<JobBuilder, failure::Error> {
+        Ok(
+            JobBuilder::new(self.kube_name(), self.meta.definition.clone())
+                .parameter_map(self.meta.params.clone())
+                .labels(self.labels())
+                .annotations(self.meta.annotations.clone())
+                .parallelism(self.replica_count.unwrap_or(1))
+                .completion_mode(self.completion_mode()?)
+                .owner_ref(self.meta.owner_ref.clone())
+                .restart_policy("Never".to_string()),
+        )
+    }
 }
 
 impl WorkloadType for ReplicatedTask {
     fn add(&self) -> InstigatorResult {
-        JobBuilder::new(self.kube_name(), self.meta.definition.clone())
-            .parameter_map(self.meta.params.clone())
-            .labels(self.labels())
-            .annotations(self.meta.annotations.clone())
-            .parallelism(self.replica_count.unwrap_or(1))
-            .owner_ref(self.meta.owner_ref.clone())
-            .restart_policy("Never".to_string())
+        //pre create config_map
+        self.meta.create_config_maps("Task")?;
+        self.job_builder()?
             .do_request(self.meta.client.clone(), self.meta.namespace.clone(), "add")
     }
     fn modify(&self) -> InstigatorResult {
-        JobBuilder::new(self.kube_name(), self.meta.definition.clone())
-            .parameter_map(self.meta.params.clone())
-            .labels(self.labels())
-            .annotations(self.meta.annotations.clone())
-            .parallelism(self.replica_count.unwrap_or(1))
-            .owner_ref(self.meta.owner_ref.clone())
-            .restart_policy("Never".to_string())
-            .do_request(
-                self.meta.client.clone(),
-                self.meta.namespace.clone(),
-                "modify",
-            )
+        self.job_builder()?.do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "modify",
+        )
     }
     fn delete(&self) -> InstigatorResult {
         JobBuilder::new(self.kube_name(), self.meta.definition.clone()).do_request(
@@ -65,6 +75,14 @@ impl WorkloadType for ReplicatedTask {
 
         Ok(resources)
     }
+    fn render(&self) -> RenderResult {
+        let mut objects = vec![self.job_builder()?.to_job_json()?];
+        objects.extend(self.meta.config_maps_json("Task")?);
+        Ok(objects)
+    }
+    fn validate(&self) -> ValidationResult {
+        self.completion_mode().map(|_| ())
+    }
 }
 
 /// SingletonTask represents a non-daemon process.
@@ -82,29 +100,108 @@ impl SingletonTask {
     fn labels(&self) -> BTreeMap<String, String> {
         self.meta.labels("SingletonTask")
     }
-}
-impl WorkloadType for SingletonTask {
-    fn add(&self) -> InstigatorResult {
+    fn job_builder(&self) -> JobBuilder {
         JobBuilder::new(self.kube_name(), self.meta.definition.clone())
             .parameter_map(self.meta.params.clone())
             .labels(self.labels())
             .annotations(self.meta.annotations.clone())
             .owner_ref(self.meta.owner_ref.clone())
             .restart_policy("Never".to_string())
+    }
+}
+impl WorkloadType for SingletonTask {
+    fn add(&self) -> InstigatorResult {
+        //pre create config_map
+        self.meta.create_config_maps("SingletonTask")?;
+        self.job_builder()
             .do_request(self.meta.client.clone(), self.meta.namespace.clone(), "add")
     }
     fn modify(&self) -> InstigatorResult {
-        JobBuilder::new(self.kube_name(), self.meta.definition.clone())
-            .parameter_map(self.meta.params.clone())
-            .labels(self.labels())
-            .annotations(self.meta.annotations.clone())
-            .owner_ref(self.meta.owner_ref.clone())
-            .restart_policy("Never".to_string())
-            .do_request(
-                self.meta.client.clone(),
-                self.meta.namespace.clone(),
-                "modify",
-            )
+        self.job_builder().do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "modify",
+        )
+    }
+    fn delete(&self) -> InstigatorResult {
+        JobBuilder::new(self.kube_name(), self.meta.definition.clone()).do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "delete",
+        )
+    }
+    fn status(&self) -> StatusResult {
+        let mut resources = BTreeMap::new();
+        let key = "job/".to_string() + self.kube_name().as_str();
+        let state = JobBuilder::new(self.kube_name(), self.meta.definition.clone())
+            .get_status(self.meta.client.clone(), self.meta.namespace.clone());
+        resources.insert(key.clone(), state);
+
+        Ok(resources)
+    }
+    fn render(&self) -> RenderResult {
+        let mut objects = vec![self.job_builder().to_job_json()?];
+        objects.extend(self.meta.config_maps_json("SingletonTask")?);
+        Ok(objects)
+    }
+}
+
+/// ReplicableTask represents a parallel, parameterized non-daemon process.
+///
+/// It is currently implemented as a Kubernetes Job. The number of completions is
+/// read from the `completions` parameter (default 1) and the parallelism from the
+/// `parallelism` parameter, which defaults to the number of completions.
+pub struct ReplicableTask {
+    pub meta: WorkloadMetadata,
+}
+impl KubeName for ReplicableTask {
+    fn kube_name(&self) -> String {
+        self.meta.instance_name.to_string()
+    }
+}
+impl ReplicableTask {
+    fn labels(&self) -> BTreeMap<String, String> {
+        self.meta.labels("ReplicableTask")
+    }
+    /// Resolve (parallelism, completions) from the parameters.
+    fn counts(&self) -> Result<(i32, i32), failure::Error> {
+        replicable_task_counts(self.kube_name().as_str(), &self.meta.params)
+    }
+    fn completion_mode(&self) -> Result<Option<String>, failure::Error> {
+        task_completion_mode(
+            self.kube_name().as_str(),
+            &self.meta.definition,
+            &self.meta.params,
+        )
+    }
+    fn job_builder(&self) -> Result<JobBuilder, failure::Error> {
+        let (parallelism, completions) = self.counts()?;
+        Ok(
+            JobBuilder::new(self.kube_name(), self.meta.definition.clone())
+                .parameter_map(self.meta.params.clone())
+                .labels(self.labels())
+                .annotations(self.meta.annotations.clone())
+                .parallelism(parallelism)
+                .completions(completions)
+                .completion_mode(self.completion_mode()?)
+                .owner_ref(self.meta.owner_ref.clone())
+                .restart_policy("Never".to_string()),
+        )
+    }
+}
+impl WorkloadType for ReplicableTask {
+    fn add(&self) -> InstigatorResult {
+        //pre create config_map
+        self.meta.create_config_maps("ReplicableTask")?;
+        self.job_builder()?
+            .do_request(self.meta.client.clone(), self.meta.namespace.clone(), "add")
+    }
+    fn modify(&self) -> InstigatorResult {
+        self.job_builder()?.do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "modify",
+        )
     }
     fn delete(&self) -> InstigatorResult {
         JobBuilder::new(self.kube_name(), self.meta.definition.clone()).do_request(
@@ -122,14 +219,200 @@ impl WorkloadType for SingletonTask {
 
         Ok(resources)
     }
+    fn render(&self) -> RenderResult {
+        let mut objects = vec![self.job_builder()?.to_job_json()?];
+        objects.extend(self.meta.config_maps_json("ReplicableTask")?);
+        Ok(objects)
+    }
+    fn validate(&self) -> ValidationResult {
+        self.counts()?;
+        self.completion_mode().map(|_| ())
+    }
+}
+
+/// Resolve the (parallelism, completions) of a ReplicableTask from its parameters.
+fn replicable_task_counts(
+    instance_name: &str,
+    params: &ParamMap,
+) -> Result<(i32, i32), failure::Error> {
+    let completions = count_param(instance_name, params, "completions")?.unwrap_or(1);
+    let parallelism = count_param(instance_name, params, "parallelism")?.unwrap_or(completions);
+    Ok((parallelism, completions))
+}
+
+/// Resolve the completion mode of the Job of a Task or ReplicableTask.
+///
+/// An Indexed Job gives each pod an index below its number of completions, so it needs
+/// the `completions` parameter, which only a ReplicableTask reads.
+fn task_completion_mode(
+    instance_name: &str,
+    component: &Component,
+    params: &ParamMap,
+) -> Result<Option<String>, failure::Error> {
+    let mode = component.completion_mode(params.clone())?;
+    if mode.as_ref().map(|m| m.as_str()) != Some("Indexed") {
+        return Ok(mode);
+    }
+    if component.workload_type != REPLICABLE_TASK_NAME {
+        return Err(format_err!(
+            "Task {} cannot use Indexed completion mode, which requires completions; use a ReplicableTask",
+            instance_name
+        ));
+    }
+    if count_param(instance_name, params, "completions")?.is_none() {
+        return Err(format_err!(
+            "ReplicableTask {} uses Indexed completion mode, which requires the completions parameter",
+            instance_name
+        ));
+    }
+    Ok(mode)
+}
+
+fn count_param(
+    instance_name: &str,
+    params: &ParamMap,
+    name: &str,
+) -> Result<Option<i32>, failure::Error> {
+    match params.get(name) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(v) => match v.as_i64() {
+            Some(i) if i > 0 && i <= i64::from(std::i32::MAX) => Ok(Some(i as i32)),
+            _ => Err(format_err!(
+                "ReplicableTask {} requires {} to be a positive integer, got {}",
+                instance_name,
+                name,
+                v
+            )),
+        },
+    }
+}
+
+/// ScheduledTask represents a non-daemon process that runs on a cron schedule.
+///
+/// It is currently implemented as a Kubernetes CronJob. The `schedule` workload
+/// setting holds the cron expression, and the optional `concurrencyPolicy`
+/// workload setting is passed through to the CronJob.
+pub struct ScheduledTask {
+    pub meta: WorkloadMetadata,
+}
+impl KubeName for ScheduledTask {
+    fn kube_name(&self) -> String {
+        self.meta.instance_name.to_string()
+    }
+}
+impl ScheduledTask {
+    fn labels(&self) -> BTreeMap<String, String> {
+        self.meta.labels("ScheduledTask")
+    }
+    fn schedule(&self) -> Result<Option<String>, failure::Error> {
+        Ok(self
+            .meta
+            .get_workload_setting("schedule")?
+            .and_then(|s| s.as_str().map(|s| s.to_string())))
+    }
+    fn concurrency_policy(&self) -> Result<Option<String>, failure::Error> {
+        Ok(self
+            .meta
+            .get_workload_setting("concurrencyPolicy")?
+            .and_then(|s| s.as_str().map(|s| s.to_string())))
+    }
+    fn cron_job_builder(&self) -> Result<CronJobBuilder, failure::Error> {
+        Ok(CronJobBuilder::new(
+            self.kube_name(),
+            self.meta.definition.clone(),
+            self.schedule()?.unwrap_or_default(),
+        )
+        .parameter_map(self.meta.params.clone())
+        .labels(self.labels())
+        .annotations(self.meta.annotations.clone())
+        .concurrency_policy(self.concurrency_policy()?)
+        .owner_ref(self.meta.owner_ref.clone())
+        .restart_policy("Never".to_string()))
+    }
+}
+impl WorkloadType for ScheduledTask {
+    fn add(&self) -> InstigatorResult {
+        //pre create config_map
+        self.meta.create_config_maps("ScheduledTask")?;
+        self.cron_job_builder()?.do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "add",
+        )
+    }
+    fn modify(&self) -> InstigatorResult {
+        self.cron_job_builder()?.do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "modify",
+        )
+    }
+    fn delete(&self) -> InstigatorResult {
+        self.cron_job_builder()?.do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "delete",
+        )
+    }
+    fn status(&self) -> StatusResult {
+        let mut resources = BTreeMap::new();
+        let key = "cronjob/".to_string() + self.kube_name().as_str();
+        let state = self
+            .cron_job_builder()?
+            .get_status(self.meta.client.clone(), self.meta.namespace.clone());
+        resources.insert(key.clone(), state);
+
+        Ok(resources)
+    }
+    fn render(&self) -> RenderResult {
+        let mut objects = vec![self.cron_job_builder()?.to_cron_job_json()?];
+        objects.extend(self.meta.config_maps_json("ScheduledTask")?);
+        Ok(objects)
+    }
+    fn validate(&self) -> ValidationResult {
+        let schedule = self.schedule()?.ok_or_else(|| {
+            format_err!(
+                "ScheduledTask {} requires a schedule workload setting",
+                self.kube_name()
+            )
+        })?;
+        validate_cron_schedule(schedule.as_str())?;
+        match self.concurrency_policy()? {
+            None => Ok(()),
+            Some(ref p) if p == "Allow" || p == "Forbid" || p == "Replace" => Ok(()),
+            Some(p) => Err(format_err!(
+                "concurrencyPolicy {} must be one of Allow, Forbid or Replace",
+                p
+            )),
+        }
+    }
+}
+
+/// Check that a cron expression has the five fields Kubernetes expects.
+///
+/// The fields themselves are checked by the Kubernetes API server.
+pub fn validate_cron_schedule(schedule: &str) -> ValidationResult {
+    let fields = schedule.split_whitespace().count();
+    if fields != 5 {
+        return Err(format_err!(
+            "cron schedule \"{}\" must have 5 fields, found {}",
+            schedule,
+            fields
+        ));
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod test {
     use kube::{client::APIClient, config::Configuration};
 
-    use crate::schematic::component::Component;
-    use crate::workload_type::{task::*, workload_builder::WorkloadMetadata, KubeName};
+    use crate::schematic::component::{Component, WorkloadSetting};
+    use crate::schematic::component_test::workload_setting;
+    use crate::schematic::parameter::ParameterType;
+    use crate::workload_type::{
+        task::*, workload_builder::WorkloadMetadata, KubeName, WorkloadType,
+    };
 
     use std::collections::BTreeMap;
 
@@ -181,6 +464,158 @@ mod test {
         assert_eq!("Task", task.labels().get("oam.dev/workload-type").unwrap());
     }
 
+    #[test]
+    fn test_scheduled_task_validate() {
+        let cli = APIClient::new(mock_kube_config());
+
+        let mut task = ScheduledTask {
+            meta: WorkloadMetadata {
+                name: "mytask".into(),
+                component_name: "taskrunner".into(),
+                instance_name: "taskinstance".into(),
+                namespace: "tests".into(),
+                definition: Component {
+                    workload_settings: vec![WorkloadSetting {
+                        name: "schedule".into(),
+                        description: None,
+                        parameter_type: ParameterType::String,
+                        required: true,
+                        value: Some(serde_json::json!("0 * * * *")),
+                        from_param: None,
+                    }],
+                    ..Default::default()
+                },
+                annotations: None,
+                params: BTreeMap::new(),
+                client: cli,
+                owner_ref: None,
+            },
+        };
+
+        assert_eq!("taskinstance", task.kube_name().as_str());
+        assert_eq!(
+            "ScheduledTask",
+            task.labels().get("oam.dev/workload-type").unwrap()
+        );
+        assert!(task.validate().is_ok());
+        assert_eq!(
+            "0 * * * *",
+            task.cron_job_builder()
+                .unwrap()
+                .to_cron_job()
+                .unwrap()
+                .spec
+                .unwrap()
+                .schedule
+        );
+
+        task.meta.definition.workload_settings[0].value = Some(serde_json::json!("every hour"));
+        assert!(task.validate().is_err());
+
+        task.meta.definition.workload_settings.clear();
+        assert!(task.validate().is_err());
+    }
+
+    #[test]
+    fn test_replicable_task_counts() {
+        let cli = APIClient::new(mock_kube_config());
+        let mut params = BTreeMap::new();
+        params.insert("parallelism".to_string(), serde_json::json!(4));
+        params.insert("completions".to_string(), serde_json::json!(8));
+
+        let mut task = ReplicableTask {
+            meta: WorkloadMetadata {
+                name: "mytask".into(),
+                component_name: "taskrunner".into(),
+                instance_name: "taskinstance".into(),
+                namespace: "tests".into(),
+                definition: Component {
+                    ..Default::default()
+                },
+                annotations: None,
+                params,
+                client: cli,
+                owner_ref: None,
+            },
+        };
+
+        assert!(task.validate().is_ok());
+        let spec = task.job_builder().unwrap().to_job().unwrap().spec.unwrap();
+        assert_eq!(Some(4), spec.parallelism);
+        assert_eq!(Some(8), spec.completions);
+
+        // Parallelism defaults to completions.
+        task.meta.params.remove("parallelism");
+        let spec = task.job_builder().unwrap().to_job().unwrap().spec.unwrap();
+        assert_eq!(Some(8), spec.parallelism);
+        assert_eq!(Some(8), spec.completions);
+
+        task.meta
+            .params
+            .insert("completions".to_string(), serde_json::json!(0));
+        assert!(task.validate().is_err());
+
+        task.meta
+            .params
+            .insert("completions".to_string(), serde_json::json!("many"));
+        assert!(task.validate().is_err());
+    }
+
+    #[test]
+    fn test_replicable_task_completion_mode() {
+        let cli = APIClient::new(mock_kube_config());
+        let mut params = BTreeMap::new();
+        params.insert("completions".to_string(), serde_json::json!(8));
+
+        let mut task = ReplicableTask {
+            meta: WorkloadMetadata {
+                name: "mytask".into(),
+                component_name: "taskrunner".into(),
+                instance_name: "taskinstance".into(),
+                namespace: "tests".into(),
+                definition: Component {
+                    workload_type: REPLICABLE_TASK_NAME.into(),
+                    workload_settings: vec![workload_setting(
+                        "completionMode",
+                        serde_json::json!("Indexed"),
+                    )],
+                    ..Default::default()
+                },
+                annotations: None,
+                params,
+                client: cli,
+                owner_ref: None,
+            },
+        };
+
+        assert!(task.validate().is_ok());
+        let job = task.job_builder().unwrap().to_job_json().unwrap();
+        assert_eq!("Indexed", job["spec"]["completionMode"]);
+        assert_eq!(8, job["spec"]["completions"]);
+
+        task.meta.params.remove("completions");
+        let err = task.validate().expect_err("Indexed requires completions");
+        assert!(err
+            .to_string()
+            .contains("requires the completions parameter"));
+
+        task.meta.definition.workload_settings[0].value = Some(serde_json::json!("Sharded"));
+        assert!(task.validate().is_err());
+
+        task.meta.definition.workload_settings.clear();
+        assert!(task.validate().is_ok());
+        let job = task.job_builder().unwrap().to_job_json().unwrap();
+        assert!(job["spec"].get("completionMode").is_none());
+    }
+
+    #[test]
+    fn test_validate_cron_schedule() {
+        assert!(validate_cron_schedule("*/15 0 1,15 * 1-5").is_ok());
+        assert!(validate_cron_schedule("* * * *").is_err());
+        assert!(validate_cron_schedule("* * * * * *").is_err());
+        assert!(validate_cron_schedule("").is_err());
+    }
+
     /// This mock builds a KubeConfig that will not be able to make any requests.
     fn mock_kube_config() -> Configuration {
         Configuration {
@@ -1,7 +1,7 @@
 use crate::workload_type::statefulset_builder::StatefulsetBuilder;
 use crate::workload_type::{
     workload_builder::DeploymentBuilder, workload_builder::WorkloadMetadata, InstigatorResult,
-    KubeName, StatusResult, ValidationResult, WorkloadType,
+    KubeName, RenderResult, StatusResult, ValidationResult, WorkloadType,
 };
 use std::collections::BTreeMap;
 use log::{warn};
@@ -16,12 +16,15 @@ impl ReplicatedWorker {
     fn labels(&self) -> BTreeMap<String, String> {
         self.meta.labels("Worker")
     }
-    fn add_deployment_builder(&self) -> InstigatorResult {
+    fn deployment_builder(&self) -> DeploymentBuilder {
         DeploymentBuilder::new(self.kube_name(), self.meta.definition.clone())
             .parameter_map(self.meta.params.clone())
             .labels(self.labels())
             .annotations(self.meta.annotations.clone())
             .owner_ref(self.meta.owner_ref.clone())
+    }
+    fn add_deployment_builder(&self) -> InstigatorResult {
+        self.deployment_builder()
             .do_request(self.meta.client.clone(), self.meta.namespace.clone(), "add")
     }
 }
@@ -37,27 +40,31 @@ impl WorkloadType for ReplicatedWorker {
         //pre create config_map
         self.meta.create_config_maps("Worker")?;
         self.add_deployment_builder()?;
+        self.meta.create_pod_disruption_budget()?;
         Ok(())
     }
     fn modify(&self) -> InstigatorResult {
         //TODO update config_map
-        DeploymentBuilder::new(self.kube_name(), self.meta.definition.clone())
-            .parameter_map(self.meta.params.clone())
-            .labels(self.labels())
-            .annotations(self.meta.annotations.clone())
-            .owner_ref(self.meta.owner_ref.clone())
-            .do_request(
-                self.meta.client.clone(),
-                self.meta.namespace.clone(),
-                "modify",
-            )
+        self.deployment_builder().do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "modify",
+        )?;
+        self.meta.apply_pod_disruption_budget()
     }
     fn delete(&self) -> InstigatorResult {
         DeploymentBuilder::new(self.kube_name(), self.meta.definition.clone()).do_request(
             self.meta.client.clone(),
             self.meta.namespace.clone(),
             "delete",
-        )
+        )?;
+        self.meta.delete_pod_disruption_budget()
+    }
+    fn render(&self) -> RenderResult {
+        let mut objects = vec![self.deployment_builder().to_deployment_json()?];
+        objects.extend(self.meta.config_maps_json("Worker")?);
+        objects.extend(self.meta.pod_disruption_budget_json()?);
+        Ok(objects)
     }
     fn status(&self) -> StatusResult {
         let key = "deployment/".to_string() + self.kube_name().as_str();
@@ -106,13 +113,19 @@ impl SingletonWorker {
     fn labels(&self) -> BTreeMap<String, String> {
         self.meta.labels("SingletonWorker")
     }
-    fn add_statefulset_builder(&self) -> InstigatorResult {
+    fn statefulset_builder(&self) -> StatefulsetBuilder {
         StatefulsetBuilder::new(self.kube_name(), self.meta.definition.clone())
             .parameter_map(self.meta.params.clone())
             .labels(self.labels())
             .annotations(self.meta.annotations.clone())
             .owner_ref(self.meta.owner_ref.clone())
-            .do_request(self.meta.client.clone(), self.meta.namespace.clone(), "add")
+    }
+    fn add_statefulset_builder(&self) -> InstigatorResult {
+        self.statefulset_builder().do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "add",
+        )
     }
 }
 
@@ -127,27 +140,15 @@ impl WorkloadType for SingletonWorker {
         //pre create config_map
         self.meta.create_config_maps("SingletonWorker")?;
 
-        StatefulsetBuilder::new(self.kube_name(), self.meta.definition.clone())
-            .parameter_map(self.meta.params.clone())
-            .labels(self.labels())
-            .annotations(self.meta.annotations.clone())
-            .owner_ref(self.meta.owner_ref.clone())
-            .do_request(self.meta.client.clone(), self.meta.namespace.clone(), "add")?;
-
-        Ok(())
+        self.add_statefulset_builder()
     }
     fn modify(&self) -> InstigatorResult {
         //TODO update config_map
-        StatefulsetBuilder::new(self.kube_name(), self.meta.definition.clone())
-            .parameter_map(self.meta.params.clone())
-            .labels(self.labels())
-            .annotations(self.meta.annotations.clone())
-            .owner_ref(self.meta.owner_ref.clone())
-            .do_request(
-                self.meta.client.clone(),
-                self.meta.namespace.clone(),
-                "modify",
-            )
+        self.statefulset_builder().do_request(
+            self.meta.client.clone(),
+            self.meta.namespace.clone(),
+            "modify",
+        )
     }
     fn delete(&self) -> InstigatorResult {
         StatefulsetBuilder::new(self.kube_name(), self.meta.definition.clone()).do_request(
@@ -156,6 +157,11 @@ impl WorkloadType for SingletonWorker {
             "delete",
         )
     }
+    fn render(&self) -> RenderResult {
+        let mut objects = vec![self.statefulset_builder().to_statefulset_json()?];
+        objects.extend(self.meta.config_maps_json("SingletonWorker")?);
+        Ok(objects)
+    }
     fn status(&self) -> StatusResult {
         let key = "statefulset/".to_string() + self.kube_name().as_str();
         let mut resources = BTreeMap::new();
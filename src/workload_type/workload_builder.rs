@@ -1,14 +1,21 @@
+use failure::Error;
 use k8s_openapi::api::apps::v1 as apps;
 use k8s_openapi::api::batch::v1 as batchapi;
+use k8s_openapi::api::batch::v1beta1 as batchbetaapi;
 use k8s_openapi::api::core::v1 as api;
+use k8s_openapi::api::policy::v1beta1 as policy;
 use k8s_openapi::apimachinery::pkg::apis::meta::v1 as meta;
 use kube::api::{DeleteParams, Object, PatchParams, PostParams};
 use kube::client::APIClient;
-use log::info;
+use log::{info, warn};
+use serde::Serialize;
 use std::collections::BTreeMap;
 
-use crate::schematic::component::Component;
-use crate::workload_type::{server::to_config_maps, InstigatorResult, ParamMap};
+use crate::schematic::component::{Component, POD_DISRUPTION_BUDGET_SETTING};
+use crate::workload_type::{
+    server::to_config_maps, InstigatorResult, ParamMap, SINGLETON_SERVER_NAME,
+    STATEFUL_SERVICE_NAME,
+};
 
 /// WorkloadMetadata contains common data about a workload.
 ///
@@ -40,26 +47,15 @@ pub struct WorkloadMetadata {
 
 impl WorkloadMetadata {
     pub fn labels(&self, workload_type: &str) -> BTreeMap<String, String> {
-        let mut labels = BTreeMap::new();
-        labels.insert("app.kubernetes.io/name".to_string(), self.name.clone());
+        let mut labels = self.select_labels();
         labels.insert(
             "oam.dev/workload-type".to_string(),
             workload_type.to_string(),
         );
-        labels.insert(
-            "oam.dev/instance-name".to_string(),
-            self.instance_name.clone(),
-        );
         labels
     }
     pub fn select_labels(&self) -> BTreeMap<String, String> {
-        let mut labels = BTreeMap::new();
-        labels.insert("app.kubernetes.io/name".to_string(), self.name.clone());
-        labels.insert(
-            "oam.dev/instance-name".to_string(),
-            self.instance_name.clone(),
-        );
-        labels
+        instance_labels(self.name.as_str(), self.instance_name.as_str())
     }
 
     pub fn kube_name(&self) -> String {
@@ -73,8 +69,20 @@ impl WorkloadMetadata {
             Some(self.labels(workload_type)),
         )
     }
+    /// Generate the ConfigMaps for volumes with inline content.
+    pub fn to_inline_config_maps(&self, workload_type: &str) -> Result<Vec<api::ConfigMap>, Error> {
+        let mut config_maps = self.definition.to_inline_config_maps()?;
+        for config in config_maps.iter_mut() {
+            if let Some(metadata) = config.metadata.as_mut() {
+                metadata.owner_references = self.owner_ref.clone();
+                metadata.labels = Some(self.labels(workload_type));
+            }
+        }
+        Ok(config_maps)
+    }
     pub fn create_config_maps(&self, workload_type: &str) -> InstigatorResult {
-        let config_maps = self.to_config_maps(workload_type);
+        let mut config_maps = self.to_config_maps(workload_type);
+        config_maps.extend(self.to_inline_config_maps(workload_type)?);
         if !config_maps.is_empty() {
             log::debug!("start to create {} config_maps", config_maps.len());
         }
@@ -89,6 +97,103 @@ impl WorkloadMetadata {
         Ok(())
     }
 
+    /// The PodDisruptionBudget set by the component's `pdb` workload setting, if any.
+    fn pod_disruption_budget(&self) -> Result<Option<policy::PodDisruptionBudget>, Error> {
+        let mut pdb = self
+            .definition
+            .to_pod_disruption_budget(self.instance_name.as_str(), self.params.clone())?;
+        if let Some(metadata) = pdb.as_mut().and_then(|p| p.metadata.as_mut()) {
+            metadata.labels = Some(self.select_labels());
+            metadata.owner_references = self.owner_ref.clone();
+        }
+        Ok(pdb)
+    }
+
+    /// Render the PodDisruptionBudget set by the component's `pdb` workload setting, if any.
+    pub(crate) fn pod_disruption_budget_json(&self) -> Result<Vec<serde_json::Value>, Error> {
+        self.pod_disruption_budget()?
+            .iter()
+            .map(|pdb| serde_json::to_value(pdb).map_err(Error::from))
+            .collect()
+    }
+
+    /// Render the ConfigMaps of the component's configs and of its volumes with inline
+    /// content.
+    pub(crate) fn config_maps_json(
+        &self,
+        workload_type: &str,
+    ) -> Result<Vec<serde_json::Value>, Error> {
+        let mut config_maps = self.to_config_maps(workload_type);
+        config_maps.extend(self.to_inline_config_maps(workload_type)?);
+        config_maps
+            .iter()
+            .map(|c| serde_json::to_value(c).map_err(Error::from))
+            .collect()
+    }
+
+    /// Create the PodDisruptionBudget set by the component's `pdb` workload setting, if any.
+    pub fn create_pod_disruption_budget(&self) -> InstigatorResult {
+        let pdb = match self.pod_disruption_budget()? {
+            Some(pdb) => pdb,
+            None => return Ok(()),
+        };
+        let (req, _) = policy::PodDisruptionBudget::create_namespaced_pod_disruption_budget(
+            self.namespace.as_str(),
+            &pdb,
+            Default::default(),
+        )?;
+        self.client.request::<policy::PodDisruptionBudget>(req)?;
+        Ok(())
+    }
+
+    /// Bring the PodDisruptionBudget in line with the component's `pdb` workload setting
+    /// on modify: patch it, creating it if it does not exist yet, or delete it if the
+    /// setting has been removed.
+    pub fn apply_pod_disruption_budget(&self) -> InstigatorResult {
+        let pdb = match self.pod_disruption_budget()? {
+            Some(pdb) => pdb,
+            None => return self.remove_pod_disruption_budget(),
+        };
+        let (req, _) = policy::PodDisruptionBudget::patch_namespaced_pod_disruption_budget(
+            self.instance_name.as_str(),
+            self.namespace.as_str(),
+            &meta::Patch::StrategicMerge(serde_json::to_value(&pdb)?),
+            Default::default(),
+        )?;
+        match self.client.request::<policy::PodDisruptionBudget>(req) {
+            Ok(_) => Ok(()),
+            Err(e) if e.to_string().contains("NotFound") => self.create_pod_disruption_budget(),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Delete the PodDisruptionBudget, if the component sets one.
+    pub fn delete_pod_disruption_budget(&self) -> InstigatorResult {
+        if self
+            .definition
+            .get_workload_setting(POD_DISRUPTION_BUDGET_SETTING)
+            .is_none()
+        {
+            return Ok(());
+        }
+        self.remove_pod_disruption_budget()
+    }
+
+    /// Delete the PodDisruptionBudget, if there is one. The setting may have been
+    /// removed since the budget was created, so a missing budget is not an error.
+    fn remove_pod_disruption_budget(&self) -> InstigatorResult {
+        let (req, _) = policy::PodDisruptionBudget::delete_namespaced_pod_disruption_budget(
+            self.instance_name.as_str(),
+            self.namespace.as_str(),
+            Default::default(),
+        )?;
+        match self.client.request::<serde_json::Value>(req) {
+            Ok(_) => Ok(()),
+            Err(e) if e.to_string().contains("NotFound") => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     pub fn deployment_status(&self) -> Result<String, kube::Error> {
         let deploy: Object<_, apps::DeploymentStatus> =
             match kube::api::Api::v1Deployment(self.client.clone())
@@ -111,15 +216,76 @@ impl WorkloadMetadata {
         Ok(state)
     }
 
-    pub fn get_workload_setting(&self, key: &str) -> Option<serde_json::Value> {
-        self.definition
-            .workload_settings
-            .iter()
-            .find(|&item| item.name.eq(key))
-            .and_then(|item| item.resolve_param(self.params.clone()))
+    /// get_workload_setting resolves the value of the named workload setting.
+    ///
+    /// A setting that is defined more than once is an error.
+    pub fn get_workload_setting(&self, key: &str) -> Result<Option<serde_json::Value>, Error> {
+        let settings = self.definition.workload_settings_map()?;
+        Ok(settings
+            .get(key)
+            .and_then(|item| item.resolve_param(self.params.clone())))
     }
 }
 
+/// Serialize a workload object, adding the pod spec fields that the Kubernetes API
+/// version Rudr is built against cannot express.
+pub(crate) fn to_workload_json<T: Serialize>(
+    workload: &T,
+    component: &Component,
+    pod_labels: &Labels,
+) -> Result<serde_json::Value, Error> {
+    let mut value = serde_json::to_value(workload)?;
+    let constraints = component.to_topology_spread_constraints(pod_labels);
+    let sidecars = component.sidecar_names();
+    // Deployments, StatefulSets and Jobs hold a pod template, CronJobs a Job template.
+    for pointer in &[
+        "/spec/template/spec",
+        "/spec/jobTemplate/spec/template/spec",
+    ] {
+        let pod_spec = match value.pointer_mut(pointer).and_then(|s| s.as_object_mut()) {
+            Some(pod_spec) => pod_spec,
+            None => continue,
+        };
+        if let Some(constraints) = constraints.clone() {
+            pod_spec.insert("topologySpreadConstraints".to_string(), constraints);
+        }
+        let init_containers = pod_spec
+            .get_mut("initContainers")
+            .and_then(|c| c.as_array_mut())
+            .into_iter()
+            .flatten();
+        for c in init_containers {
+            let is_sidecar = c
+                .get("name")
+                .and_then(|n| n.as_str())
+                .map_or(false, |n| sidecars.contains(&n));
+            if !is_sidecar {
+                continue;
+            }
+            // Sidecars are init containers that keep running for the life of the pod.
+            if let Some(c) = c.as_object_mut() {
+                c.insert(
+                    "restartPolicy".to_string(),
+                    serde_json::Value::String("Always".to_string()),
+                );
+            }
+        }
+    }
+    Ok(value)
+}
+
+/// The labels that select the pods of a component instance: the name of its application
+/// configuration and its own name.
+pub fn instance_labels(name: &str, instance_name: &str) -> Labels {
+    let mut labels = Labels::new();
+    labels.insert("app.kubernetes.io/name".to_string(), name.to_string());
+    labels.insert(
+        "oam.dev/instance-name".to_string(),
+        instance_name.to_string(),
+    );
+    labels
+}
+
 pub fn form_metadata(
     name: String,
     labels: BTreeMap<String, String>,
@@ -135,6 +301,33 @@ pub fn form_metadata(
 
 pub type Labels = BTreeMap<String, String>;
 
+/// Build a workload's pod template from the component's, adding the workload's labels,
+/// annotations and owner references.
+///
+/// The workload's labels take precedence over the standard labels, since the workload
+/// selects its pods by them. The spec hash annotation cannot be overridden.
+pub(crate) fn pod_template_spec(
+    component: &Component,
+    name: &str,
+    labels: &Labels,
+    annotations: &Option<Labels>,
+    owner_ref: &Option<Vec<meta::OwnerReference>>,
+    pod_spec: api::PodSpec,
+) -> api::PodTemplateSpec {
+    let mut template = component.to_pod_template_spec_with(name, pod_spec);
+    if let Some(metadata) = template.metadata.as_mut() {
+        metadata
+            .labels
+            .get_or_insert_with(Labels::new)
+            .extend(labels.clone());
+        let mut pod_annotations = annotations.clone().unwrap_or_default();
+        pod_annotations.extend(metadata.annotations.take().unwrap_or_default());
+        metadata.annotations = Some(pod_annotations);
+        metadata.owner_references = owner_ref.clone();
+    }
+    template
+}
+
 /// DeploymentBuilder builds new deployments specific to Rudr
 ///
 /// This hides many of the details of building a Deployment, exposing only
@@ -188,8 +381,12 @@ impl DeploymentBuilder {
         self
     }
 
-    pub fn to_deployment(&self) -> apps::Deployment {
-        apps::Deployment {
+    pub fn to_deployment(&self) -> Result<apps::Deployment, Error> {
+        let replicas = match self.replicas {
+            Some(replicas) => Some(replicas),
+            None => self.component.replica_count(self.param_vals.clone())?,
+        };
+        Ok(apps::Deployment {
             // TODO: Could make this generic.
             metadata: form_metadata(
                 self.name.clone(),
@@ -197,32 +394,48 @@ impl DeploymentBuilder {
                 self.owner_ref.clone(),
             ),
             spec: Some(apps::DeploymentSpec {
-                replicas: self.replicas,
+                replicas,
                 selector: meta::LabelSelector {
                     match_labels: Some(self.labels.clone()),
                     ..Default::default()
                 },
-                template: api::PodTemplateSpec {
-                    metadata: Some(meta::ObjectMeta {
-                        name: Some(self.name.clone()),
-                        labels: Some(self.labels.clone()),
-                        annotations: self.annotations.clone(),
-                        owner_references: self.owner_ref.clone(),
-                        ..Default::default()
-                    }),
-                    spec: Some(self.component.to_pod_spec_with_policy(
-                        self.param_vals.clone(),
-                        self.restart_policy.clone(),
-                    )),
-                },
+                template: pod_template_spec(
+                    &self.component,
+                    &self.name,
+                    &self.labels,
+                    &self.annotations,
+                    &self.owner_ref,
+                    self.to_pod_spec()?,
+                ),
+                strategy: Some(
+                    self.component
+                        .deployment_strategy(self.param_vals.clone())?,
+                ),
+                min_ready_seconds: self.component.min_ready_seconds(self.param_vals.clone())?,
+                progress_deadline_seconds: self
+                    .component
+                    .progress_deadline_seconds(self.param_vals.clone())?,
                 ..Default::default()
             }),
             ..Default::default()
-        }
+        })
+    }
+
+    fn to_pod_spec(&self) -> Result<api::PodSpec, Error> {
+        let mut pod_spec = self
+            .component
+            .to_pod_spec_with_policy(self.param_vals.clone(), self.restart_policy.clone())?;
+        pod_spec.affinity = self.component.to_affinity(&self.labels);
+        Ok(pod_spec)
+    }
+
+    /// Serialize the Deployment, with the fields `to_workload_json` adds.
+    pub fn to_deployment_json(&self) -> Result<serde_json::Value, Error> {
+        to_workload_json(&self.to_deployment()?, &self.component, &self.labels)
     }
 
     pub fn do_request(self, client: APIClient, namespace: String, phase: &str) -> InstigatorResult {
-        let deployment = self.to_deployment();
+        let deployment = self.to_deployment_json()?;
         match phase {
             "modify" => {
                 let pp = kube::api::PatchParams::default();
@@ -261,6 +474,8 @@ pub(crate) struct JobBuilder {
     restart_policy: String,
     owner_ref: Option<Vec<meta::OwnerReference>>,
     parallelism: Option<i32>,
+    completions: Option<i32>,
+    completion_mode: Option<String>,
     param_vals: ParamMap,
 }
 
@@ -275,6 +490,8 @@ impl JobBuilder {
             restart_policy: "Never".to_string(),
             owner_ref: None,
             parallelism: None,
+            completions: None,
+            completion_mode: None,
             param_vals: BTreeMap::new(),
         }
     }
@@ -311,39 +528,52 @@ impl JobBuilder {
         self.parallelism = Some(count);
         self
     }
+    /// Set the number of completions
+    pub fn completions(mut self, count: i32) -> Self {
+        self.completions = Some(count);
+        self
+    }
+    /// Set the completion mode (NonIndexed or Indexed)
+    pub fn completion_mode(mut self, mode: Option<String>) -> Self {
+        self.completion_mode = mode;
+        self
+    }
 
     fn to_config_maps(&self) -> Vec<api::ConfigMap> {
         let configs = self.component.evaluate_configs(self.param_vals.clone());
         to_config_maps(configs, self.owner_ref.clone(), Some(self.labels.clone()))
     }
 
-    fn to_job(&self) -> batchapi::Job {
-        batchapi::Job {
+    fn to_job_spec(&self) -> Result<batchapi::JobSpec, Error> {
+        Ok(batchapi::JobSpec {
+            backoff_limit: Some(4),
+            parallelism: self.parallelism,
+            completions: self.completions,
+            template: pod_template_spec(
+                &self.component,
+                &self.name,
+                &self.labels,
+                &self.annotations,
+                &self.owner_ref,
+                self.component.to_pod_spec_with_policy(
+                    self.param_vals.clone(),
+                    self.restart_policy.clone(),
+                )?,
+            ),
+            ..Default::default()
+        })
+    }
+
+    pub fn to_job(&self) -> Result<batchapi::Job, Error> {
+        Ok(batchapi::Job {
             metadata: form_metadata(
                 self.name.clone(),
                 self.labels.clone(),
                 self.owner_ref.clone(),
             ),
-            spec: Some(batchapi::JobSpec {
-                backoff_limit: Some(4),
-                parallelism: self.parallelism,
-                template: api::PodTemplateSpec {
-                    metadata: Some(meta::ObjectMeta {
-                        name: Some(self.name.clone()),
-                        labels: Some(self.labels.clone()),
-                        annotations: self.annotations.clone(),
-                        owner_references: self.owner_ref.clone(),
-                        ..Default::default()
-                    }),
-                    spec: Some(self.component.to_pod_spec_with_policy(
-                        self.param_vals.clone(),
-                        self.restart_policy.clone(),
-                    )),
-                },
-                ..Default::default()
-            }),
+            spec: Some(self.to_job_spec()?),
             ..Default::default()
-        }
+        })
     }
 
     pub fn get_status(self, client: APIClient, namespace: String) -> String {
@@ -370,8 +600,23 @@ impl JobBuilder {
         "succeeded".to_string()
     }
 
+    /// Serialize the Job with the fields the Kubernetes API version Rudr is built against
+    /// cannot express, such as the completion mode.
+    pub fn to_job_json(&self) -> Result<serde_json::Value, Error> {
+        let mut job = to_workload_json(&self.to_job()?, &self.component, &self.labels)?;
+        if let Some(mode) = self.completion_mode.as_ref() {
+            if let Some(spec) = job.get_mut("spec").and_then(|s| s.as_object_mut()) {
+                spec.insert(
+                    "completionMode".to_string(),
+                    serde_json::Value::String(mode.clone()),
+                );
+            }
+        }
+        Ok(job)
+    }
+
     pub fn do_request(self, client: APIClient, namespace: String, phase: &str) -> InstigatorResult {
-        let job = self.to_job();
+        let job = self.to_job_json()?;
         match phase {
             "modify" => {
                 //TODO support modify config_map
@@ -409,12 +654,178 @@ impl JobBuilder {
     }
 }
 
+/// CronJobBuilder builds new cron jobs specific to Rudr
+///
+/// The Job template is built the same way as JobBuilder builds a Job, and is
+/// run on the given cron schedule.
+pub(crate) struct CronJobBuilder {
+    component: Component,
+    labels: Labels,
+    annotations: Option<Labels>,
+    name: String,
+    schedule: String,
+    concurrency_policy: Option<String>,
+    restart_policy: String,
+    owner_ref: Option<Vec<meta::OwnerReference>>,
+    param_vals: ParamMap,
+}
+
+impl CronJobBuilder {
+    /// Create a CronJobBuilder
+    pub fn new(instance_name: String, component: Component, schedule: String) -> Self {
+        CronJobBuilder {
+            component,
+            name: instance_name,
+            labels: Labels::new(),
+            annotations: None,
+            schedule,
+            concurrency_policy: None,
+            restart_policy: "Never".to_string(),
+            owner_ref: None,
+            param_vals: BTreeMap::new(),
+        }
+    }
+    /// Add labels
+    pub fn labels(mut self, labels: Labels) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Add annotations.
+    ///
+    /// In Kubernetes, these will be added to the pod specification.
+    pub fn annotations(mut self, annotations: Option<Labels>) -> Self {
+        self.annotations = annotations;
+        self
+    }
+
+    pub fn parameter_map(mut self, param_vals: ParamMap) -> Self {
+        self.param_vals = param_vals;
+        self
+    }
+    /// Set the concurrency policy (Allow, Forbid or Replace)
+    pub fn concurrency_policy(mut self, policy: Option<String>) -> Self {
+        self.concurrency_policy = policy;
+        self
+    }
+    /// Set the restart policy
+    pub fn restart_policy(mut self, policy: String) -> Self {
+        self.restart_policy = policy;
+        self
+    }
+    /// Set the owner refence for the cron job and the pod
+    pub fn owner_ref(mut self, owner: Option<Vec<meta::OwnerReference>>) -> Self {
+        self.owner_ref = owner;
+        self
+    }
+
+    pub fn to_cron_job(&self) -> Result<batchbetaapi::CronJob, Error> {
+        let job = JobBuilder::new(self.name.clone(), self.component.clone())
+            .parameter_map(self.param_vals.clone())
+            .labels(self.labels.clone())
+            .annotations(self.annotations.clone())
+            .owner_ref(self.owner_ref.clone())
+            .restart_policy(self.restart_policy.clone());
+        Ok(batchbetaapi::CronJob {
+            metadata: form_metadata(
+                self.name.clone(),
+                self.labels.clone(),
+                self.owner_ref.clone(),
+            ),
+            spec: Some(batchbetaapi::CronJobSpec {
+                schedule: self.schedule.clone(),
+                concurrency_policy: self.concurrency_policy.clone(),
+                job_template: batchbetaapi::JobTemplateSpec {
+                    metadata: Some(meta::ObjectMeta {
+                        labels: Some(self.labels.clone()),
+                        ..Default::default()
+                    }),
+                    spec: Some(job.to_job_spec()?),
+                },
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    }
+
+    pub fn get_status(self, client: APIClient, namespace: String) -> String {
+        let (req, _) = match batchbetaapi::CronJob::read_namespaced_cron_job_status(
+            self.name.as_str(),
+            namespace.as_str(),
+            Default::default(),
+        ) {
+            Ok(req) => req,
+            Err(e) => return e.to_string(),
+        };
+        let cron_job = match client.request::<batchbetaapi::CronJob>(req) {
+            Ok(cron_job) => cron_job,
+            Err(e) => return e.to_string(),
+        };
+        let active = cron_job
+            .status
+            .and_then(|s| s.active)
+            .map_or(0, |a| a.len());
+        if active > 0 {
+            return "running".to_string();
+        }
+        "scheduled".to_string()
+    }
+
+    /// Serialize the CronJob, with the fields `to_workload_json` adds.
+    pub fn to_cron_job_json(&self) -> Result<serde_json::Value, Error> {
+        to_workload_json(&self.to_cron_job()?, &self.component, &self.labels)
+    }
+
+    pub fn do_request(self, client: APIClient, namespace: String, phase: &str) -> InstigatorResult {
+        let cron_job = self.to_cron_job()?;
+        match phase {
+            "modify" => {
+                let values = self.to_cron_job_json()?;
+                let (req, _) = batchbetaapi::CronJob::patch_namespaced_cron_job(
+                    self.name.as_str(),
+                    namespace.as_str(),
+                    &meta::Patch::StrategicMerge(values),
+                    Default::default(),
+                )?;
+                client.request::<batchbetaapi::CronJob>(req)?;
+                Ok(())
+            }
+            "delete" => {
+                let (req, _) = batchbetaapi::CronJob::delete_namespaced_cron_job(
+                    self.name.as_str(),
+                    namespace.as_str(),
+                    Default::default(),
+                )?;
+                client.request_status::<batchbetaapi::CronJob>(req)?;
+                Ok(())
+            }
+            _ => {
+                let (mut req, _) = batchbetaapi::CronJob::create_namespaced_cron_job(
+                    namespace.as_str(),
+                    &cron_job,
+                    Default::default(),
+                )?;
+                // Send the fields the typed CronJob cannot hold as well.
+                *req.body_mut() = serde_json::to_vec(&to_workload_json(
+                    &cron_job,
+                    &self.component,
+                    &self.labels,
+                )?)?;
+                client.request::<batchbetaapi::CronJob>(req)?;
+                Ok(())
+            }
+        }
+    }
+}
+
 pub struct ServiceBuilder {
     component: Component,
     labels: Labels,
     selector: Labels,
     name: String,
     owner_ref: Option<Vec<meta::OwnerReference>>,
+    target_port_by_name: bool,
+    param_vals: ParamMap,
 }
 
 impl ServiceBuilder {
@@ -425,6 +836,8 @@ impl ServiceBuilder {
             labels: Labels::new(),
             selector: Labels::new(),
             owner_ref: None,
+            target_port_by_name: false,
+            param_vals: BTreeMap::new(),
         }
     }
     pub fn labels(mut self, labels: Labels) -> Self {
@@ -435,26 +848,116 @@ impl ServiceBuilder {
         self.selector = labels;
         self
     }
+    pub fn parameter_map(mut self, param_vals: ParamMap) -> Self {
+        self.param_vals = param_vals;
+        self
+    }
     pub fn owner_ref(mut self, owner_ref: Option<Vec<meta::OwnerReference>>) -> Self {
         self.owner_ref = owner_ref;
         self
     }
-    fn to_service(&self) -> Option<api::Service> {
-        self.component.clone().listening_port().and_then(|port| {
-            Some(api::Service {
-                metadata: form_metadata(
-                    self.name.clone(),
-                    self.labels.clone(),
-                    self.owner_ref.clone(),
-                ),
-                spec: Some(api::ServiceSpec {
-                    selector: Some(self.selector.clone()),
-                    ports: Some(vec![port.to_service_port()]),
-                    ..Default::default()
+    /// Reference the backing container port by name instead of by number.
+    ///
+    /// This keeps the Service valid when the container port is renumbered.
+    pub fn target_port_by_name(mut self, by_name: bool) -> Self {
+        self.target_port_by_name = by_name;
+        self
+    }
+    /// Build a Service exposing every container port, each with its own protocol.
+    ///
+    /// Returns None when the component does not listen on any port, and an error when a
+    /// port does not resolve to a valid port number.
+    pub fn to_service(&self) -> Result<Option<api::Service>, Error> {
+        let service_ports: Vec<api::ServicePort> = self
+            .component
+            .listening_ports()
+            .iter()
+            .map(|port| {
+                if self.target_port_by_name {
+                    port.to_named_service_port(&self.param_vals)
+                } else {
+                    port.to_service_port(&self.param_vals)
+                }
+            })
+            .collect::<Result<_, Error>>()?;
+        if service_ports.is_empty() {
+            return Ok(None);
+        }
+        let cluster_ip = if self.component.is_headless(self.param_vals.clone()) {
+            if self.component.workload_type != SINGLETON_SERVER_NAME
+                && self.component.workload_type != STATEFUL_SERVICE_NAME
+            {
+                warn!(
+                    "headless Service requested for {} with workload type {}; headless Services are intended for SingletonServer and StatefulService",
+                    self.name, self.component.workload_type
+                );
+            }
+            Some("None".to_string())
+        } else {
+            None
+        };
+        let session_affinity = self.component.session_affinity(self.param_vals.clone())?;
+        let session_affinity_config = self
+            .component
+            .session_affinity_timeout(self.param_vals.clone())?
+            .map(|timeout| api::SessionAffinityConfig {
+                client_ip: Some(api::ClientIPConfig {
+                    timeout_seconds: Some(timeout),
                 }),
+            });
+        let type_ = self.component.service_type(self.param_vals.clone())?;
+        let external_traffic_policy = self
+            .component
+            .external_traffic_policy(self.param_vals.clone())?;
+        Ok(Some(api::Service {
+            metadata: form_metadata(
+                self.name.clone(),
+                self.labels.clone(),
+                self.owner_ref.clone(),
+            ),
+            spec: Some(api::ServiceSpec {
+                selector: Some(self.selector.clone()),
+                ports: Some(service_ports),
+                cluster_ip,
+                session_affinity,
+                session_affinity_config,
+                type_,
+                external_traffic_policy,
                 ..Default::default()
-            })
-        })
+            }),
+            ..Default::default()
+        }))
+    }
+    /// Render the Service as JSON, adding the `appProtocol` of each port.
+    ///
+    /// Kubernetes 1.15 has no typed field for it, so it is set on the JSON instead. The
+    /// exposed ports are validated first, since the API server rejects the whole Service
+    /// for a duplicate or missing port name.
+    pub fn to_service_json(&self) -> Result<Option<serde_json::Value>, Error> {
+        self.component
+            .validate_service_ports(self.param_vals.clone())?;
+        let svc = match self.to_service()? {
+            Some(svc) => svc,
+            None => return Ok(None),
+        };
+        let mut json = serde_json::to_value(&svc)?;
+        if let Some(ports) = json
+            .pointer_mut("/spec/ports")
+            .and_then(|p| p.as_array_mut())
+        {
+            // Service ports are rendered in the order of the listening ports.
+            for (rendered, port) in ports.iter_mut().zip(self.component.listening_ports()) {
+                if let (Some(obj), Some(protocol)) =
+                    (rendered.as_object_mut(), port.app_protocol.as_ref())
+                {
+                    obj.insert(
+                        "appProtocol".to_string(),
+                        serde_json::Value::String(protocol.clone()),
+                    );
+                }
+            }
+        }
+        Ok(Some(json))
     }
     pub fn get_status(self, client: APIClient, namespace: String) -> Result<String, kube::Error> {
         match kube::api::Api::v1Service(client)
@@ -472,7 +975,7 @@ impl ServiceBuilder {
         }
     }
     pub fn do_request(self, client: APIClient, namespace: String, phase: &str) -> InstigatorResult {
-        match self.to_service() {
+        match self.to_service_json()? {
             Some(svc) => {
                 log::debug!("Service:\n{}", serde_json::to_string_pretty(&svc).unwrap());
                 match phase {
@@ -480,7 +983,7 @@ impl ServiceBuilder {
                         let pp = PatchParams::default();
                         kube::api::Api::v1Service(client)
                             .within(namespace.as_str())
-                            .patch(self.name.as_str(), &pp, serde_json::to_vec(&svc.spec)?)?;
+                            .patch(self.name.as_str(), &pp, serde_json::to_vec(&svc["spec"])?)?;
                         Ok(())
                     }
                     "delete" => {
@@ -510,9 +1013,14 @@ impl ServiceBuilder {
 
 #[cfg(test)]
 mod test {
-    use crate::schematic::component::{Component, Container, Port, PortProtocol};
+    use crate::schematic::component::{
+        AntiAffinity, AntiAffinityMode, Component, Container, Port, PortProtocol,
+        TopologySpreadConstraint, SPEC_HASH_ANNOTATION,
+    };
+    use crate::schematic::component_test::workload_setting;
     use crate::workload_type::workload_builder::*;
     use k8s_openapi::apimachinery::pkg::apis::meta::v1::OwnerReference;
+    use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
     use kube::config::Configuration;
 
     #[test]
@@ -560,6 +1068,16 @@ mod test {
                 .get("app.kubernetes.io/name")
                 .expect("app name")
         );
+
+        let mut wmd = wmd;
+        let setting = workload_setting("schedule", serde_json::json!("0 * * * *"));
+        wmd.definition.workload_settings = vec![setting.clone()];
+        assert_eq!(
+            Some(serde_json::json!("0 * * * *")),
+            wmd.get_workload_setting("schedule").expect("setting")
+        );
+        wmd.definition.workload_settings = vec![setting.clone(), setting];
+        assert!(wmd.get_workload_setting("schedule").is_err());
     }
 
     #[test]
@@ -572,7 +1090,8 @@ mod test {
             .labels(skeleton_labels())
             .annotations(Some(annotations))
             .owner_ref(skeleton_owner_ref())
-            .to_deployment();
+            .to_deployment()
+            .expect("deployment");
         assert_eq!(
             deployment
                 .metadata
@@ -619,6 +1138,116 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_deployment_strategy() {
+        let strategy = |value: serde_json::Value| {
+            let mut component = skeleton_component();
+            component.workload_settings = vec![workload_setting("deploymentStrategy", value)];
+            component
+        };
+
+        let recreate = strategy(serde_json::json!({"type": "Recreate"}));
+        let spec = DeploymentBuilder::new("test".into(), recreate)
+            .to_deployment()
+            .expect("deployment")
+            .spec
+            .and_then(|s| s.strategy)
+            .expect("strategy");
+        assert_eq!(Some("Recreate".to_string()), spec.type_);
+        assert!(spec.rolling_update.is_none());
+
+        let rolling = strategy(serde_json::json!({
+            "type": "RollingUpdate",
+            "maxSurge": 2,
+            "maxUnavailable": "0%"
+        }));
+        let spec = DeploymentBuilder::new("test".into(), rolling)
+            .to_deployment()
+            .expect("deployment")
+            .spec
+            .and_then(|s| s.strategy)
+            .expect("strategy");
+        assert_eq!(Some("RollingUpdate".to_string()), spec.type_);
+        let rolling_update = spec.rolling_update.expect("rolling update");
+        assert_eq!(Some(IntOrString::Int(2)), rolling_update.max_surge);
+        assert_eq!(
+            Some(IntOrString::String("0%".into())),
+            rolling_update.max_unavailable
+        );
+
+        let spec = DeploymentBuilder::new("test".into(), skeleton_component())
+            .to_deployment()
+            .expect("deployment")
+            .spec
+            .and_then(|s| s.strategy)
+            .expect("strategy");
+        let rolling_update = spec.rolling_update.expect("rolling update");
+        assert_eq!(
+            Some(IntOrString::String("25%".into())),
+            rolling_update.max_surge
+        );
+        assert_eq!(
+            Some(IntOrString::String("25%".into())),
+            rolling_update.max_unavailable
+        );
+
+        let stuck = strategy(serde_json::json!({"maxSurge": 0, "maxUnavailable": "0%"}));
+        assert!(stuck.validate_workload_settings(BTreeMap::new()).is_err());
+        let recreate_with_surge = strategy(serde_json::json!({"type": "Recreate", "maxSurge": 1}));
+        assert!(recreate_with_surge
+            .validate_workload_settings(BTreeMap::new())
+            .is_err());
+        let unknown = strategy(serde_json::json!({"type": "BlueGreen"}));
+        assert!(unknown.validate_workload_settings(BTreeMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_deployment_rollout_timing() {
+        let timing = |min_ready: i32, deadline: Option<i32>| {
+            let mut component = skeleton_component();
+            component.workload_settings = vec![workload_setting(
+                "minReadySeconds",
+                serde_json::json!(min_ready),
+            )];
+            component.workload_settings.extend(
+                deadline
+                    .into_iter()
+                    .map(|d| workload_setting("progressDeadlineSeconds", serde_json::json!(d))),
+            );
+            component
+        };
+
+        let component = timing(10, Some(120));
+        assert!(component
+            .validate_workload_settings(BTreeMap::new())
+            .is_ok());
+        let spec = DeploymentBuilder::new("test".into(), component)
+            .to_deployment()
+            .expect("deployment")
+            .spec
+            .expect("spec");
+        assert_eq!(Some(10), spec.min_ready_seconds);
+        assert_eq!(Some(120), spec.progress_deadline_seconds);
+
+        let spec = DeploymentBuilder::new("test".into(), skeleton_component())
+            .to_deployment()
+            .expect("deployment")
+            .spec
+            .expect("spec");
+        assert_eq!(None, spec.min_ready_seconds);
+        assert_eq!(None, spec.progress_deadline_seconds);
+
+        assert!(timing(120, Some(120))
+            .validate_workload_settings(BTreeMap::new())
+            .is_err());
+        assert!(timing(900, None)
+            .validate_workload_settings(BTreeMap::new())
+            .is_err());
+        assert!(timing(-1, None)
+            .validate_workload_settings(BTreeMap::new())
+            .is_err());
+    }
+
     #[test]
     fn test_job_builder() {
         let mut annotations = Labels::new();
@@ -630,7 +1259,8 @@ mod test {
             .restart_policy("OnError".into())
             .owner_ref(skeleton_owner_ref())
             .parallelism(2)
-            .to_job();
+            .to_job()
+            .expect("job");
         assert_eq!(
             job.metadata
                 .clone()
@@ -673,6 +1303,42 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_cron_job_builder() {
+        let cron_job = CronJobBuilder::new(
+            "testcron".into(),
+            skeleton_component(),
+            "*/5 * * * *".into(),
+        )
+        .labels(skeleton_labels())
+        .concurrency_policy(Some("Forbid".into()))
+        .owner_ref(skeleton_owner_ref())
+        .to_cron_job()
+        .expect("cron job");
+        let spec = cron_job.spec.expect("spec");
+        assert_eq!("*/5 * * * *", spec.schedule);
+        assert_eq!(Some("Forbid".to_string()), spec.concurrency_policy);
+        assert_eq!(
+            Some("Never".to_string()),
+            spec.job_template
+                .spec
+                .expect("job spec")
+                .template
+                .spec
+                .expect("pod spec")
+                .restart_policy
+        );
+        assert_eq!(
+            2,
+            cron_job
+                .metadata
+                .expect("metadata")
+                .labels
+                .expect("labels")
+                .len()
+        );
+    }
+
     #[test]
     fn test_service_builder() {
         let svc = ServiceBuilder::new("test".into(), skeleton_component())
@@ -680,6 +1346,7 @@ mod test {
             .select_labels(skeleton_select_labels())
             .owner_ref(skeleton_owner_ref())
             .to_service()
+            .expect("service must render")
             .expect("service");
         assert_eq!(
             svc.metadata
@@ -710,6 +1377,302 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_service_builder_target_port_by_name() {
+        let svc = ServiceBuilder::new("test".into(), skeleton_component())
+            .labels(skeleton_labels())
+            .select_labels(skeleton_select_labels())
+            .target_port_by_name(true)
+            .to_service()
+            .expect("service must render")
+            .expect("service");
+        let ports = svc.spec.expect("spec").ports.expect("ports");
+        assert_eq!(80, ports[0].port);
+        assert_eq!(
+            Some(IntOrString::String("http".into())),
+            ports[0].target_port
+        );
+    }
+
+    #[test]
+    fn test_service_builder_headless() {
+        let mut component = skeleton_component();
+        component.workload_type = SINGLETON_SERVER_NAME.into();
+        component.workload_settings = vec![workload_setting("headless", serde_json::json!(true))];
+        let svc = ServiceBuilder::new("test".into(), component)
+            .labels(skeleton_labels())
+            .select_labels(skeleton_select_labels())
+            .to_service()
+            .expect("service must render")
+            .expect("service");
+        assert_eq!(Some("None".to_string()), svc.spec.expect("spec").cluster_ip);
+
+        let svc = ServiceBuilder::new("test".into(), skeleton_component())
+            .labels(skeleton_labels())
+            .to_service()
+            .expect("service must render")
+            .expect("service");
+        assert_eq!(None, svc.spec.expect("spec").cluster_ip);
+    }
+
+    #[test]
+    fn test_service_builder_session_affinity() {
+        let mut component = skeleton_component();
+        component.workload_settings = vec![
+            workload_setting("sessionAffinity", serde_json::json!("ClientIP")),
+            workload_setting("sessionAffinityTimeoutSeconds", serde_json::json!(3600)),
+        ];
+        let spec = ServiceBuilder::new("test".into(), component)
+            .labels(skeleton_labels())
+            .select_labels(skeleton_select_labels())
+            .to_service()
+            .expect("service must render")
+            .expect("service")
+            .spec
+            .expect("spec");
+        assert_eq!(Some("ClientIP".to_string()), spec.session_affinity);
+        assert_eq!(
+            Some(3600),
+            spec.session_affinity_config
+                .and_then(|c| c.client_ip)
+                .and_then(|c| c.timeout_seconds)
+        );
+
+        let spec = ServiceBuilder::new("test".into(), skeleton_component())
+            .to_service()
+            .expect("service must render")
+            .expect("service")
+            .spec
+            .expect("spec");
+        assert_eq!(None, spec.session_affinity);
+        assert_eq!(None, spec.session_affinity_config);
+
+        // An invalid setting fails the build rather than being left out.
+        let mut sticky = skeleton_component();
+        sticky.workload_settings = vec![workload_setting(
+            "sessionAffinity",
+            serde_json::json!("Cookie"),
+        )];
+        assert!(ServiceBuilder::new("test".into(), sticky)
+            .to_service()
+            .is_err());
+    }
+
+    #[test]
+    fn test_service_builder_load_balancer() {
+        let mut component = skeleton_component();
+        component.workload_settings = vec![
+            workload_setting("serviceType", serde_json::json!("LoadBalancer")),
+            workload_setting("externalTrafficPolicy", serde_json::json!("Local")),
+        ];
+        let spec = ServiceBuilder::new("test".into(), component)
+            .labels(skeleton_labels())
+            .select_labels(skeleton_select_labels())
+            .to_service()
+            .expect("service must render")
+            .expect("service")
+            .spec
+            .expect("spec");
+        assert_eq!(Some("LoadBalancer".to_string()), spec.type_);
+        assert_eq!(Some("Local".to_string()), spec.external_traffic_policy);
+
+        let spec = ServiceBuilder::new("test".into(), skeleton_component())
+            .to_service()
+            .expect("service must render")
+            .expect("service")
+            .spec
+            .expect("spec");
+        assert_eq!(None, spec.type_);
+        assert_eq!(None, spec.external_traffic_policy);
+    }
+
+    #[test]
+    fn test_pod_spec_hash_annotation() {
+        let component = skeleton_component();
+        let annotations = DeploymentBuilder::new("test".into(), component.clone())
+            .to_deployment()
+            .expect("deployment")
+            .spec
+            .and_then(|s| s.template.metadata)
+            .and_then(|m| m.annotations)
+            .expect("pod annotations");
+        assert_eq!(
+            Some(&component.spec_hash()),
+            annotations.get(SPEC_HASH_ANNOTATION)
+        );
+    }
+
+    #[test]
+    fn test_required_anti_affinity() {
+        let mut component = skeleton_component();
+        component.anti_affinity = Some(AntiAffinity {
+            mode: AntiAffinityMode::Required,
+            topology_key: "kubernetes.io/hostname".into(),
+        });
+        let pod_spec = DeploymentBuilder::new("test".into(), component)
+            .labels(skeleton_labels())
+            .to_deployment()
+            .expect("deployment")
+            .spec
+            .and_then(|s| s.template.spec)
+            .expect("pod spec");
+        let anti_affinity = pod_spec
+            .affinity
+            .and_then(|a| a.pod_anti_affinity)
+            .expect("pod anti-affinity");
+        assert!(anti_affinity
+            .preferred_during_scheduling_ignored_during_execution
+            .is_none());
+        let terms = anti_affinity
+            .required_during_scheduling_ignored_during_execution
+            .expect("required terms");
+        assert_eq!(1, terms.len());
+        assert_eq!("kubernetes.io/hostname", terms[0].topology_key);
+        assert_eq!(
+            Some(skeleton_labels()),
+            terms[0].label_selector.clone().and_then(|s| s.match_labels)
+        );
+
+        let pod_spec = DeploymentBuilder::new("test".into(), skeleton_component())
+            .to_deployment()
+            .expect("deployment")
+            .spec
+            .and_then(|s| s.template.spec)
+            .expect("pod spec");
+        assert!(pod_spec.affinity.is_none());
+    }
+
+    #[test]
+    fn test_topology_spread_constraints() {
+        let mut component = skeleton_component();
+        component.topology_spread_constraints = Some(vec![TopologySpreadConstraint {
+            max_skew: 1,
+            topology_key: "topology.kubernetes.io/zone".into(),
+            when_unsatisfiable: "DoNotSchedule".into(),
+            label_selector: None,
+        }]);
+        assert!(component.validate().is_ok());
+        let deployment = DeploymentBuilder::new("test".into(), component.clone())
+            .labels(skeleton_labels())
+            .to_deployment()
+            .expect("deployment");
+        let value =
+            to_workload_json(&deployment, &component, &skeleton_labels()).expect("deployment json");
+        assert_eq!(
+            serde_json::json!([{
+                "maxSkew": 1,
+                "topologyKey": "topology.kubernetes.io/zone",
+                "whenUnsatisfiable": "DoNotSchedule",
+                "labelSelector": {
+                    "matchLabels": skeleton_labels(),
+                },
+            }]),
+            value["spec"]["template"]["spec"]["topologySpreadConstraints"]
+        );
+
+        let plain = to_workload_json(&deployment, &skeleton_component(), &skeleton_labels())
+            .expect("deployment json");
+        assert!(plain["spec"]["template"]["spec"]
+            .get("topologySpreadConstraints")
+            .is_none());
+
+        component.topology_spread_constraints = Some(vec![TopologySpreadConstraint {
+            max_skew: 0,
+            topology_key: "topology.kubernetes.io/zone".into(),
+            when_unsatisfiable: "DoNotSchedule".into(),
+            label_selector: None,
+        }]);
+        assert!(component.validate().is_err());
+    }
+
+    #[test]
+    fn test_service_builder_udp_port() {
+        let mut component = skeleton_component();
+        component.containers[0].ports = vec![Port {
+            name: "syslog".into(),
+            container_port: Some(IntOrString::Int(514)),
+            protocol: PortProtocol::UDP,
+            app_protocol: None,
+            container_port_range: None,
+        }];
+        let svc = ServiceBuilder::new("test".into(), component)
+            .labels(skeleton_labels())
+            .select_labels(skeleton_select_labels())
+            .to_service()
+            .expect("service must render")
+            .expect("service");
+        let ports = svc.spec.expect("spec").ports.expect("ports");
+        assert_eq!(1, ports.len());
+        assert_eq!(Some("UDP".to_string()), ports[0].protocol);
+        assert_eq!(514, ports[0].port);
+    }
+
+    #[test]
+    fn test_service_builder_mixed_protocols() {
+        let mut component = skeleton_component();
+        component.containers[0].ports = vec![
+            Port {
+                name: "dns-tcp".into(),
+                container_port: Some(IntOrString::Int(53)),
+                protocol: PortProtocol::TCP,
+                app_protocol: None,
+                container_port_range: None,
+            },
+            Port {
+                name: "dns-udp".into(),
+                container_port: Some(IntOrString::Int(53)),
+                protocol: PortProtocol::UDP,
+                app_protocol: None,
+                container_port_range: None,
+            },
+        ];
+        let svc = ServiceBuilder::new("test".into(), component)
+            .labels(skeleton_labels())
+            .select_labels(skeleton_select_labels())
+            .to_service()
+            .expect("service must render")
+            .expect("service");
+        let ports = svc.spec.expect("spec").ports.expect("ports");
+        let protocols: Vec<(Option<String>, Option<String>)> =
+            ports.into_iter().map(|p| (p.name, p.protocol)).collect();
+        assert_eq!(
+            vec![
+                (Some("dns-tcp".to_string()), Some("TCP".to_string())),
+                (Some("dns-udp".to_string()), Some("UDP".to_string())),
+            ],
+            protocols
+        );
+    }
+
+    #[test]
+    fn test_service_builder_app_protocol() {
+        let mut component = skeleton_component();
+        component.containers[0].ports = vec![
+            Port {
+                name: "grpc".into(),
+                container_port: Some(IntOrString::Int(9090)),
+                protocol: PortProtocol::TCP,
+                app_protocol: Some("grpc".into()),
+                container_port_range: None,
+            },
+            Port {
+                name: "metrics".into(),
+                container_port: Some(IntOrString::Int(9100)),
+                protocol: PortProtocol::TCP,
+                app_protocol: None,
+                container_port_range: None,
+            },
+        ];
+        let svc = ServiceBuilder::new("test".into(), component)
+            .labels(skeleton_labels())
+            .select_labels(skeleton_select_labels())
+            .to_service_json()
+            .expect("service json")
+            .expect("service");
+        assert_eq!("grpc", svc["spec"]["ports"][0]["appProtocol"]);
+        assert!(svc["spec"]["ports"][1].get("appProtocol").is_none());
+    }
+
     #[test]
     fn test_form_metadata() {
         let mut labels = BTreeMap::new();
@@ -741,16 +1704,31 @@ mod test {
                 args: None,
                 image: "test/foo:latest".into(),
                 image_pull_secret: None,
+                image_pull_policy: None,
                 liveness_probe: None,
                 readiness_probe: None,
-                resources: Default::default(),
+                lifecycle: None,
+                resources: None,
             }],
+            init_containers: vec![],
+            sidecars: vec![],
             workload_settings: vec![],
+            topology_spread_constraints: None,
+            anti_affinity: None,
+            termination_grace_period_seconds: None,
+            default_resources: None,
+            runtime_class_name: None,
+            priority_class_name: None,
+            host_network: false,
+            host_pid: false,
+            host_ipc: false,
+            automount_service_account_token: None,
         };
         assert!(ServiceBuilder::new("test".into(), c)
             .labels(skeleton_labels())
             .owner_ref(skeleton_owner_ref())
             .to_service()
+            .expect("service must render")
             .is_none());
     }
 
@@ -774,9 +1752,11 @@ mod test {
             containers: vec![Container {
                 name: "foo".into(),
                 ports: vec![Port {
-                    container_port: 80,
+                    container_port: Some(IntOrString::Int(80)),
                     name: "http".into(),
                     protocol: PortProtocol::TCP,
+                    app_protocol: None,
+                    container_port_range: None,
                 }],
                 cmd: None,
                 args: None,
@@ -784,11 +1764,25 @@ mod test {
                 config: None,
                 image: "test/foo:latest".into(),
                 image_pull_secret: None,
+                image_pull_policy: None,
                 liveness_probe: None,
                 readiness_probe: None,
-                resources: Default::default(),
+                lifecycle: None,
+                resources: None,
             }],
+            init_containers: vec![],
+            sidecars: vec![],
             workload_settings: vec![],
+            topology_spread_constraints: None,
+            anti_affinity: None,
+            termination_grace_period_seconds: None,
+            default_resources: None,
+            runtime_class_name: None,
+            priority_class_name: None,
+            host_network: false,
+            host_pid: false,
+            host_ipc: false,
+            automount_service_account_token: None,
         }
     }
     fn skeleton_owner_ref() -> Option<Vec<OwnerReference>> {